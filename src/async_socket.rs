@@ -0,0 +1,146 @@
+use std::io;
+use std::mem::MaybeUninit;
+use std::task::{Context, Poll};
+
+use tokio::io::unix::{AsyncFd, AsyncFdReadyGuard};
+
+use crate::Socket;
+
+/// A [`Socket`] registered with the Tokio runtime.
+///
+/// This provides the readiness hooks needed to build asynchronous types on top
+/// of a socket, while [`AsyncSocket::get_ref`] gives access to all options of
+/// the [`Socket`]. It's a thin wrapper around Tokio's [`AsyncFd`].
+///
+/// This type is only available with the `tokio` feature, on Unix.
+///
+/// [`AsyncFd`]: https://docs.rs/tokio/1/tokio/io/unix/struct.AsyncFd.html
+///
+/// # Examples
+///
+/// ```no_run
+/// # #[allow(dead_code)]
+/// # async fn f() -> std::io::Result<()> {
+/// use socket2::{AsyncSocket, Domain, Socket, Type};
+///
+/// let socket = Socket::new(Domain::IPV4, Type::DGRAM, None)?;
+/// socket.bind(&"127.0.0.1:0".parse::<std::net::SocketAddr>().unwrap().into())?;
+/// let socket = AsyncSocket::new(socket)?;
+/// socket.get_ref().set_broadcast(true)?;
+///
+/// let mut buf = [std::mem::MaybeUninit::new(0); 1500];
+/// let n = socket.recv(&mut buf).await?;
+/// println!("received {} bytes", n);
+/// # Ok(()) }
+/// ```
+#[derive(Debug)]
+pub struct AsyncSocket {
+    inner: AsyncFd<Socket>,
+}
+
+impl AsyncSocket {
+    /// Puts `socket` in non-blocking mode and registers it with the Tokio
+    /// runtime.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if it's not called from within a Tokio runtime
+    /// with I/O enabled.
+    pub fn new(socket: Socket) -> io::Result<AsyncSocket> {
+        socket.set_nonblocking(true)?;
+        AsyncFd::new(socket).map(|inner| AsyncSocket { inner })
+    }
+
+    /// Returns a reference to the underlying socket.
+    pub fn get_ref(&self) -> &Socket {
+        self.inner.get_ref()
+    }
+
+    /// Deregisters the socket from the Tokio runtime and returns it.
+    ///
+    /// The socket is left in non-blocking mode.
+    pub fn into_inner(self) -> Socket {
+        self.inner.into_inner()
+    }
+
+    /// Polls for read readiness.
+    ///
+    /// If the socket isn't ready this registers the waker of `cx` to be woken
+    /// once it is. An operation that returns [`io::ErrorKind::WouldBlock`]
+    /// must be run using [`AsyncFdReadyGuard::try_io`], or the readiness must
+    /// be cleared, to avoid busy looping.
+    pub fn poll_read_ready<'a>(
+        &'a self,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<AsyncFdReadyGuard<'a, Socket>>> {
+        self.inner.poll_read_ready(cx)
+    }
+
+    /// Polls for write readiness.
+    ///
+    /// See [`AsyncSocket::poll_read_ready`].
+    pub fn poll_write_ready<'a>(
+        &'a self,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<AsyncFdReadyGuard<'a, Socket>>> {
+        self.inner.poll_write_ready(cx)
+    }
+
+    /// Waits for the socket to become readable.
+    pub async fn readable(&self) -> io::Result<AsyncFdReadyGuard<'_, Socket>> {
+        self.inner.readable().await
+    }
+
+    /// Waits for the socket to become writable.
+    pub async fn writable(&self) -> io::Result<AsyncFdReadyGuard<'_, Socket>> {
+        self.inner.writable().await
+    }
+
+    /// Polls to receive data, see [`Socket::recv`].
+    pub fn poll_recv(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &mut [MaybeUninit<u8>],
+    ) -> Poll<io::Result<usize>> {
+        loop {
+            let mut guard = match self.inner.poll_read_ready(cx) {
+                Poll::Ready(Ok(guard)) => guard,
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            };
+            match guard.try_io(|inner| inner.get_ref().recv(buf)) {
+                Ok(res) => return Poll::Ready(res),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Polls to send data, see [`Socket::send`].
+    pub fn poll_send(&self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        loop {
+            let mut guard = match self.inner.poll_write_ready(cx) {
+                Poll::Ready(Ok(guard)) => guard,
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            };
+            match guard.try_io(|inner| inner.get_ref().send(buf)) {
+                Ok(res) => return Poll::Ready(res),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Receives data, see [`Socket::recv`].
+    pub async fn recv(&self, buf: &mut [MaybeUninit<u8>]) -> io::Result<usize> {
+        self.inner
+            .async_io(tokio::io::Interest::READABLE, |socket| socket.recv(buf))
+            .await
+    }
+
+    /// Sends data, see [`Socket::send`].
+    pub async fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        self.inner
+            .async_io(tokio::io::Interest::WRITABLE, |socket| socket.send(buf))
+            .await
+    }
+}
@@ -0,0 +1,429 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use crate::sys::c_int;
+use crate::{Domain, Protocol, SockAddr, Socket, Type};
+
+/// Builder to create a [`Socket`], set options and bind or connect it.
+///
+/// Some options, such as `SO_REUSEADDR`, only have an effect if they're set
+/// before the socket is bound or connected. The builder records all options
+/// and applies them in the correct order when the socket is created, see
+/// [`SocketBuilder::build`], [`SocketBuilder::bind`] and
+/// [`SocketBuilder::connect`].
+///
+/// If setting an option fails the returned error includes the option that
+/// failed to be set. The original error, including its OS error code, is
+/// available as the [source] of the error.
+///
+/// [source]: std::error::Error::source
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> std::io::Result<()> {
+/// use std::net::SocketAddr;
+///
+/// use socket2::{Domain, Socket, Type};
+///
+/// let address: SocketAddr = "127.0.0.1:0".parse().unwrap();
+/// let listener = Socket::builder(Domain::IPV4, Type::STREAM)
+///     .with_reuse_address(true)
+///     .with_recv_buffer_size(1 << 16)
+///     .bind(&address.into())?;
+/// listener.listen(128)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SocketBuilder {
+    domain: Domain,
+    ty: Type,
+    protocol: Option<Protocol>,
+    nonblocking: Option<bool>,
+    reuse_address: Option<bool>,
+    #[cfg(all(
+        feature = "all",
+        unix,
+        not(any(target_os = "solaris", target_os = "illumos"))
+    ))]
+    reuse_port: Option<bool>,
+    only_v6: Option<bool>,
+    broadcast: Option<bool>,
+    nodelay: Option<bool>,
+    recv_buffer_size: Option<usize>,
+    send_buffer_size: Option<usize>,
+}
+
+impl SocketBuilder {
+    /// Returns a new builder for a socket in `domain` of type `ty`.
+    ///
+    /// Also see [`Socket::builder`].
+    pub const fn new(domain: Domain, ty: Type) -> SocketBuilder {
+        SocketBuilder {
+            domain,
+            ty,
+            protocol: None,
+            nonblocking: None,
+            reuse_address: None,
+            #[cfg(all(
+                feature = "all",
+                unix,
+                not(any(target_os = "solaris", target_os = "illumos"))
+            ))]
+            reuse_port: None,
+            only_v6: None,
+            broadcast: None,
+            nodelay: None,
+            recv_buffer_size: None,
+            send_buffer_size: None,
+        }
+    }
+
+    /// Set the protocol of the socket, see [`Socket::new`].
+    pub const fn with_protocol(self, protocol: Protocol) -> SocketBuilder {
+        SocketBuilder {
+            protocol: Some(protocol),
+            ..self
+        }
+    }
+
+    /// Set the socket in nonblocking mode, see [`Socket::set_nonblocking`].
+    pub const fn with_nonblocking(self, nonblocking: bool) -> SocketBuilder {
+        SocketBuilder {
+            nonblocking: Some(nonblocking),
+            ..self
+        }
+    }
+
+    /// Set `SO_REUSEADDR`, see [`Socket::set_reuse_address`].
+    pub const fn with_reuse_address(self, reuse: bool) -> SocketBuilder {
+        SocketBuilder {
+            reuse_address: Some(reuse),
+            ..self
+        }
+    }
+
+    /// Set `SO_REUSEPORT`, see [`Socket::set_reuse_port`].
+    ///
+    /// This function is only available on Unix when the `all` feature is
+    /// enabled.
+    #[cfg(all(
+        feature = "all",
+        unix,
+        not(any(target_os = "solaris", target_os = "illumos"))
+    ))]
+    pub const fn with_reuse_port(self, reuse: bool) -> SocketBuilder {
+        SocketBuilder {
+            reuse_port: Some(reuse),
+            ..self
+        }
+    }
+
+    /// Set `IPV6_V6ONLY`, see [`Socket::set_only_v6`].
+    pub const fn with_only_v6(self, only_v6: bool) -> SocketBuilder {
+        SocketBuilder {
+            only_v6: Some(only_v6),
+            ..self
+        }
+    }
+
+    /// Set `SO_BROADCAST`, see [`Socket::set_broadcast`].
+    pub const fn with_broadcast(self, broadcast: bool) -> SocketBuilder {
+        SocketBuilder {
+            broadcast: Some(broadcast),
+            ..self
+        }
+    }
+
+    /// Set `TCP_NODELAY`, see [`Socket::set_nodelay`].
+    pub const fn with_nodelay(self, nodelay: bool) -> SocketBuilder {
+        SocketBuilder {
+            nodelay: Some(nodelay),
+            ..self
+        }
+    }
+
+    /// Set `SO_RCVBUF`, see [`Socket::set_recv_buffer_size`].
+    pub const fn with_recv_buffer_size(self, size: usize) -> SocketBuilder {
+        SocketBuilder {
+            recv_buffer_size: Some(size),
+            ..self
+        }
+    }
+
+    /// Set `SO_SNDBUF`, see [`Socket::set_send_buffer_size`].
+    pub const fn with_send_buffer_size(self, size: usize) -> SocketBuilder {
+        SocketBuilder {
+            send_buffer_size: Some(size),
+            ..self
+        }
+    }
+
+    /// Create the socket and set all options.
+    pub fn build(&self) -> io::Result<Socket> {
+        let socket = self.create()?;
+        self.set_nonblocking(&socket)?;
+        Ok(socket)
+    }
+
+    /// Create the socket, set all options and bind it to `address`.
+    pub fn bind(&self, address: &SockAddr) -> io::Result<Socket> {
+        let socket = self.create()?;
+        socket.bind(address)?;
+        self.set_nonblocking(&socket)?;
+        Ok(socket)
+    }
+
+    /// Create the socket, set all options and connect it to `address`.
+    ///
+    /// The connect is always done in blocking mode, the nonblocking mode (see
+    /// [`SocketBuilder::with_nonblocking`]) is set once the connection is
+    /// established.
+    pub fn connect(&self, address: &SockAddr) -> io::Result<Socket> {
+        let socket = self.create()?;
+        socket.connect(address)?;
+        self.set_nonblocking(&socket)?;
+        Ok(socket)
+    }
+
+    /// Create the socket and set all options, except the nonblocking mode.
+    fn create(&self) -> io::Result<Socket> {
+        let socket = Socket::new(self.domain, self.ty, self.protocol)?;
+        if let Some(reuse) = self.reuse_address {
+            context(socket.set_reuse_address(reuse), "SO_REUSEADDR")?;
+        }
+        #[cfg(all(
+            feature = "all",
+            unix,
+            not(any(target_os = "solaris", target_os = "illumos"))
+        ))]
+        if let Some(reuse) = self.reuse_port {
+            context(socket.set_reuse_port(reuse), "SO_REUSEPORT")?;
+        }
+        if let Some(only_v6) = self.only_v6 {
+            context(socket.set_only_v6(only_v6), "IPV6_V6ONLY")?;
+        }
+        if let Some(broadcast) = self.broadcast {
+            context(socket.set_broadcast(broadcast), "SO_BROADCAST")?;
+        }
+        if let Some(nodelay) = self.nodelay {
+            context(socket.set_nodelay(nodelay), "TCP_NODELAY")?;
+        }
+        if let Some(size) = self.recv_buffer_size {
+            context(socket.set_recv_buffer_size(size), "SO_RCVBUF")?;
+        }
+        if let Some(size) = self.send_buffer_size {
+            context(socket.set_send_buffer_size(size), "SO_SNDBUF")?;
+        }
+        Ok(socket)
+    }
+
+    fn set_nonblocking(&self, socket: &Socket) -> io::Result<()> {
+        match self.nonblocking {
+            Some(nonblocking) => context(socket.set_nonblocking(nonblocking), "nonblocking mode"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Options used in [`Socket::tcp_listen`].
+///
+/// By default `SO_REUSEADDR` is set on Unix (matching the standard library's
+/// [`TcpListener::bind`]), but not on Windows where it allows other sockets to
+/// bind to the same address. `IPV6_V6ONLY` is left to the OS default and the
+/// socket is in blocking mode.
+///
+/// [`TcpListener::bind`]: std::net::TcpListener::bind
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> std::io::Result<()> {
+/// use std::net::SocketAddr;
+///
+/// use socket2::{ListenerOptions, Socket};
+///
+/// let address: SocketAddr = "[::1]:0".parse().unwrap();
+/// let options = ListenerOptions::new().with_only_v6(true).with_nonblocking(true);
+/// let listener = Socket::tcp_listen(&address.into(), 1024, &options)?;
+/// # drop(listener);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ListenerOptions {
+    reuse_address: bool,
+    only_v6: Option<bool>,
+    nonblocking: bool,
+}
+
+impl ListenerOptions {
+    /// Returns the default options.
+    pub const fn new() -> ListenerOptions {
+        ListenerOptions {
+            reuse_address: cfg!(unix),
+            only_v6: None,
+            nonblocking: false,
+        }
+    }
+
+    /// Set `SO_REUSEADDR`, see [`Socket::set_reuse_address`].
+    pub const fn with_reuse_address(self, reuse_address: bool) -> ListenerOptions {
+        ListenerOptions {
+            reuse_address,
+            ..self
+        }
+    }
+
+    /// Set `IPV6_V6ONLY`, see [`Socket::set_only_v6`].
+    ///
+    /// This is ignored for IPv4 addresses.
+    pub const fn with_only_v6(self, only_v6: bool) -> ListenerOptions {
+        ListenerOptions {
+            only_v6: Some(only_v6),
+            ..self
+        }
+    }
+
+    /// Set the listener in nonblocking mode, see [`Socket::set_nonblocking`].
+    pub const fn with_nonblocking(self, nonblocking: bool) -> ListenerOptions {
+        ListenerOptions {
+            nonblocking,
+            ..self
+        }
+    }
+
+    /// Create a listener bound to `address`, see [`Socket::tcp_listen`].
+    pub(crate) fn listen(&self, address: &SockAddr, backlog: c_int) -> io::Result<Socket> {
+        let domain = Domain::from(c_int::from(address.family()));
+        let mut builder = SocketBuilder::new(domain, Type::STREAM)
+            .with_protocol(Protocol::TCP)
+            .with_reuse_address(self.reuse_address)
+            .with_nonblocking(self.nonblocking);
+        if let (Some(only_v6), true) = (self.only_v6, domain == Domain::IPV6) {
+            builder = builder.with_only_v6(only_v6);
+        }
+        let socket = builder.bind(address)?;
+        socket.listen(backlog)?;
+        Ok(socket)
+    }
+}
+
+impl Default for ListenerOptions {
+    fn default() -> ListenerOptions {
+        ListenerOptions::new()
+    }
+}
+
+/// Options used in [`Socket::udp_connect`].
+///
+/// By default the OS default buffer sizes are used and the socket is in
+/// blocking mode.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> std::io::Result<()> {
+/// use std::net::SocketAddr;
+///
+/// use socket2::{Socket, UdpOptions};
+///
+/// let remote: SocketAddr = "127.0.0.1:53".parse().unwrap();
+/// let options = UdpOptions::new().with_recv_buffer_size(1 << 16);
+/// let socket = Socket::udp_connect(None, &remote.into(), &options)?;
+/// # drop(socket);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct UdpOptions {
+    recv_buffer_size: Option<usize>,
+    send_buffer_size: Option<usize>,
+    nonblocking: bool,
+}
+
+impl UdpOptions {
+    /// Returns the default options.
+    pub const fn new() -> UdpOptions {
+        UdpOptions {
+            recv_buffer_size: None,
+            send_buffer_size: None,
+            nonblocking: false,
+        }
+    }
+
+    /// Set `SO_RCVBUF`, see [`Socket::set_recv_buffer_size`].
+    pub const fn with_recv_buffer_size(self, size: usize) -> UdpOptions {
+        UdpOptions {
+            recv_buffer_size: Some(size),
+            ..self
+        }
+    }
+
+    /// Set `SO_SNDBUF`, see [`Socket::set_send_buffer_size`].
+    pub const fn with_send_buffer_size(self, size: usize) -> UdpOptions {
+        UdpOptions {
+            send_buffer_size: Some(size),
+            ..self
+        }
+    }
+
+    /// Set the socket in nonblocking mode, see [`Socket::set_nonblocking`].
+    pub const fn with_nonblocking(self, nonblocking: bool) -> UdpOptions {
+        UdpOptions {
+            nonblocking,
+            ..self
+        }
+    }
+
+    /// Create a connected socket, see [`Socket::udp_connect`].
+    pub(crate) fn connect(
+        &self,
+        local: Option<&SockAddr>,
+        remote: &SockAddr,
+    ) -> io::Result<Socket> {
+        let domain = Domain::from(c_int::from(remote.family()));
+        let mut builder = SocketBuilder::new(domain, Type::DGRAM)
+            .with_protocol(Protocol::UDP)
+            .with_nonblocking(self.nonblocking);
+        if let Some(size) = self.recv_buffer_size {
+            builder = builder.with_recv_buffer_size(size);
+        }
+        if let Some(size) = self.send_buffer_size {
+            builder = builder.with_send_buffer_size(size);
+        }
+        let socket = builder.create()?;
+        if let Some(local) = local {
+            socket.bind(local)?;
+        }
+        socket.connect(remote)?;
+        builder.set_nonblocking(&socket)?;
+        Ok(socket)
+    }
+}
+
+/// Adds the name of the option that failed to be set to the error `res`.
+fn context(res: io::Result<()>, option: &'static str) -> io::Result<()> {
+    res.map_err(|error| io::Error::new(error.kind(), OptionError { option, error }))
+}
+
+/// Error setting `option`, returned by [`context`].
+#[derive(Debug)]
+struct OptionError {
+    option: &'static str,
+    error: io::Error,
+}
+
+impl fmt::Display for OptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to set {}: {}", self.option, self.error)
+    }
+}
+
+impl Error for OptionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
@@ -0,0 +1,252 @@
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use crate::sys::{self, c_int};
+
+thread_local! {
+    /// Context of the last failed system call on this thread.
+    #[allow(clippy::missing_const_for_thread_local)] // `const` requires Rust 1.59.
+    static LAST_CONTEXT: Cell<Option<Context>> = Cell::new(None);
+}
+
+/// Context of a failed system call.
+#[derive(Copy, Clone, Debug)]
+struct Context {
+    operation: &'static str,
+    socket: Option<sys::Socket>,
+    option: Option<(c_int, c_int)>,
+    /// OS error code of the failure, used to match the context to the error.
+    code: Option<i32>,
+}
+
+/// Records the context of system call `operation` on `socket` (if the system
+/// call takes one), if it failed with `error`. Otherwise clears the context, so
+/// that no stale context is attached to a later error.
+pub(crate) fn record(
+    operation: &'static str,
+    socket: Option<sys::Socket>,
+    error: Option<&io::Error>,
+) {
+    let context = error.map(|error| Context {
+        operation,
+        socket,
+        option: None,
+        code: error.raw_os_error(),
+    });
+    LAST_CONTEXT.with(|last| last.set(context));
+}
+
+/// Records the context of a failed `getsockopt` or `setsockopt` call.
+pub(crate) fn record_option(
+    operation: &'static str,
+    socket: sys::Socket,
+    level: c_int,
+    option: c_int,
+    error: &io::Error,
+) {
+    LAST_CONTEXT.with(|last| {
+        last.set(Some(Context {
+            operation,
+            socket: Some(socket),
+            option: Some((level, option)),
+            code: error.raw_os_error(),
+        }))
+    });
+}
+
+/// Names of the socket option levels.
+const LEVELS: &[(c_int, &str)] = &[
+    (sys::SOL_SOCKET, "SOL_SOCKET"),
+    (sys::IPPROTO_IP, "IPPROTO_IP"),
+    (sys::IPPROTO_IPV6, "IPPROTO_IPV6"),
+    (sys::IPPROTO_TCP, "IPPROTO_TCP"),
+    (sys::IPPROTO_UDP, "IPPROTO_UDP"),
+];
+
+/// Names of the socket options, per level.
+const OPTIONS: &[(c_int, c_int, &str)] = &[
+    (sys::SOL_SOCKET, sys::SO_BROADCAST, "SO_BROADCAST"),
+    (sys::SOL_SOCKET, sys::SO_ERROR, "SO_ERROR"),
+    (sys::SOL_SOCKET, sys::SO_KEEPALIVE, "SO_KEEPALIVE"),
+    (sys::SOL_SOCKET, sys::SO_LINGER, "SO_LINGER"),
+    #[cfg(not(target_os = "redox"))]
+    (sys::SOL_SOCKET, sys::SO_OOBINLINE, "SO_OOBINLINE"),
+    (sys::SOL_SOCKET, sys::SO_RCVBUF, "SO_RCVBUF"),
+    (sys::SOL_SOCKET, sys::SO_RCVTIMEO, "SO_RCVTIMEO"),
+    (sys::SOL_SOCKET, sys::SO_REUSEADDR, "SO_REUSEADDR"),
+    (sys::SOL_SOCKET, sys::SO_SNDBUF, "SO_SNDBUF"),
+    (sys::SOL_SOCKET, sys::SO_SNDTIMEO, "SO_SNDTIMEO"),
+    (sys::IPPROTO_IP, sys::IP_ADD_MEMBERSHIP, "IP_ADD_MEMBERSHIP"),
+    (
+        sys::IPPROTO_IP,
+        sys::IP_DROP_MEMBERSHIP,
+        "IP_DROP_MEMBERSHIP",
+    ),
+    (sys::IPPROTO_IP, sys::IP_MULTICAST_IF, "IP_MULTICAST_IF"),
+    (sys::IPPROTO_IP, sys::IP_MULTICAST_LOOP, "IP_MULTICAST_LOOP"),
+    (sys::IPPROTO_IP, sys::IP_MULTICAST_TTL, "IP_MULTICAST_TTL"),
+    (sys::IPPROTO_IP, sys::IP_TTL, "IP_TTL"),
+    (
+        sys::IPPROTO_IPV6,
+        sys::IPV6_MULTICAST_HOPS,
+        "IPV6_MULTICAST_HOPS",
+    ),
+    (
+        sys::IPPROTO_IPV6,
+        sys::IPV6_MULTICAST_IF,
+        "IPV6_MULTICAST_IF",
+    ),
+    (
+        sys::IPPROTO_IPV6,
+        sys::IPV6_MULTICAST_LOOP,
+        "IPV6_MULTICAST_LOOP",
+    ),
+    (
+        sys::IPPROTO_IPV6,
+        sys::IPV6_UNICAST_HOPS,
+        "IPV6_UNICAST_HOPS",
+    ),
+    (sys::IPPROTO_IPV6, sys::IPV6_V6ONLY, "IPV6_V6ONLY"),
+    (sys::IPPROTO_TCP, sys::TCP_NODELAY, "TCP_NODELAY"),
+];
+
+/// Writes the name of `level`, or its value if it's unknown.
+fn fmt_level(level: c_int, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match LEVELS.iter().find(|(l, _)| *l == level) {
+        Some((_, name)) => f.write_str(name),
+        None => write!(f, "{}", level),
+    }
+}
+
+/// Writes the name of `option` at `level`, or its value if it's unknown.
+fn fmt_option(level: c_int, option: c_int, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match OPTIONS.iter().find(|(l, o, _)| *l == level && *o == option) {
+        Some((_, _, name)) => f.write_str(name),
+        None => write!(f, "{}", option),
+    }
+}
+
+/// An I/O error with the context of the operation that failed.
+///
+/// All functions in this crate return a plain [`io::Error`], so that the OS
+/// error code is available using [`io::Error::raw_os_error`]. If more context
+/// is wanted, e.g. to attribute an error from deep inside connection setup, the
+/// error can be converted into a `ContextError` using
+/// [`ContextError::from_last`], which attaches the system call (and the socket
+/// it was called on, and the option for socket options) that failed last on
+/// the current thread.
+///
+/// The context is only recorded with the `error-context` feature enabled. It's
+/// cleared by every successful system call on the thread and by
+/// [`ContextError::from_last`].
+///
+/// # Examples
+///
+/// ```
+/// use socket2::{ContextError, Domain, Socket, Type};
+///
+/// let socket = Socket::new(Domain::IPV4, Type::DGRAM, None)?;
+/// // Can't listen on a UDP socket.
+/// if let Err(err) = socket.listen(128) {
+///     let err = ContextError::from_last(err);
+///     assert_eq!(err.operation(), Some("listen"));
+///     // E.g. `listen on fd 3: Operation not supported (os error 95)`.
+///     println!("{}", err);
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct ContextError {
+    context: Option<Context>,
+    error: io::Error,
+}
+
+impl ContextError {
+    /// Attach the context of the last failed system call on the current thread
+    /// to `error`.
+    ///
+    /// This should be called directly after the function returning `error`,
+    /// as the context is cleared by a successful system call, and by this
+    /// function. The context is only attached if the OS error code of the
+    /// failed system call matches the one of `error`, so errors not created by
+    /// this crate (or without an OS error code) don't get a wrong context.
+    pub fn from_last(error: io::Error) -> ContextError {
+        let context = LAST_CONTEXT
+            .with(Cell::take)
+            .filter(|context| context.code.is_some() && context.code == error.raw_os_error());
+        ContextError { context, error }
+    }
+
+    /// Returns the name of the system call that failed, e.g. `setsockopt`.
+    pub fn operation(&self) -> Option<&'static str> {
+        self.context.map(|context| context.operation)
+    }
+
+    /// Returns the level and name of the socket option, if the failed system
+    /// call was `getsockopt` or `setsockopt`.
+    pub fn option(&self) -> Option<(c_int, c_int)> {
+        self.context.and_then(|context| context.option)
+    }
+
+    /// Returns a reference to the underlying I/O error.
+    pub fn io_error(&self) -> &io::Error {
+        &self.error
+    }
+
+    /// Returns the underlying I/O error, dropping the context.
+    pub fn into_io_error(self) -> io::Error {
+        self.error
+    }
+}
+
+impl From<ContextError> for io::Error {
+    fn from(err: ContextError) -> io::Error {
+        err.error
+    }
+}
+
+impl fmt::Display for ContextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(context) = self.context {
+            f.write_str(context.operation)?;
+            if let Some((level, option)) = context.option {
+                f.write_str("(")?;
+                fmt_level(level, f)?;
+                f.write_str(", ")?;
+                fmt_option(level, option, f)?;
+                f.write_str(")")?;
+            }
+            if let Some(socket) = context.socket {
+                #[cfg(unix)]
+                write!(f, " on fd {}", socket)?;
+                #[cfg(windows)]
+                write!(f, " on socket {}", socket)?;
+            }
+            f.write_str(": ")?;
+        }
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl fmt::Debug for ContextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("ContextError");
+        if let Some(context) = self.context {
+            d.field("operation", &context.operation);
+            if let Some(socket) = context.socket {
+                d.field("socket", &socket);
+            }
+            if let Some((level, option)) = context.option {
+                d.field("level", &level).field("option", &option);
+            }
+        }
+        d.field("error", &self.error).finish()
+    }
+}
+
+impl Error for ContextError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
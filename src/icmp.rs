@@ -0,0 +1,224 @@
+/// ICMP echo request or reply message, as used by `ping`.
+///
+/// This can be used with sockets created using [`Protocol::ICMPV4`] or
+/// [`Protocol::ICMPV6`], either raw (requiring elevated privileges) or, on
+/// some OSs, datagram ("ping") sockets. Note that for datagram sockets the OS
+/// may overwrite the identifier with the local port of the socket.
+///
+/// # Examples
+///
+/// ```
+/// use socket2::IcmpEcho;
+///
+/// let request = IcmpEcho::new(0x1234, 1, b"ping");
+/// let packet = request.encode_request_v4();
+///
+/// // The request can be parsed using the same type.
+/// let echo = IcmpEcho::parse_request_v4(&packet).unwrap();
+/// assert_eq!(echo, request);
+/// ```
+///
+/// [`Protocol::ICMPV4`]: crate::Protocol::ICMPV4
+/// [`Protocol::ICMPV6`]: crate::Protocol::ICMPV6
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct IcmpEcho<'a> {
+    identifier: u16,
+    sequence: u16,
+    payload: &'a [u8],
+}
+
+/// Size of the ICMP echo header: type, code, checksum, identifier and sequence
+/// number.
+const HEADER_LEN: usize = 8;
+
+// Message types from RFC 792 and RFC 4443.
+const ECHO_REPLY_V4: u8 = 0;
+const ECHO_REQUEST_V4: u8 = 8;
+const ECHO_REQUEST_V6: u8 = 128;
+const ECHO_REPLY_V6: u8 = 129;
+
+impl<'a> IcmpEcho<'a> {
+    /// Create a new echo message.
+    pub const fn new(identifier: u16, sequence: u16, payload: &'a [u8]) -> IcmpEcho<'a> {
+        IcmpEcho {
+            identifier,
+            sequence,
+            payload,
+        }
+    }
+
+    /// Returns the identifier of the message.
+    pub const fn identifier(&self) -> u16 {
+        self.identifier
+    }
+
+    /// Returns the sequence number of the message.
+    pub const fn sequence(&self) -> u16 {
+        self.sequence
+    }
+
+    /// Returns the payload of the message.
+    pub const fn payload(&self) -> &'a [u8] {
+        self.payload
+    }
+
+    /// Encode the message as ICMPv4 echo request, including the checksum.
+    pub fn encode_request_v4(&self) -> Vec<u8> {
+        self.encode(ECHO_REQUEST_V4, true)
+    }
+
+    /// Encode the message as ICMPv4 echo reply, including the checksum.
+    pub fn encode_reply_v4(&self) -> Vec<u8> {
+        self.encode(ECHO_REPLY_V4, true)
+    }
+
+    /// Encode the message as ICMPv6 echo request.
+    ///
+    /// The ICMPv6 checksum includes the IPv6 pseudo-header, it's left zero as
+    /// the OS calculates it when sending.
+    pub fn encode_request_v6(&self) -> Vec<u8> {
+        self.encode(ECHO_REQUEST_V6, false)
+    }
+
+    /// Encode the message as ICMPv6 echo reply, see [`encode_request_v6`].
+    ///
+    /// [`encode_request_v6`]: IcmpEcho::encode_request_v6
+    pub fn encode_reply_v6(&self) -> Vec<u8> {
+        self.encode(ECHO_REPLY_V6, false)
+    }
+
+    /// Parse an ICMPv4 echo request.
+    ///
+    /// See [`parse_reply_v4`] for the accepted input.
+    ///
+    /// [`parse_reply_v4`]: IcmpEcho::parse_reply_v4
+    pub fn parse_request_v4(packet: &'a [u8]) -> Option<IcmpEcho<'a>> {
+        IcmpEcho::parse(skip_ipv4_header(packet)?, ECHO_REQUEST_V4, true)
+    }
+
+    /// Parse an ICMPv4 echo reply.
+    ///
+    /// `packet` may start with the IPv4 header, as received on raw sockets,
+    /// which is skipped. Returns `None` if the packet is not an echo reply or
+    /// if the checksum is invalid.
+    pub fn parse_reply_v4(packet: &'a [u8]) -> Option<IcmpEcho<'a>> {
+        IcmpEcho::parse(skip_ipv4_header(packet)?, ECHO_REPLY_V4, true)
+    }
+
+    /// Parse an ICMPv6 echo request.
+    ///
+    /// See [`parse_reply_v6`] for the accepted input.
+    ///
+    /// [`parse_reply_v6`]: IcmpEcho::parse_reply_v6
+    pub fn parse_request_v6(packet: &'a [u8]) -> Option<IcmpEcho<'a>> {
+        IcmpEcho::parse(packet, ECHO_REQUEST_V6, false)
+    }
+
+    /// Parse an ICMPv6 echo reply.
+    ///
+    /// Returns `None` if the packet is not an echo reply. The checksum is not
+    /// validated, the OS already does that for ICMPv6.
+    pub fn parse_reply_v6(packet: &'a [u8]) -> Option<IcmpEcho<'a>> {
+        IcmpEcho::parse(packet, ECHO_REPLY_V6, false)
+    }
+
+    fn encode(&self, r#type: u8, with_checksum: bool) -> Vec<u8> {
+        let mut packet = Vec::with_capacity(HEADER_LEN + self.payload.len());
+        packet.extend_from_slice(&[r#type, 0, 0, 0]);
+        packet.extend_from_slice(&self.identifier.to_be_bytes());
+        packet.extend_from_slice(&self.sequence.to_be_bytes());
+        packet.extend_from_slice(self.payload);
+        if with_checksum {
+            let checksum = checksum(&packet);
+            packet[2..4].copy_from_slice(&checksum.to_be_bytes());
+        }
+        packet
+    }
+
+    fn parse(packet: &'a [u8], r#type: u8, with_checksum: bool) -> Option<IcmpEcho<'a>> {
+        if packet.len() < HEADER_LEN || packet[0] != r#type || packet[1] != 0 {
+            return None;
+        }
+        // The checksum of a packet including a valid checksum is zero.
+        if with_checksum && checksum(packet) != 0 {
+            return None;
+        }
+        Some(IcmpEcho {
+            identifier: u16::from_be_bytes([packet[4], packet[5]]),
+            sequence: u16::from_be_bytes([packet[6], packet[7]]),
+            payload: &packet[HEADER_LEN..],
+        })
+    }
+}
+
+/// Skips the IPv4 header, if any, at the start of `packet`.
+///
+/// None of the ICMP message types start with `0x4`, so if the first nibble
+/// is the IP version the packet includes the IPv4 header.
+fn skip_ipv4_header(packet: &[u8]) -> Option<&[u8]> {
+    match packet.first() {
+        Some(first) if first >> 4 == 4 => packet.get(usize::from(first & 0xf) * 4..),
+        _ => Some(packet),
+    }
+}
+
+/// The internet checksum as defined in RFC 1071.
+fn checksum(data: &[u8]) -> u16 {
+    let mut sum = 0u32;
+    let mut chunks = data.chunks_exact(2);
+    for chunk in &mut chunks {
+        sum += u32::from(u16::from_be_bytes([chunk[0], chunk[1]]));
+    }
+    if let [last] = chunks.remainder() {
+        sum += u32::from(*last) << 8;
+    }
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+#[test]
+fn checksum_rfc1071() {
+    // Example from RFC 1071 section 3, which sums to `0xddf2`.
+    let data = [0x00, 0x01, 0xf2, 0x03, 0xf4, 0xf5, 0xf6, 0xf7];
+    assert_eq!(checksum(&data), !0xddf2);
+    // Odd number of bytes.
+    assert_eq!(checksum(&[0xff]), !0xff00);
+}
+
+#[test]
+fn echo_v4() {
+    let echo = IcmpEcho::new(0xabcd, 7, b"Hello, world!");
+    let request = echo.encode_request_v4();
+    assert_eq!(&request[..2], &[8, 0]);
+    assert_eq!(IcmpEcho::parse_request_v4(&request), Some(echo));
+    assert_eq!(IcmpEcho::parse_reply_v4(&request), None);
+
+    let reply = echo.encode_reply_v4();
+    assert_eq!(IcmpEcho::parse_reply_v4(&reply), Some(echo));
+
+    // With an IPv4 header (without options) in front.
+    let mut packet = vec![0x45; 20];
+    packet.extend_from_slice(&reply);
+    assert_eq!(IcmpEcho::parse_reply_v4(&packet), Some(echo));
+
+    // Invalid checksum.
+    let mut reply = reply;
+    reply[HEADER_LEN] ^= 1;
+    assert_eq!(IcmpEcho::parse_reply_v4(&reply), None);
+}
+
+#[test]
+fn echo_v6() {
+    let echo = IcmpEcho::new(1, 2, &[]);
+    let request = echo.encode_request_v6();
+    assert_eq!(request, &[128, 0, 0, 0, 0, 1, 0, 2]);
+    assert_eq!(IcmpEcho::parse_request_v6(&request), Some(echo));
+    assert_eq!(IcmpEcho::parse_reply_v6(&request), None);
+    assert_eq!(
+        IcmpEcho::parse_reply_v6(&echo.encode_reply_v6()),
+        Some(echo)
+    );
+    assert_eq!(IcmpEcho::parse_reply_v6(&request[..4]), None);
+}
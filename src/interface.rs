@@ -0,0 +1,102 @@
+use std::io;
+
+use crate::{sys, SockAddr};
+
+/// A network interface of the system, see [`interfaces`].
+#[derive(Debug)]
+pub struct Interface {
+    pub(crate) name: String,
+    pub(crate) index: u32,
+    pub(crate) flags: u32,
+    pub(crate) up: bool,
+    pub(crate) loopback: bool,
+    pub(crate) multicast: bool,
+    pub(crate) addresses: Vec<SockAddr>,
+}
+
+impl Interface {
+    /// Returns the name of the interface, e.g. `eth0` or `Ethernet`.
+    ///
+    /// On Windows this is the friendly name of the adapter.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the index of the interface.
+    ///
+    /// This can be used in, for example, [`Socket::join_multicast_v6`] or
+    /// [`Socket::set_multicast_if_v6`].
+    ///
+    /// [`Socket::join_multicast_v6`]: crate::Socket::join_multicast_v6
+    /// [`Socket::set_multicast_if_v6`]: crate::Socket::set_multicast_if_v6
+    pub const fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Returns the raw flags of the interface.
+    ///
+    /// On Unix these are the `IFF_*` flags (`ifa_flags`), on Windows these are
+    /// the `IP_ADAPTER_*` flags (`Flags` of `IP_ADAPTER_ADDRESSES`).
+    pub const fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    /// Returns `true` if the interface is up.
+    pub const fn is_up(&self) -> bool {
+        self.up
+    }
+
+    /// Returns `true` if the interface is a loopback interface.
+    pub const fn is_loopback(&self) -> bool {
+        self.loopback
+    }
+
+    /// Returns `true` if the interface supports multicast.
+    pub const fn supports_multicast(&self) -> bool {
+        self.multicast
+    }
+
+    /// Returns the IPv4 and IPv6 addresses assigned to the interface.
+    pub fn addresses(&self) -> &[SockAddr] {
+        &self.addresses
+    }
+}
+
+/// Returns all network interfaces of the system.
+///
+/// This uses `getifaddrs(3)` on Unix and `GetAdaptersAddresses` on Windows.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> std::io::Result<()> {
+/// for interface in socket2::interfaces()? {
+///     println!("{} ({}): {:?}", interface.name(), interface.index(), interface.addresses());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn interfaces() -> io::Result<Vec<Interface>> {
+    sys::interfaces()
+}
+
+/// Returns the MTU of the interface with `name`.
+///
+/// This uses the `SIOCGIFMTU` ioctl on Unix and `GetAdaptersAddresses` on
+/// Windows, where `name` is the friendly name of the adapter (see
+/// [`Interface::name`]).
+#[cfg(any(
+    windows,
+    all(
+        feature = "all",
+        any(
+            target_os = "android",
+            target_os = "freebsd",
+            target_os = "linux",
+            target_vendor = "apple",
+        )
+    )
+))]
+pub fn interface_mtu(name: &str) -> io::Result<u32> {
+    sys::interface_mtu(name)
+}
@@ -47,8 +47,48 @@
 //!
 //! ## Features
 //!
-//! This crate has a single feature `all`, which enables all functions even ones
-//! that are not available on all OSs.
+//! This crate has the following features:
+//!
+//!  * `all`: enables all functions even ones that are not available on all
+//!    OSs.
+//!  * `error-context`: enables `ContextError`, recording the system call that
+//!    failed last on each thread so it can be attached to the returned error.
+//!  * `icmp`: enables [`IcmpEcho`], a helper to build and parse ICMP echo
+//!    (ping) messages.
+//!  * `mio`: implements [`mio::event::Source`] for [`Socket`], allowing it to
+//!    be registered with a [`mio::Poll`] directly. Only available on Unix.
+//!  * `serde`: implements `Serialize` and `Deserialize` for [`SockAddr`],
+//!    [`Domain`], [`Type`] and [`Protocol`]. Addresses use the textual form
+//!    of [`SocketAddr`], e.g. `127.0.0.1:8080`, the others the name of the
+//!    constant, e.g. `ipv4` or `tcp`, or their raw value if it has no name.
+//!  * `tokio`: enables `AsyncSocket`, a [`Socket`] registered with the Tokio
+//!    runtime providing readiness hooks for asynchronous I/O. Only available
+//!    on Unix.
+//!  * `tracing`: emits a trace level [`tracing`] event for every system call,
+//!    with the name and result (including the error). Sending, receiving,
+//!    connecting and getting or setting socket options do so within a span
+//!    holding the actual arguments, such as the socket, buffer length, flags,
+//!    address and option.
+//!
+//! [`mio::event::Source`]: https://docs.rs/mio/1/mio/event/trait.Source.html
+//! [`mio::Poll`]: https://docs.rs/mio/1/mio/struct.Poll.html
+//! [`SocketAddr`]: std::net::SocketAddr
+//! [`tracing`]: https://docs.rs/tracing
+//!
+//! ## Asynchronous runtimes
+//!
+//! This crate doesn't provide any asynchronous I/O itself, but [`Socket`]
+//! implements `AsFd`/`AsRawFd` on Unix and `AsSocket`/`AsRawSocket` on Windows.
+//! This means it can be wrapped directly in a readiness type of a runtime, such
+//! as Tokio's [`AsyncFd`] or async-io's [`Async`], after putting it in
+//! non-blocking mode using [`Socket::set_nonblocking`]. These wrappers provide
+//! the `poll_read_ready`/`poll_write_ready` style hooks, while still giving
+//! access to the socket (via `get_ref`) so all options on [`Socket`] remain
+//! available. With the `tokio` feature `AsyncSocket` provides such a wrapper
+//! for Tokio.
+//!
+//! [`AsyncFd`]: https://docs.rs/tokio/1/tokio/io/unix/struct.AsyncFd.html
+//! [`Async`]: https://docs.rs/async-io/2/async_io/struct.Async.html
 
 #![doc(html_root_url = "https://docs.rs/socket2/0.3")]
 #![deny(missing_docs, missing_debug_implementations, rust_2018_idioms)]
@@ -61,8 +101,16 @@
 
 use std::fmt;
 use std::mem::MaybeUninit;
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "fuchsia",
+    target_os = "linux",
+    windows,
+))]
+use std::net::Ipv4Addr;
 use std::net::SocketAddr;
-use std::ops::{Deref, DerefMut};
+use std::ops::{BitOr, BitOrAssign, Deref, DerefMut};
 use std::time::Duration;
 
 /// Macro to implement `fmt::Debug` for a type, printing the constant names
@@ -115,6 +163,29 @@ macro_rules! from {
     };
 }
 
+/// Macro to enter a trace level span, with the actual arguments of a system
+/// call as fields, until the end of the current scope. The event emitted by
+/// `syscall!` is recorded within it. Does nothing without the `tracing`
+/// feature.
+macro_rules! syscall_span {
+    ($name: expr, $($field: tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!($name, $($field)*).entered();
+    };
+}
+
+#[cfg(all(unix, feature = "tokio"))]
+mod async_socket;
+mod builder;
+#[cfg(feature = "error-context")]
+mod error;
+#[cfg(feature = "icmp")]
+mod icmp;
+#[cfg(not(target_os = "redox"))]
+mod interface;
+mod resolve;
+#[cfg(feature = "serde")]
+mod serialize;
 mod sockaddr;
 mod socket;
 mod sockref;
@@ -128,9 +199,51 @@ mod sys;
 
 use sys::c_int;
 
+#[cfg(all(unix, feature = "tokio"))]
+pub use async_socket::AsyncSocket;
+pub use builder::{ListenerOptions, SocketBuilder, UdpOptions};
+#[cfg(feature = "error-context")]
+pub use error::ContextError;
+#[cfg(feature = "icmp")]
+pub use icmp::IcmpEcho;
+#[cfg(any(
+    windows,
+    all(
+        feature = "all",
+        any(
+            target_os = "android",
+            target_os = "freebsd",
+            target_os = "linux",
+            target_vendor = "apple",
+        )
+    )
+))]
+pub use interface::interface_mtu;
+#[cfg(not(target_os = "redox"))]
+pub use interface::{interfaces, Interface};
+pub use resolve::{getaddrinfo, AddrInfo, AddrInfoHints};
 pub use sockaddr::SockAddr;
 pub use socket::Socket;
 pub use sockref::SockRef;
+#[cfg(all(
+    feature = "all",
+    any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_vendor = "apple",
+    )
+))]
+pub use sys::Icmpv6Filter;
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+pub use sys::{
+    NetlinkMessage, NetlinkMessages, RdsTransport, SockExtendedErr, TcpRepairQueue,
+    TcpZeroCopyReceive,
+};
+#[cfg(all(windows, feature = "all"))]
+pub use sys::{NetworkEvents, RcvAllMode};
+#[cfg(all(feature = "all", target_os = "linux"))]
+pub use sys::{PreparedOp, TcpAoKey, TlsCryptoInfo, TlsVersion};
 
 /// Specification of the communication domain for a socket.
 ///
@@ -196,12 +309,22 @@ impl Type {
     pub const DGRAM: Type = Type(sys::SOCK_DGRAM);
 
     /// Type corresponding to `SOCK_SEQPACKET`.
+    ///
+    /// A connection-oriented type that preserves message boundaries, used for
+    /// protocols such as SCTP. On most Unix OSs it can also be used with
+    /// [`Domain::UNIX`], e.g. in [`Socket::pair`], for local IPC.
     #[cfg(feature = "all")]
     pub const SEQPACKET: Type = Type(sys::SOCK_SEQPACKET);
 
     /// Type corresponding to `SOCK_RAW`.
     #[cfg(all(feature = "all", not(target_os = "redox")))]
     pub const RAW: Type = Type(sys::SOCK_RAW);
+
+    /// Type corresponding to `SOCK_RDM`.
+    ///
+    /// Used for reliably delivered messages, e.g. TIPC or RDS.
+    #[cfg(all(feature = "all", not(any(target_os = "redox", target_os = "haiku"))))]
+    pub const RDM: Type = Type(sys::SOCK_RDM);
 }
 
 impl From<c_int> for Type {
@@ -238,6 +361,11 @@ impl Protocol {
 
     /// Protocol corresponding to `UDP`.
     pub const UDP: Protocol = Protocol(sys::IPPROTO_UDP);
+
+    /// Protocol corresponding to `RAW`, used with [`Type::RAW`] to send IP
+    /// packets including the header.
+    #[cfg(all(feature = "all", not(target_os = "redox")))]
+    pub const RAW: Protocol = Protocol(sys::IPPROTO_RAW);
 }
 
 impl From<c_int> for Protocol {
@@ -254,9 +382,11 @@ impl From<Protocol> for c_int {
 
 /// Flags for incoming messages.
 ///
-/// Flags provide additional information about incoming messages.
+/// Flags provide additional information about incoming messages. They are
+/// returned by the receive functions, such as [`Socket::recv_vectored`], to
+/// set flags for a receive call use [`RecvOptions`].
 #[cfg(not(target_os = "redox"))]
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Default, Eq, PartialEq)]
 pub struct RecvFlags(c_int);
 
 #[cfg(not(target_os = "redox"))]
@@ -271,11 +401,172 @@ impl RecvFlags {
     pub const fn is_truncated(self) -> bool {
         self.0 & sys::MSG_TRUNC != 0
     }
+
+    /// Create `RecvFlags` from the raw OS flags, e.g. the `msg_flags` field
+    /// returned by `recvmsg` called by another library.
+    pub const fn from_bits(bits: c_int) -> RecvFlags {
+        RecvFlags(bits)
+    }
+
+    /// Returns the raw OS flags.
+    ///
+    /// This is the same as converting the flags using [`c_int::from`].
+    pub const fn bits(self) -> c_int {
+        self.0
+    }
+}
+
+#[cfg(not(target_os = "redox"))]
+impl From<RecvFlags> for c_int {
+    fn from(flags: RecvFlags) -> c_int {
+        flags.0
+    }
+}
+
+#[cfg(not(target_os = "redox"))]
+impl From<c_int> for RecvFlags {
+    fn from(bits: c_int) -> RecvFlags {
+        RecvFlags(bits)
+    }
+}
+
+/// Flags to set for a receive call.
+///
+/// The constants, such as [`RecvOptions::PEEK`], can be combined and passed to
+/// the `recv*_with_flags` functions, such as [`Socket::recv_with_flags`].
+///
+/// # Examples
+///
+/// ```
+/// use std::mem::MaybeUninit;
+/// use std::net::UdpSocket;
+///
+/// use socket2::{RecvOptions, Socket};
+///
+/// # fn main() -> std::io::Result<()> {
+/// let sender = UdpSocket::bind("127.0.0.1:0")?;
+/// let socket = Socket::from(UdpSocket::bind("127.0.0.1:0")?);
+/// sender.send_to(b"hello", socket.local_addr()?.as_socket().unwrap())?;
+///
+/// // Peeking doesn't remove the data from the queue.
+/// let mut buf = [MaybeUninit::new(0); 16];
+/// let n = socket.recv_with_flags(&mut buf, RecvOptions::PEEK)?;
+/// assert_eq!(n, 5);
+/// assert_eq!(socket.recv_with_flags(&mut buf, RecvOptions::default())?, 5);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Copy, Clone, Default, Eq, PartialEq)]
+pub struct RecvOptions(c_int);
+
+impl RecvOptions {
+    /// Peek at incoming data without removing it from the queue, corresponding
+    /// to `MSG_PEEK`.
+    pub const PEEK: RecvOptions = RecvOptions(sys::MSG_PEEK);
+
+    /// Receive out-of-band data, corresponding to `MSG_OOB`.
+    pub const OUT_OF_BAND: RecvOptions = RecvOptions(sys::MSG_OOB);
+
+    /// Block until the full request is satisfied, corresponding to
+    /// `MSG_WAITALL`.
+    pub const WAIT_ALL: RecvOptions = RecvOptions(sys::MSG_WAITALL);
+}
+
+impl BitOr for RecvOptions {
+    type Output = RecvOptions;
+
+    fn bitor(self, rhs: RecvOptions) -> RecvOptions {
+        RecvOptions(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for RecvOptions {
+    fn bitor_assign(&mut self, rhs: RecvOptions) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl From<RecvOptions> for c_int {
+    fn from(flags: RecvOptions) -> c_int {
+        flags.0
+    }
+}
+
+impl fmt::Debug for RecvOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RecvOptions({:#x})", self.0)
+    }
+}
+
+/// Flags for outgoing messages.
+///
+/// The constants, such as [`SendFlags::OUT_OF_BAND`], can be combined and
+/// passed to the `send*_with_flags` functions, such as
+/// [`Socket::send_with_flags`], to set flags for the send call.
+///
+/// # Examples
+///
+/// ```
+/// use std::net::UdpSocket;
+///
+/// use socket2::{SendFlags, Socket};
+///
+/// # fn main() -> std::io::Result<()> {
+/// let receiver = UdpSocket::bind("127.0.0.1:0")?;
+/// let socket = Socket::from(UdpSocket::bind("127.0.0.1:0")?);
+/// socket.connect(&receiver.local_addr()?.into())?;
+///
+/// // Only send to directly connected hosts.
+/// let n = socket.send_with_flags(b"hello", SendFlags::DONT_ROUTE)?;
+/// assert_eq!(n, 5);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Copy, Clone, Default, Eq, PartialEq)]
+pub struct SendFlags(c_int);
+
+impl SendFlags {
+    /// Send out-of-band data, corresponding to `MSG_OOB`.
+    pub const OUT_OF_BAND: SendFlags = SendFlags(sys::MSG_OOB);
+
+    /// Don't use a gateway to send the data, only send to directly connected
+    /// hosts, corresponding to `MSG_DONTROUTE`.
+    pub const DONT_ROUTE: SendFlags = SendFlags(sys::MSG_DONTROUTE);
+}
+
+impl BitOr for SendFlags {
+    type Output = SendFlags;
+
+    fn bitor(self, rhs: SendFlags) -> SendFlags {
+        SendFlags(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for SendFlags {
+    fn bitor_assign(&mut self, rhs: SendFlags) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl From<SendFlags> for c_int {
+    fn from(flags: SendFlags) -> c_int {
+        flags.0
+    }
+}
+
+impl fmt::Debug for SendFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SendFlags({:#x})", self.0)
+    }
 }
 
 /// A version of [`IoSliceMut`] that allows the buffer to be uninitialised.
 ///
 /// [`IoSliceMut`]: std::io::IoSliceMut
+///
+/// This type is ABI compatible with `iovec` on Unix and `WSABUF` on Windows, so
+/// a slice of `MaybeUninitSlice`s can be passed to the OS directly, for example
+/// when submitting a vectored receive operation to `io_uring`.
 #[repr(transparent)]
 pub struct MaybeUninitSlice<'a>(sys::MaybeUninitSlice<'a>);
 
@@ -326,6 +617,7 @@ pub struct TcpKeepalive {
 
 impl TcpKeepalive {
     /// Returns a new, empty set of TCP keepalive parameters.
+    #[allow(clippy::new_without_default)]
     pub const fn new() -> TcpKeepalive {
         TcpKeepalive {
             time: None,
@@ -339,8 +631,9 @@ impl TcpKeepalive {
     ///
     /// This will set the value of `SO_KEEPALIVE` on OpenBSD and Haiku,
     /// `TCP_KEEPALIVE` on macOS and iOS, and `TCP_KEEPIDLE` on all other Unix
-    /// operating systems. On Windows, this sets the value of the
-    /// `tcp_keepalive` struct's `keepalivetime` field.
+    /// operating systems. On Windows, this sets the value of `TCP_KEEPIDLE`,
+    /// falling back to the `tcp_keepalive` struct's `keepalivetime` field on
+    /// versions before Windows 10, version 1709.
     ///
     /// Some platforms specify this value in seconds, so sub-second
     /// specifications may be omitted.
@@ -351,8 +644,9 @@ impl TcpKeepalive {
         }
     }
 
-    /// Set the value of the `TCP_KEEPINTVL` option. On Windows, this sets the
-    /// value of the `tcp_keepalive` struct's `keepaliveinterval` field.
+    /// Set the value of the `TCP_KEEPINTVL` option. On Windows versions before
+    /// Windows 10, version 1709, this sets the value of the `tcp_keepalive`
+    /// struct's `keepaliveinterval` field.
     ///
     /// Sets the time interval between TCP keepalive probes.
     ///
@@ -380,6 +674,8 @@ impl TcpKeepalive {
     ///
     /// Set the maximum number of TCP keepalive probes that will be sent before
     /// dropping a connection, if TCP keepalive is enabled on this socket.
+    ///
+    /// On Windows this requires Windows 10, version 1709 or later.
     #[cfg(all(
         feature = "all",
         any(
@@ -388,6 +684,7 @@ impl TcpKeepalive {
             target_os = "linux",
             target_os = "netbsd",
             target_vendor = "apple",
+            windows,
         )
     ))]
     pub const fn with_retries(self, retries: u32) -> Self {
@@ -397,3 +694,21 @@ impl TcpKeepalive {
         }
     }
 }
+
+/// Interface to use for IPv4 multicast, either by index or by address.
+///
+/// See [`Socket::join_multicast_v4_n`].
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "fuchsia",
+    target_os = "linux",
+    windows,
+))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum InterfaceIndexOrAddress {
+    /// An interface index, see `if_nametoindex(3)`.
+    Index(u32),
+    /// Address of the interface.
+    Address(Ipv4Addr),
+}
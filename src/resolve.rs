@@ -0,0 +1,208 @@
+use std::ffi::CString;
+use std::io;
+use std::mem;
+use std::ptr;
+
+use crate::sys::{self, addrinfo, c_int, socklen_t};
+use crate::{Domain, Protocol, SockAddr, Type};
+
+/// Hints used in [`getaddrinfo`] to restrict the returned addresses.
+///
+/// By default all domains, types and protocols are accepted.
+///
+/// # Examples
+///
+/// ```
+/// use socket2::{AddrInfoHints, Domain, Type};
+///
+/// let hints = AddrInfoHints::new()
+///     .with_domain(Domain::IPV4)
+///     .with_type(Type::STREAM);
+/// # drop(hints);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AddrInfoHints {
+    domain: Option<Domain>,
+    ty: Option<Type>,
+    protocol: Option<Protocol>,
+    passive: bool,
+}
+
+impl AddrInfoHints {
+    /// Returns hints that accept any address.
+    pub const fn new() -> AddrInfoHints {
+        AddrInfoHints {
+            domain: None,
+            ty: None,
+            protocol: None,
+            passive: false,
+        }
+    }
+
+    /// Only return addresses for `domain`, setting `ai_family`.
+    pub const fn with_domain(self, domain: Domain) -> AddrInfoHints {
+        AddrInfoHints {
+            domain: Some(domain),
+            ..self
+        }
+    }
+
+    /// Only return addresses for `ty`, setting `ai_socktype`.
+    pub const fn with_type(self, ty: Type) -> AddrInfoHints {
+        AddrInfoHints {
+            ty: Some(ty),
+            ..self
+        }
+    }
+
+    /// Only return addresses for `protocol`, setting `ai_protocol`.
+    pub const fn with_protocol(self, protocol: Protocol) -> AddrInfoHints {
+        AddrInfoHints {
+            protocol: Some(protocol),
+            ..self
+        }
+    }
+
+    /// Return addresses suitable for binding a socket, setting `AI_PASSIVE`.
+    ///
+    /// If no host is passed to [`getaddrinfo`] this returns the unspecified
+    /// addresses, e.g. `0.0.0.0` and `::`.
+    pub const fn with_passive(self, passive: bool) -> AddrInfoHints {
+        AddrInfoHints { passive, ..self }
+    }
+}
+
+/// A single address returned by [`getaddrinfo`].
+///
+/// Next to the address it contains the domain, type and protocol to use when
+/// creating a socket for the address.
+#[derive(Debug)]
+pub struct AddrInfo {
+    address: SockAddr,
+    domain: Domain,
+    ty: Type,
+    protocol: Protocol,
+}
+
+impl AddrInfo {
+    /// Returns the address.
+    pub const fn address(&self) -> &SockAddr {
+        &self.address
+    }
+
+    /// Returns the domain of the address, from `ai_family`.
+    pub const fn domain(&self) -> Domain {
+        self.domain
+    }
+
+    /// Returns the type of the socket to use, from `ai_socktype`.
+    pub const fn r#type(&self) -> Type {
+        self.ty
+    }
+
+    /// Returns the protocol of the socket to use, from `ai_protocol`.
+    pub const fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    /// Converts the address info in a tuple of the address, domain, type and
+    /// protocol.
+    pub fn into_parts(self) -> (SockAddr, Domain, Type, Protocol) {
+        (self.address, self.domain, self.ty, self.protocol)
+    }
+}
+
+/// Resolve `host` and/or `service` into addresses.
+///
+/// This wraps `getaddrinfo(3)` on Unix and `getaddrinfo` on Windows. Unlike
+/// [`ToSocketAddrs`] this keeps the domain, type and protocol information of
+/// every address, which can be passed to [`Socket::new`] directly.
+///
+/// [`ToSocketAddrs`]: std::net::ToSocketAddrs
+/// [`Socket::new`]: crate::Socket::new
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> std::io::Result<()> {
+/// use socket2::{getaddrinfo, AddrInfoHints, Socket, Type};
+///
+/// let hints = AddrInfoHints::new().with_type(Type::STREAM);
+/// for info in getaddrinfo(Some("localhost"), Some("8080"), &hints)? {
+///     let socket = Socket::new(info.domain(), info.r#type(), Some(info.protocol()))?;
+///     // Connect `socket` to `info.address()`.
+/// #   drop(socket);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn getaddrinfo(
+    host: Option<&str>,
+    service: Option<&str>,
+    hints: &AddrInfoHints,
+) -> io::Result<Vec<AddrInfo>> {
+    let host = host.map(to_cstring).transpose()?;
+    let service = service.map(to_cstring).transpose()?;
+
+    // Safety: all zeroes is a valid `addrinfo`.
+    let mut raw_hints: addrinfo = unsafe { mem::zeroed() };
+    raw_hints.ai_family = hints.domain.map_or(0, c_int::from);
+    raw_hints.ai_socktype = hints.ty.map_or(0, c_int::from);
+    raw_hints.ai_protocol = hints.protocol.map_or(0, c_int::from);
+    if hints.passive {
+        raw_hints.ai_flags = sys::AI_PASSIVE;
+    }
+
+    let list = sys::getaddrinfo(host.as_deref(), service.as_deref(), &raw_hints)?;
+    let mut infos = Vec::new();
+    let mut next = list;
+    while !next.is_null() {
+        // Safety: `getaddrinfo` returned a valid linked list.
+        let info = unsafe { &*next };
+        next = info.ai_next;
+        if info.ai_addr.is_null() {
+            continue;
+        }
+        let res = unsafe {
+            SockAddr::init(|storage, len| {
+                let addr_len = info.ai_addrlen as socklen_t;
+                if addr_len > *len {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "address too large for storage",
+                    ));
+                }
+                ptr::copy_nonoverlapping(
+                    info.ai_addr.cast::<u8>(),
+                    storage.cast::<u8>(),
+                    addr_len as usize,
+                );
+                *len = addr_len;
+                Ok(())
+            })
+        };
+        match res {
+            Ok(((), address)) => infos.push(AddrInfo {
+                address,
+                domain: Domain::from(info.ai_family),
+                ty: Type::from(info.ai_socktype),
+                protocol: Protocol::from(info.ai_protocol),
+            }),
+            Err(err) => {
+                unsafe { sys::freeaddrinfo(list) };
+                return Err(err);
+            }
+        }
+    }
+    unsafe { sys::freeaddrinfo(list) };
+    Ok(infos)
+}
+
+fn to_cstring(s: &str) -> io::Result<CString> {
+    CString::new(s).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "host or service contains a nul byte",
+        )
+    })
+}
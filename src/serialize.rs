@@ -0,0 +1,242 @@
+//! Implementations of serde's `Serialize` and `Deserialize` traits.
+//!
+//! `SockAddr` is (de)serialized using the textual form of [`SocketAddr`], e.g.
+//! `127.0.0.1:8080` or `[::1]:8080`, which is the same on all OSs. `Domain`,
+//! `Type` and `Protocol` are (de)serialized using the lower case name of the
+//! well-known constant, e.g. `ipv4`, `stream` or `tcp`, as the raw value
+//! differs per OS. Values without a name use their raw integer value.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::net::SocketAddr;
+
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{self, Serialize, Serializer};
+
+use crate::{Domain, Protocol, SockAddr, Type};
+
+impl Serialize for SockAddr {
+    /// Only IPv4 and IPv6 addresses can be serialized, serializing an address
+    /// of any other family returns an error.
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.as_socket() {
+            Some(addr) => serializer.collect_str(&addr),
+            None => Err(ser::Error::custom(
+                "only IPv4 and IPv6 socket addresses can be serialized",
+            )),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SockAddr {
+    fn deserialize<D>(deserializer: D) -> Result<SockAddr, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct SockAddrVisitor;
+
+        impl<'de> Visitor<'de> for SockAddrVisitor {
+            type Value = SockAddr;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an IPv4 or IPv6 socket address")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<SockAddr, E>
+            where
+                E: de::Error,
+            {
+                value
+                    .parse::<SocketAddr>()
+                    .map(SockAddr::from)
+                    .map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(SockAddrVisitor)
+    }
+}
+
+/// Names of the well-known domains.
+const DOMAINS: &[(Domain, &str)] = &[
+    (Domain::IPV4, "ipv4"),
+    (Domain::IPV6, "ipv6"),
+    #[cfg(unix)]
+    (Domain::UNIX, "unix"),
+    #[cfg(all(
+        feature = "all",
+        any(target_os = "android", target_os = "fuchsia", target_os = "linux")
+    ))]
+    (Domain::PACKET, "packet"),
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    (Domain::VSOCK, "vsock"),
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    (Domain::NETLINK, "netlink"),
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    (Domain::RDS, "rds"),
+];
+
+/// Names of the well-known types.
+const TYPES: &[(Type, &str)] = &[
+    (Type::STREAM, "stream"),
+    (Type::DGRAM, "dgram"),
+    #[cfg(feature = "all")]
+    (Type::SEQPACKET, "seqpacket"),
+    #[cfg(all(feature = "all", not(target_os = "redox")))]
+    (Type::RAW, "raw"),
+    #[cfg(all(feature = "all", not(any(target_os = "redox", target_os = "haiku"))))]
+    (Type::RDM, "rdm"),
+];
+
+/// Names of the well-known protocols.
+const PROTOCOLS: &[(Protocol, &str)] = &[
+    (Protocol::ICMPV4, "icmpv4"),
+    (Protocol::ICMPV6, "icmpv6"),
+    (Protocol::TCP, "tcp"),
+    (Protocol::UDP, "udp"),
+    #[cfg(all(feature = "all", not(target_os = "redox")))]
+    (Protocol::RAW, "raw"),
+    #[cfg(all(
+        feature = "all",
+        any(target_os = "android", target_os = "freebsd", target_os = "linux")
+    ))]
+    (Protocol::SCTP, "sctp"),
+];
+
+/// Implements `Serialize` and `Deserialize` for a newtype around `c_int`,
+/// using the `names` of the well-known values.
+macro_rules! impl_serde_c_int {
+    ($( $type: ident, $names: ident, $expecting: expr; )*) => {
+        $(
+            impl Serialize for $type {
+                fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: Serializer,
+                {
+                    match $names.iter().find(|(value, _)| value == self) {
+                        Some((_, name)) => serializer.serialize_str(name),
+                        None => serializer.serialize_i32(self.0),
+                    }
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $type {
+                fn deserialize<D>(deserializer: D) -> Result<$type, D::Error>
+                where
+                    D: Deserializer<'de>,
+                {
+                    struct CIntVisitor;
+
+                    impl<'de> Visitor<'de> for CIntVisitor {
+                        type Value = $type;
+
+                        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                            f.write_str($expecting)
+                        }
+
+                        fn visit_str<E>(self, value: &str) -> Result<$type, E>
+                        where
+                            E: de::Error,
+                        {
+                            match $names.iter().find(|(_, name)| *name == value) {
+                                Some((value, _)) => Ok(*value),
+                                None => Err(E::invalid_value(de::Unexpected::Str(value), &self)),
+                            }
+                        }
+
+                        fn visit_i64<E>(self, value: i64) -> Result<$type, E>
+                        where
+                            E: de::Error,
+                        {
+                            i32::try_from(value).map($type).map_err(|_| {
+                                E::invalid_value(de::Unexpected::Signed(value), &self)
+                            })
+                        }
+
+                        fn visit_u64<E>(self, value: u64) -> Result<$type, E>
+                        where
+                            E: de::Error,
+                        {
+                            i32::try_from(value).map($type).map_err(|_| {
+                                E::invalid_value(de::Unexpected::Unsigned(value), &self)
+                            })
+                        }
+                    }
+
+                    deserializer.deserialize_any(CIntVisitor)
+                }
+            }
+        )*
+    };
+}
+
+impl_serde_c_int!(
+    Domain, DOMAINS, "a socket domain name or integer";
+    Type, TYPES, "a socket type name or integer";
+    Protocol, PROTOCOLS, "a protocol name or integer";
+);
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
+
+    use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_tokens, Token};
+
+    use crate::{Domain, Protocol, SockAddr, Type};
+
+    #[test]
+    fn sockaddr() {
+        let addr = SockAddr::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 8080));
+        assert_tokens(&addr, &[Token::Str("127.0.0.1:8080")]);
+        let addr = SockAddr::from(SocketAddrV6::new(Ipv6Addr::LOCALHOST, 8080, 0, 0));
+        assert_tokens(&addr, &[Token::Str("[::1]:8080")]);
+
+        assert_de_tokens_error::<SockAddr>(
+            &[Token::Str("localhost")],
+            "invalid socket address syntax",
+        );
+    }
+
+    #[test]
+    #[cfg(all(unix, feature = "all"))]
+    fn sockaddr_unix() {
+        let addr = SockAddr::unix("/tmp/socket2").unwrap();
+        serde_test::assert_ser_tokens_error(
+            &addr,
+            &[],
+            "only IPv4 and IPv6 socket addresses can be serialized",
+        );
+    }
+
+    #[test]
+    fn c_int() {
+        assert_tokens(&Domain::IPV4, &[Token::Str("ipv4")]);
+        assert_tokens(&Domain::IPV6, &[Token::Str("ipv6")]);
+        assert_tokens(&Type::STREAM, &[Token::Str("stream")]);
+        assert_tokens(&Type::DGRAM, &[Token::Str("dgram")]);
+        assert_tokens(&Protocol::TCP, &[Token::Str("tcp")]);
+        assert_tokens(&Protocol::UDP, &[Token::Str("udp")]);
+
+        // Values without a name use the raw value.
+        assert_tokens(&Protocol::from(1234), &[Token::I32(1234)]);
+        assert_de_tokens(&Protocol::from(1234), &[Token::U64(1234)]);
+
+        assert_de_tokens_error::<Domain>(
+            &[Token::Str("ipv5")],
+            "invalid value: string \"ipv5\", expected a socket domain name or integer",
+        );
+        assert_de_tokens_error::<Type>(
+            &[Token::I64(i64::MAX)],
+            "invalid value: integer `9223372036854775807`, expected a socket type name or integer",
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn c_int_unix() {
+        assert_tokens(&Domain::UNIX, &[Token::Str("unix")]);
+    }
+}
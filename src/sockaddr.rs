@@ -1,6 +1,8 @@
+use std::hash::{Hash, Hasher};
 use std::mem::{self, size_of, MaybeUninit};
-use std::net::{SocketAddr, SocketAddrV4, SocketAddrV6};
-use std::{fmt, io};
+use std::net::{AddrParseError, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::str::FromStr;
+use std::{fmt, io, slice};
 
 use crate::sys::{
     sa_family_t, sockaddr, sockaddr_in, sockaddr_in6, sockaddr_storage, socklen_t, AF_INET,
@@ -19,6 +21,40 @@ pub struct SockAddr {
 }
 
 impl SockAddr {
+    /// Create a `SockAddr` from the underlying storage and its length.
+    ///
+    /// This is useful if the address is filled in by an operation outside of
+    /// this crate, for example a completion based API such as `io_uring`, which
+    /// requires the storage to have a stable address until the operation
+    /// completes.
+    ///
+    /// # Safety
+    ///
+    /// Caller must ensure that the address family and length match the type of
+    /// storage address. For example if `storage.ss_family` is set to `AF_INET`
+    /// the `storage` must be initialised as `sockaddr_in`, setting the content
+    /// and length appropriately.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::net::SocketAddr;
+    ///
+    /// use socket2::SockAddr;
+    ///
+    /// // The storage and length would normally be filled in by the OS, e.g. as
+    /// // the result of an `accept` operation submitted to `io_uring`.
+    /// let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+    /// let addr = SockAddr::from(addr);
+    /// let (storage, len) = (addr.as_storage(), addr.len());
+    ///
+    /// let address = unsafe { SockAddr::new(storage, len) };
+    /// assert_eq!(address.as_socket(), addr.as_socket());
+    /// ```
+    pub const unsafe fn new(storage: sockaddr_storage, len: socklen_t) -> SockAddr {
+        SockAddr { storage, len }
+    }
+
     /// Initialise a `SockAddr` by calling the function `init`.
     ///
     /// The type of the address storage and length passed to the function `init`
@@ -28,8 +64,9 @@ impl SockAddr {
     ///
     /// Caller must initialise the provided address storage and set the length
     /// properly. The address is zeroed before `init` is called and is thus
-    /// valid to dereference and read from. The length initialised to the
-    /// maximum length of the storage.
+    /// valid to dereference and read from. The length is initialised to the
+    /// maximum length of the storage, `init` must set it to the actual length
+    /// of the address.
     ///
     /// # Examples
     ///
@@ -43,10 +80,10 @@ impl SockAddr {
     /// # fn main() -> io::Result<()> {
     /// let socket = Socket::new(Domain::IPV4, Type::STREAM, None)?;
     ///
-    /// // Initialise a `SocketAddr` byte calling `getsockname(2)`.
+    /// // Initialise a `SocketAddr` by calling `getsockname(2)`.
     /// let (_, address) = unsafe {
     ///     SockAddr::init(|addr_storage, len| {
-    ///         // The `getsockname(2)` system call will intiliase `storage` for
+    ///         // The `getsockname(2)` system call will initialise `storage` for
     ///         // us, setting `len` to the correct length.
     ///         if libc::getsockname(socket.as_raw_fd(), addr_storage.cast(), len) == -1 {
     ///             Err(io::Error::last_os_error())
@@ -83,12 +120,56 @@ impl SockAddr {
         })
     }
 
+    /// Same as [`SockAddr::init`], but doesn't zero the storage before calling
+    /// `init`.
+    ///
+    /// Only the bytes after the length set by `init` are zeroed afterwards,
+    /// which avoids zeroing the entire storage for system calls that (almost)
+    /// always write the address, e.g. `accept(2)` and `recvfrom(2)`.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`SockAddr::init`], but the storage passed to `init` is
+    /// uninitialised, so it must not be read. After `init` returns `Ok` the
+    /// first `len` bytes must be initialised, if the OS didn't write the
+    /// address `init` must set `len` to zero.
+    ///
+    /// Not used on Windows, as it doesn't write the address for
+    /// connection-oriented sockets in e.g. `recvfrom`.
+    #[cfg(unix)]
+    pub(crate) unsafe fn init_prefix<F, T>(init: F) -> io::Result<(T, SockAddr)>
+    where
+        F: FnOnce(*mut sockaddr_storage, *mut socklen_t) -> io::Result<T>,
+    {
+        const STORAGE_SIZE: socklen_t = size_of::<sockaddr_storage>() as socklen_t;
+        let mut storage = MaybeUninit::<sockaddr_storage>::uninit();
+        let mut len = STORAGE_SIZE;
+        init(storage.as_mut_ptr(), &mut len).map(|res| {
+            debug_assert!(len <= STORAGE_SIZE, "overflown address storage");
+            let init_len = (len as usize).min(size_of::<sockaddr_storage>());
+            // Safety: `init_len` is within the bounds of `storage`.
+            storage
+                .as_mut_ptr()
+                .cast::<u8>()
+                .add(init_len)
+                .write_bytes(0, size_of::<sockaddr_storage>() - init_len);
+            let addr = SockAddr {
+                // Safety: caller must ensure the first `len` bytes are
+                // initialised, we zeroed the remainder above.
+                storage: storage.assume_init(),
+                len,
+            };
+            (res, addr)
+        })
+    }
+
     /// Returns this address's family.
     pub const fn family(&self) -> sa_family_t {
         self.storage.ss_family
     }
 
     /// Returns the size of this address in bytes.
+    #[allow(clippy::len_without_is_empty)]
     pub const fn len(&self) -> socklen_t {
         self.len
     }
@@ -98,6 +179,74 @@ impl SockAddr {
         &self.storage as *const _ as *const _
     }
 
+    /// Returns the underlying address storage.
+    ///
+    /// Only the first [`len`] bytes of the storage are meaningful, the contents
+    /// of the remainder are unspecified.
+    ///
+    /// [`len`]: SockAddr::len
+    pub const fn as_storage(&self) -> sockaddr_storage {
+        self.storage
+    }
+
+    /// Returns a reference to the underlying address storage.
+    ///
+    /// Unlike [`as_storage`] this doesn't copy the storage, so its address is
+    /// stable for as long as the `SockAddr` isn't moved. This makes it usable
+    /// for completion based APIs, such as `io_uring`, which read the address
+    /// (e.g. for `connect` or `sendmsg`) after the submission returns.
+    ///
+    /// [`as_storage`]: SockAddr::as_storage
+    pub const fn as_storage_ref(&self) -> &sockaddr_storage {
+        &self.storage
+    }
+
+    /// Returns a mutable raw pointer to the underlying address storage.
+    ///
+    /// This allows an operation outside of this crate, for example an
+    /// `accept` or `recvmsg` operation submitted to `io_uring`, to fill in the
+    /// address in place. Afterwards [`set_length`] must be used to set the
+    /// length of the address written.
+    ///
+    /// [`set_length`]: SockAddr::set_length
+    pub fn as_mut_ptr(&mut self) -> *mut sockaddr_storage {
+        &mut self.storage
+    }
+
+    /// Returns a raw pointer to the length of the address.
+    #[cfg(all(feature = "all", target_os = "linux"))]
+    pub(crate) fn len_ptr(&self) -> *const socklen_t {
+        &self.len
+    }
+
+    /// Returns a mutable raw pointer to the length of the address.
+    #[cfg(all(feature = "all", target_os = "linux"))]
+    pub(crate) fn as_mut_len_ptr(&mut self) -> *mut socklen_t {
+        &mut self.len
+    }
+
+    /// Set the length of the address.
+    ///
+    /// # Safety
+    ///
+    /// Caller must ensure that the first `length` bytes of the storage (see
+    /// [`SockAddr::as_mut_ptr`]) are a valid address of the family in the
+    /// storage, and that `length` is not larger than the size of
+    /// `sockaddr_storage`.
+    pub unsafe fn set_length(&mut self, length: socklen_t) {
+        debug_assert!(length as usize <= size_of::<sockaddr_storage>());
+        self.len = length;
+    }
+
+    /// Returns the significant bytes of the address, i.e. the first `len`
+    /// bytes of the storage.
+    fn as_bytes(&self) -> &[u8] {
+        let len = (self.len as usize).min(size_of::<sockaddr_storage>());
+        // Safety: `len` is within the bounds of `storage`, which is fully
+        // initialised.
+        unsafe { slice::from_raw_parts((&self.storage as *const sockaddr_storage).cast(), len) }
+    }
+
     /// Returns a raw pointer to the address storage.
     #[cfg(all(unix, not(target_os = "redox")))]
     pub(crate) const fn as_storage_ptr(&self) -> *const sockaddr_storage {
@@ -106,6 +255,24 @@ impl SockAddr {
 
     /// Returns this address as a `SocketAddr` if it is in the `AF_INET` (IPv4)
     /// or `AF_INET6` (IPv6) family, otherwise returns `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> std::io::Result<()> {
+    /// use std::net::SocketAddr;
+    ///
+    /// use socket2::{Domain, Socket, Type};
+    ///
+    /// let socket = Socket::new(Domain::IPV4, Type::STREAM, None)?;
+    /// let address: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    /// socket.bind(&address.into())?;
+    ///
+    /// let local_addr: SocketAddr = socket.local_addr()?.as_socket().unwrap();
+    /// assert!(local_addr.ip().is_loopback());
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn as_socket(&self) -> Option<SocketAddr> {
         if self.storage.ss_family == AF_INET as sa_family_t {
             // Safety: if the ss_family field is AF_INET then storage must be a sockaddr_in.
@@ -153,6 +320,78 @@ impl SockAddr {
             _ => None,
         }
     }
+
+    /// Encodes this address in a portable binary format.
+    ///
+    /// Unlike the raw `sockaddr_storage` bytes, which use OS specific family
+    /// values and layouts, the encoding is the same on all OSs, so it can be
+    /// passed to other processes or machines and decoded using
+    /// [`SockAddr::from_bytes`]. The layout is a family tag followed by the
+    /// address, with all integers in big-endian byte order:
+    ///
+    ///  * IPv4: `4`, address (4 bytes), port (2 bytes), 7 bytes in total.
+    ///  * IPv6: `6`, address (16 bytes), port (2 bytes), flow info (4 bytes),
+    ///    scope id (4 bytes), 27 bytes in total.
+    ///
+    /// Returns an error if the address is not an IPv4 or IPv6 address.
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        match self.as_socket() {
+            Some(SocketAddr::V4(addr)) => {
+                let mut bytes = Vec::with_capacity(7);
+                bytes.push(4);
+                bytes.extend_from_slice(&addr.ip().octets());
+                bytes.extend_from_slice(&addr.port().to_be_bytes());
+                Ok(bytes)
+            }
+            Some(SocketAddr::V6(addr)) => {
+                let mut bytes = Vec::with_capacity(27);
+                bytes.push(6);
+                bytes.extend_from_slice(&addr.ip().octets());
+                bytes.extend_from_slice(&addr.port().to_be_bytes());
+                bytes.extend_from_slice(&addr.flowinfo().to_be_bytes());
+                bytes.extend_from_slice(&addr.scope_id().to_be_bytes());
+                Ok(bytes)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "only IPv4 and IPv6 addresses can be encoded",
+            )),
+        }
+    }
+
+    /// Decodes an address encoded by [`SockAddr::to_bytes`].
+    ///
+    /// Returns an error if `bytes` is not a valid encoding.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<SockAddr> {
+        fn be_u16(bytes: &[u8]) -> u16 {
+            u16::from_be_bytes([bytes[0], bytes[1]])
+        }
+        fn be_u32(bytes: &[u8]) -> u32 {
+            u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+        }
+
+        match bytes {
+            [4, rest @ ..] if rest.len() == 6 => {
+                let ip = Ipv4Addr::new(rest[0], rest[1], rest[2], rest[3]);
+                Ok(SocketAddrV4::new(ip, be_u16(&rest[4..])).into())
+            }
+            [6, rest @ ..] if rest.len() == 26 => {
+                let mut octets = [0; 16];
+                octets.copy_from_slice(&rest[..16]);
+                let addr = SocketAddrV6::new(
+                    Ipv6Addr::from(octets),
+                    be_u16(&rest[16..]),
+                    be_u32(&rest[18..]),
+                    be_u32(&rest[22..]),
+                );
+                Ok(addr.into())
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid encoded socket address",
+            )),
+        }
+    }
 }
 
 impl From<SocketAddr> for SockAddr {
@@ -169,7 +408,7 @@ impl From<SocketAddrV4> for SockAddr {
         let sockaddr_in = sockaddr_in {
             sin_family: AF_INET as sa_family_t,
             sin_port: addr.port().to_be(),
-            sin_addr: crate::sys::to_in_addr(&addr.ip()),
+            sin_addr: crate::sys::to_in_addr(addr.ip()),
             sin_zero: Default::default(),
             #[cfg(any(
                 target_os = "dragonfly",
@@ -254,9 +493,53 @@ impl fmt::Debug for SockAddr {
     }
 }
 
+/// Formats the address.
+///
+/// IPv4 and IPv6 addresses are formatted the same way as [`SocketAddr`],
+/// Unix addresses are formatted as their path, using `@` as prefix for
+/// addresses in the abstract namespace. Addresses of other families are
+/// formatted as `<unknown family N>`.
+impl fmt::Display for SockAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(addr) = self.as_socket() {
+            return fmt::Display::fmt(&addr, f);
+        }
+        #[cfg(unix)]
+        if self.family() == crate::sys::AF_UNIX as sa_family_t {
+            return crate::sys::fmt_unix_addr(self, f);
+        }
+        write!(f, "<unknown family {}>", self.family())
+    }
+}
+
+/// Parses an IPv4 or IPv6 address with a port, e.g. `127.0.0.1:8080` or
+/// `[::1]:8080`, see [`SocketAddr`]'s `FromStr` implementation.
+impl FromStr for SockAddr {
+    type Err = AddrParseError;
+
+    fn from_str(s: &str) -> Result<SockAddr, AddrParseError> {
+        s.parse::<SocketAddr>().map(SockAddr::from)
+    }
+}
+
+/// Compares the address family and the significant bytes of the addresses,
+/// that is the first [`SockAddr::len`] bytes.
+impl PartialEq for SockAddr {
+    fn eq(&self, other: &SockAddr) -> bool {
+        self.family() == other.family() && self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for SockAddr {}
+
+impl Hash for SockAddr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state);
+    }
+}
+
 #[test]
 fn ipv4() {
-    use std::net::Ipv4Addr;
     let std = SocketAddrV4::new(Ipv4Addr::new(1, 2, 3, 4), 9876);
     let addr = SockAddr::from(std);
     assert_eq!(addr.family(), AF_INET as sa_family_t);
@@ -275,7 +558,6 @@ fn ipv4() {
 
 #[test]
 fn ipv6() {
-    use std::net::Ipv6Addr;
     let std = SocketAddrV6::new(Ipv6Addr::new(1, 2, 3, 4, 5, 6, 7, 8), 9876, 11, 12);
     let addr = SockAddr::from(std);
     assert_eq!(addr.family(), AF_INET6 as sa_family_t);
@@ -291,3 +573,126 @@ fn ipv6() {
     assert!(addr.as_socket_ipv4().is_none());
     assert_eq!(addr.as_socket_ipv6(), Some(std));
 }
+
+#[test]
+fn storage_roundtrip() {
+    let std: SocketAddr = "[::1]:9876".parse().unwrap();
+    let addr = SockAddr::from(std);
+    let addr = unsafe { SockAddr::new(addr.as_storage(), addr.len()) };
+    assert_eq!(addr.family(), AF_INET6 as sa_family_t);
+    assert_eq!(addr.len(), size_of::<sockaddr_in6>() as socklen_t);
+    assert_eq!(addr.as_socket(), Some(std));
+}
+
+#[test]
+fn storage_in_place() {
+    let std: SocketAddr = "127.0.0.1:9876".parse().unwrap();
+    let src = SockAddr::from(std);
+    assert!(std::ptr::eq(src.as_storage_ref(), src.as_ptr().cast()));
+
+    let mut addr = unsafe { SockAddr::new(mem::zeroed(), 0) };
+    // Fill in the address like the OS would.
+    unsafe {
+        addr.as_mut_ptr().write(*src.as_storage_ref());
+        addr.set_length(src.len());
+    }
+    assert_eq!(addr, src);
+    assert_eq!(addr.as_socket(), Some(std));
+}
+
+#[test]
+#[cfg(unix)]
+fn init_prefix() {
+    let std: SocketAddr = "127.0.0.1:9876".parse().unwrap();
+    let src = SockAddr::from(std);
+    let (_, addr) = unsafe {
+        SockAddr::init_prefix(|storage, len| {
+            // Only write the address itself, like the OS would.
+            std::ptr::copy_nonoverlapping(
+                src.as_ptr().cast::<u8>(),
+                storage.cast::<u8>(),
+                src.len() as usize,
+            );
+            *len = src.len();
+            Ok(())
+        })
+    }
+    .unwrap();
+    assert_eq!(addr, src);
+    assert_eq!(addr.as_socket(), Some(std));
+    let storage = addr.as_storage();
+    // Safety: `sockaddr_storage` is plain old data.
+    let bytes = unsafe {
+        slice::from_raw_parts(
+            (&storage as *const sockaddr_storage).cast::<u8>(),
+            size_of::<sockaddr_storage>(),
+        )
+    };
+    assert!(bytes[src.len() as usize..].iter().all(|b| *b == 0));
+
+    // Nothing written.
+    let (_, addr) = unsafe {
+        SockAddr::init_prefix(|_, len| {
+            *len = 0;
+            Ok(())
+        })
+    }
+    .unwrap();
+    assert_eq!(addr.len(), 0);
+    assert_eq!(addr.family(), 0);
+}
+
+#[test]
+fn display_from_str() {
+    let addr: SockAddr = "127.0.0.1:9876".parse().unwrap();
+    assert_eq!(addr.family(), AF_INET as sa_family_t);
+    assert_eq!(addr.to_string(), "127.0.0.1:9876");
+
+    let addr: SockAddr = "[::1]:9876".parse().unwrap();
+    assert_eq!(addr.family(), AF_INET6 as sa_family_t);
+    assert_eq!(addr.to_string(), "[::1]:9876");
+
+    assert!("127.0.0.1".parse::<SockAddr>().is_err());
+}
+
+#[test]
+fn eq_hash() {
+    use std::collections::hash_map::DefaultHasher;
+
+    fn hash(addr: &SockAddr) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        addr.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    let a: SockAddr = "127.0.0.1:9876".parse().unwrap();
+    let b: SockAddr = "127.0.0.1:9876".parse().unwrap();
+    assert_eq!(a, b);
+    assert_eq!(hash(&a), hash(&b));
+
+    let c: SockAddr = "127.0.0.1:9877".parse().unwrap();
+    assert_ne!(a, c);
+    let d: SockAddr = "[::ffff:127.0.0.1]:9876".parse().unwrap();
+    assert_ne!(a, d);
+}
+
+#[test]
+fn to_from_bytes() {
+    let addr: SockAddr = "1.2.3.4:9876".parse().unwrap();
+    let bytes = addr.to_bytes().unwrap();
+    assert_eq!(bytes, [4, 1, 2, 3, 4, 0x26, 0x94]);
+    assert_eq!(SockAddr::from_bytes(&bytes).unwrap(), addr);
+
+    let std = SocketAddrV6::new(Ipv6Addr::new(1, 2, 3, 4, 5, 6, 7, 8), 9876, 11, 12);
+    let addr = SockAddr::from(std);
+    let bytes = addr.to_bytes().unwrap();
+    assert_eq!(bytes.len(), 27);
+    assert_eq!(bytes[0], 6);
+    assert_eq!(&bytes[17..], [0x26, 0x94, 0, 0, 0, 11, 0, 0, 0, 12]);
+    let decoded = SockAddr::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded.as_socket_ipv6(), Some(std));
+
+    assert!(SockAddr::from_bytes(&[]).is_err());
+    assert!(SockAddr::from_bytes(&bytes[..26]).is_err());
+    assert!(SockAddr::from_bytes(&[5, 1, 2, 3, 4, 0, 0]).is_err());
+}
@@ -6,6 +6,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::convert::{TryFrom, TryInto};
 use std::fmt;
 use std::io::{self, Read, Write};
 #[cfg(not(target_os = "redox"))]
@@ -16,10 +17,20 @@ use std::net::{self, Ipv4Addr, Ipv6Addr, Shutdown};
 use std::os::unix::io::{FromRawFd, IntoRawFd};
 #[cfg(windows)]
 use std::os::windows::io::{FromRawSocket, IntoRawSocket};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crate::sys::{self, c_int, getsockopt, setsockopt, Bool};
-use crate::{Domain, Protocol, SockAddr, TcpKeepalive, Type};
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "fuchsia",
+    target_os = "linux",
+    windows,
+))]
+use crate::InterfaceIndexOrAddress;
+use crate::{
+    Domain, ListenerOptions, Protocol, SockAddr, SocketBuilder, TcpKeepalive, Type, UdpOptions,
+};
 #[cfg(not(target_os = "redox"))]
 use crate::{MaybeUninitSlice, RecvFlags};
 
@@ -88,6 +99,43 @@ impl Socket {
         Socket::new_raw(domain, ty, protocol).and_then(set_common_flags)
     }
 
+    /// Returns a [`SocketBuilder`] to create a new socket, set options and bind
+    /// or connect it in a single chain.
+    ///
+    /// See [`SocketBuilder`] for an example.
+    pub const fn builder(domain: Domain, ty: Type) -> SocketBuilder {
+        SocketBuilder::new(domain, ty)
+    }
+
+    /// Creates a new TCP listener bound to `address`, listening with
+    /// `backlog`.
+    ///
+    /// The domain is based on the family of `address` and `options` are set
+    /// before the socket is bound. This is a shorthand for using
+    /// [`Socket::builder`], [`Socket::bind`] and [`Socket::listen`], see
+    /// [`ListenerOptions`] for an example.
+    pub fn tcp_listen(
+        address: &SockAddr,
+        backlog: c_int,
+        options: &ListenerOptions,
+    ) -> io::Result<Socket> {
+        options.listen(address, backlog)
+    }
+
+    /// Creates a new UDP socket connected to `remote`, optionally bound to
+    /// `local` first.
+    ///
+    /// The domain is based on the family of `remote` and `options` are set
+    /// before the socket is bound and connected. See [`UdpOptions`] for an
+    /// example.
+    pub fn udp_connect(
+        local: Option<&SockAddr>,
+        remote: &SockAddr,
+        options: &UdpOptions,
+    ) -> io::Result<Socket> {
+        options.connect(local, remote)
+    }
+
     /// Creates a new socket ready to be configured.
     ///
     /// This function corresponds to `socket(2)` on Unix and `WSASocketW` on
@@ -199,6 +247,96 @@ impl Socket {
         sys::poll_connect(self, timeout)
     }
 
+    /// Same as [`connect_timeout`], but waits until an absolute `deadline`
+    /// rather than for a duration.
+    ///
+    /// This is useful for retry loops, where passing the same deadline to each
+    /// attempt doesn't accumulate the time spent in earlier attempts the way
+    /// passing the same timeout does. If `deadline` has already passed an
+    /// error of kind [`io::ErrorKind::TimedOut`] is returned without
+    /// connecting.
+    ///
+    /// [`connect_timeout`]: Socket::connect_timeout
+    pub fn connect_deadline(&self, addr: &SockAddr, deadline: Instant) -> io::Result<()> {
+        self.connect_timeout(addr, remaining(deadline)?)
+    }
+
+    /// Initiate a non-blocking connection on this socket to the specified
+    /// address.
+    ///
+    /// This puts the socket in non-blocking mode and calls `connect(2)`.
+    /// Returns `true` if the connection was established immediately and
+    /// `false` if it's in progress (`EINPROGRESS` on Unix, `WSAEWOULDBLOCK` on
+    /// Windows). In the latter case wait for the socket to become writable
+    /// (on Windows a failed connection is reported as exceptional condition
+    /// instead) and call [`Socket::finish_connect`].
+    ///
+    /// The socket is left in non-blocking mode.
+    pub fn connect_nonblocking(&self, address: &SockAddr) -> io::Result<bool> {
+        self.set_nonblocking(true)?;
+        match self.connect(address) {
+            Ok(()) => Ok(true),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => Ok(false),
+            #[cfg(unix)]
+            Err(ref e) if e.raw_os_error() == Some(libc::EINPROGRESS) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Check the result of a connection started by
+    /// [`Socket::connect_nonblocking`].
+    ///
+    /// Returns `true` if the connection is established and `false` if it's
+    /// still in progress. If the connection failed the error stored in the
+    /// socket (`SO_ERROR`, see [`Socket::take_error`]) is returned.
+    ///
+    /// This is the same as [`Socket::is_connected`].
+    pub fn finish_connect(&self) -> io::Result<bool> {
+        self.is_connected()
+    }
+
+    /// Returns whether the socket is connected.
+    ///
+    /// Returns `true` if the connection is established and `false` if the
+    /// socket is not connected, e.g. because a non-blocking connect is still in
+    /// progress. If the connection failed the error stored in the socket
+    /// (`SO_ERROR`, see [`Socket::take_error`]) is returned.
+    ///
+    /// # Notes
+    ///
+    /// This uses `getpeername(2)` and the `SO_ERROR` option. Because reading
+    /// `SO_ERROR` clears it, a failed connection is only reported once, after
+    /// that `false` is returned.
+    ///
+    /// On Windows the error of a failed connection is only stored once it's
+    /// reported as exceptional condition by `select`, so if the socket is not
+    /// connected this also checks the except set of `select` (without
+    /// blocking). The except set is not checked for connected sockets, as
+    /// out-of-band data is reported in it as well.
+    #[allow(clippy::io_other_error)] // `io::Error::other` requires Rust 1.74.
+    pub fn is_connected(&self) -> io::Result<bool> {
+        if let Some(err) = self.take_error()? {
+            return Err(err);
+        }
+        match self.peer_addr() {
+            Ok(_) => Ok(true),
+            Err(ref e) if e.kind() == io::ErrorKind::NotConnected => {
+                #[cfg(windows)]
+                if sys::connect_failed(self.inner)? {
+                    return match self.take_error()? {
+                        Some(err) => Err(err),
+                        None => Err(io::Error::new(
+                            io::ErrorKind::Other,
+                            "connection failed, but no error set",
+                        )),
+                    };
+                }
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
     /// Mark a socket as ready to accept incoming connection requests using
     /// [`Socket::accept()`].
     ///
@@ -293,10 +431,30 @@ impl Socket {
     /// On Windows this can **not** be used function cannot be used on a
     /// QOS-enabled socket, see
     /// <https://docs.microsoft.com/en-us/windows/win32/api/winsock2/nf-winsock2-wsaduplicatesocketw>.
+    ///
+    /// [`Socket::try_clone_raw`] can be used if you don't want these flags to
+    /// be set, e.g. to pass the socket to a child process.
     pub fn try_clone(&self) -> io::Result<Socket> {
         sys::try_clone(self.inner).map(|inner| Socket { inner })
     }
 
+    /// Creates a new independently owned handle to the underlying socket,
+    /// without setting any flags.
+    ///
+    /// Unlike [`Socket::try_clone`] the returned socket is inherited by child
+    /// processes, `set_cloexec` (Unix) or `set_no_inherit` (Windows) can be
+    /// used to change this.
+    ///
+    /// # Notes
+    ///
+    /// On Unix this uses `F_DUPFD`.
+    ///
+    /// On Windows this uses `WSADuplicateSocketW` and `WSASocketW` without
+    /// `WSA_FLAG_NO_HANDLE_INHERIT`.
+    pub fn try_clone_raw(&self) -> io::Result<Socket> {
+        sys::try_clone_raw(self.inner).map(|inner| Socket { inner })
+    }
+
     /// Moves this TCP stream into or out of nonblocking mode.
     ///
     /// # Notes
@@ -309,6 +467,49 @@ impl Socket {
         sys::set_nonblocking(self.inner, nonblocking)
     }
 
+    /// Returns the number of bytes that can be read without blocking.
+    ///
+    /// For stream sockets this is the amount of data in the receive queue. For
+    /// datagram sockets the meaning differs per OS, e.g. on Linux it's the size
+    /// of the next datagram, while on macOS it's the size of all queued data.
+    ///
+    /// # Notes
+    ///
+    /// On Unix this corresponds to calling `ioctl` with `FIONREAD`.
+    ///
+    /// On Windows this corresponds to calling `ioctlsocket` with `FIONREAD`.
+    pub fn bytes_available(&self) -> io::Result<usize> {
+        sys::bytes_available(self.inner)
+    }
+
+    /// Returns the size of the next datagram in the receive queue, without
+    /// removing it from the queue.
+    ///
+    /// This can be used to allocate a buffer of the right size before calling
+    /// [`recv`] or [`recv_from`].
+    ///
+    /// # Notes
+    ///
+    /// On Android, Fuchsia and Linux this corresponds to calling `recv` with
+    /// `MSG_PEEK | MSG_TRUNC` and an empty buffer, which (like [`peek`]) waits
+    /// for a datagram if the socket is in blocking mode.
+    ///
+    /// On Apple platforms this corresponds to the `SO_NREAD` option.
+    ///
+    /// On other OSs this is the same as [`bytes_available`]. On the BSDs, such
+    /// as FreeBSD, `FIONREAD` returns the total size of *all* queued datagrams
+    /// instead of the size of the next one, so the result is only exact if a
+    /// single datagram is queued. On these OSs and Apple platforms 0 is
+    /// returned if no datagram is queued.
+    ///
+    /// [`recv`]: Socket::recv
+    /// [`recv_from`]: Socket::recv_from
+    /// [`peek`]: Socket::peek
+    /// [`bytes_available`]: Socket::bytes_available
+    pub fn peek_size(&self) -> io::Result<usize> {
+        sys::peek_size(self.inner)
+    }
+
     /// Shuts down the read, write, or both halves of this connection.
     ///
     /// This function will cause all pending and future I/O on the specified
@@ -323,7 +524,11 @@ impl Socket {
     /// The [`connect`] method will connect this socket to a remote address.
     /// This method might fail if the socket is not connected.
     ///
+    /// To also get the flags of the received message, e.g. to detect a
+    /// truncated datagram, use [`recv_msg_flags`].
+    ///
     /// [`connect`]: Socket::connect
+    /// [`recv_msg_flags`]: Socket::recv_msg_flags
     ///
     /// # Safety
     ///
@@ -352,16 +557,123 @@ impl Socket {
         self.recv_with_flags(buf, sys::MSG_OOB)
     }
 
+    /// Receives data on the socket until `buf` is completely filled by setting
+    /// the `MSG_WAITALL` flag.
+    ///
+    /// This is intended for reading fixed-length frames from stream sockets.
+    /// Even with `MSG_WAITALL` the OS may return fewer bytes, e.g. when a signal
+    /// is caught, in which case this function calls `recv` again for the
+    /// remaining bytes. [`io::ErrorKind::Interrupted`] errors are retried.
+    ///
+    /// On Windows `MSG_WAITALL` isn't supported on non-blocking sockets, nor
+    /// for some socket types on older versions. If it's rejected (with
+    /// `WSAEOPNOTSUPP`) this falls back to calling `recv` without flags until
+    /// `buf` is filled.
+    ///
+    /// Returns the number of bytes received, which is `buf.len()` unless the
+    /// connection is closed or an error occurs after part of `buf` was filled.
+    /// In that case the number of bytes received so far is returned, so the
+    /// caller doesn't lose track of the frame, e.g. on a non-blocking socket
+    /// the remainder can be received by calling this again with the unfilled
+    /// part of `buf` once the socket is readable. Persistent errors are
+    /// returned by the next call.
+    ///
+    /// If the connection is closed before any bytes are received an error of
+    /// kind [`io::ErrorKind::UnexpectedEof`] is returned.
+    ///
+    /// For more information, see [`recv`].
+    ///
+    /// [`recv`]: Socket::recv
+    pub fn recv_exact(&self, buf: &mut [MaybeUninit<u8>]) -> io::Result<usize> {
+        #[cfg_attr(not(windows), allow(unused_mut))]
+        let mut flags = sys::MSG_WAITALL;
+        let mut filled = 0;
+        while filled < buf.len() {
+            match self.recv_with_flags(&mut buf[filled..], flags) {
+                Ok(0) if filled == 0 => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "failed to fill whole buffer",
+                    ))
+                }
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                #[cfg(windows)]
+                Err(ref err)
+                    if flags != 0 && err.raw_os_error() == Some(sys::WSAEOPNOTSUPP as i32) =>
+                {
+                    flags = 0
+                }
+                Err(_) if filled != 0 => break,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(filled)
+    }
+
+    /// Returns `true` if the socket is at the out-of-band (OOB) data mark, i.e.
+    /// the next read will return the OOB data.
+    ///
+    /// For more information, see [`recv_out_of_band`], [`out_of_band_inline`].
+    ///
+    /// # Notes
+    ///
+    /// On Unix this corresponds to calling `sockatmark(3)`.
+    ///
+    /// On Windows this corresponds to calling `ioctlsocket` with `SIOCATMARK`,
+    /// which returns `TRUE` if no OOB data is waiting to be read. The result is
+    /// inverted to match Unix, i.e. this returns `true` if OOB data is waiting.
+    ///
+    /// [`recv_out_of_band`]: Socket::recv_out_of_band
+    /// [`out_of_band_inline`]: Socket::out_of_band_inline
+    pub fn at_mark(&self) -> io::Result<bool> {
+        sys::at_mark(self.inner)
+    }
+
     /// Identical to [`recv`] but allows for specification of arbitrary flags to
-    /// the underlying `recv` call.
+    /// the underlying `recv` call, e.g. using [`RecvOptions`]. See
+    /// [`recv_msg_flags`] to also get the flags of the received message.
     ///
     /// [`recv`]: Socket::recv
+    /// [`RecvOptions`]: crate::RecvOptions
+    /// [`recv_msg_flags`]: Socket::recv_msg_flags
     pub fn recv_with_flags(
         &self,
         buf: &mut [MaybeUninit<u8>],
-        flags: sys::c_int,
+        flags: impl Into<c_int>,
     ) -> io::Result<usize> {
-        sys::recv(self.inner, buf, flags)
+        sys::recv(self.inner, buf, flags.into())
+    }
+
+    /// Receives data on the socket, waiting at most until `deadline`.
+    ///
+    /// This sets the read timeout (`SO_RCVTIMEO`, see [`set_read_timeout`]) to
+    /// the time remaining until `deadline` and then calls [`recv`], so calling
+    /// this in a loop with the same `deadline` doesn't accumulate timeout drift.
+    /// The previous read timeout is restored before this returns.
+    ///
+    /// If `deadline` has already passed, or passes while waiting, an error of
+    /// kind [`io::ErrorKind::TimedOut`] is returned on all platforms.
+    ///
+    /// # Notes
+    ///
+    /// The socket should be in blocking mode, as the read timeout has no effect
+    /// on non-blocking sockets.
+    ///
+    /// [`set_read_timeout`]: Socket::set_read_timeout
+    /// [`recv`]: Socket::recv
+    pub fn recv_deadline(
+        &self,
+        buf: &mut [MaybeUninit<u8>],
+        deadline: Instant,
+    ) -> io::Result<usize> {
+        with_deadline(
+            deadline,
+            || self.read_timeout(),
+            |timeout| self.set_read_timeout(timeout),
+            || self.recv(buf),
+        )
     }
 
     /// Receives data on the socket from the remote address to which it is
@@ -412,9 +724,34 @@ impl Socket {
     pub fn recv_vectored_with_flags(
         &self,
         bufs: &mut [MaybeUninitSlice<'_>],
-        flags: c_int,
+        flags: impl Into<c_int>,
     ) -> io::Result<(usize, RecvFlags)> {
-        sys::recv_vectored(self.inner, bufs, flags)
+        sys::recv_vectored(self.inner, bufs, flags.into())
+    }
+
+    /// Identical to [`recv_with_flags`] but also returns the flags for the
+    /// received message, like [`recv_vectored_with_flags`] does.
+    ///
+    /// This can be used to detect a truncated datagram, see
+    /// [`RecvFlags::is_truncated`], when receiving into a single buffer.
+    ///
+    /// [`recv_with_flags`]: Socket::recv_with_flags
+    /// [`recv_vectored_with_flags`]: Socket::recv_vectored_with_flags
+    ///
+    /// # Safety
+    ///
+    /// `recv_msg_flags` makes the same safety guarantees regarding the
+    /// `buf`fer as [`recv`].
+    ///
+    /// [`recv`]: Socket::recv
+    #[cfg(not(target_os = "redox"))]
+    pub fn recv_msg_flags(
+        &self,
+        buf: &mut [MaybeUninit<u8>],
+        flags: impl Into<c_int>,
+    ) -> io::Result<(usize, RecvFlags)> {
+        let mut bufs = [MaybeUninitSlice::new(buf)];
+        sys::recv_vectored(self.inner, &mut bufs, flags.into())
     }
 
     /// Receives data on the socket from the remote adress to which it is
@@ -454,9 +791,9 @@ impl Socket {
     pub fn recv_from_with_flags(
         &self,
         buf: &mut [MaybeUninit<u8>],
-        flags: c_int,
+        flags: impl Into<c_int>,
     ) -> io::Result<(usize, SockAddr)> {
-        sys::recv_from(self.inner, buf, flags)
+        sys::recv_from(self.inner, buf, flags.into())
     }
 
     /// Receives data from the socket. Returns the amount of bytes read, the
@@ -494,9 +831,9 @@ impl Socket {
     pub fn recv_from_vectored_with_flags(
         &self,
         bufs: &mut [MaybeUninitSlice<'_>],
-        flags: c_int,
+        flags: impl Into<c_int>,
     ) -> io::Result<(usize, RecvFlags, SockAddr)> {
-        sys::recv_from_vectored(self.inner, bufs, flags)
+        sys::recv_from_vectored(self.inner, bufs, flags.into())
     }
 
     /// Receives data from the socket, without removing it from the queue.
@@ -517,22 +854,69 @@ impl Socket {
         self.recv_from_with_flags(buf, sys::MSG_PEEK)
     }
 
+    /// Receives data from the socket, without removing it from the queue.
+    /// Unlike [`peek_from`] this allows passing multiple buffers.
+    ///
+    /// This is accomplished by passing `MSG_PEEK` as a flag to the underlying
+    /// `recvmsg`/`WSARecvFrom` call.
+    ///
+    /// [`peek_from`]: Socket::peek_from
+    ///
+    /// # Safety
+    ///
+    /// `peek_from_vectored` makes the same safety guarantees regarding `bufs`
+    /// as [`recv_vectored`].
+    ///
+    /// [`recv_vectored`]: Socket::recv_vectored
+    #[cfg(not(target_os = "redox"))]
+    pub fn peek_from_vectored(
+        &self,
+        bufs: &mut [MaybeUninitSlice<'_>],
+    ) -> io::Result<(usize, RecvFlags, SockAddr)> {
+        self.recv_from_vectored_with_flags(bufs, sys::MSG_PEEK)
+    }
+
     /// Sends data on the socket to a connected peer.
     ///
     /// This is typically used on TCP sockets or datagram sockets which have
     /// been connected.
     ///
-    /// On success returns the number of bytes that were sent.
+    /// On success returns the number of bytes that were sent. This may be less
+    /// than the length of `buf`, for example on Windows at most `i32::MAX`
+    /// bytes are sent in a single call. Use [`send_all`] to send the entire
+    /// buffer.
+    ///
+    /// [`send_all`]: Socket::send_all
     pub fn send(&self, buf: &[u8]) -> io::Result<usize> {
         self.send_with_flags(buf, 0)
     }
 
     /// Identical to [`send`] but allows for specification of arbitrary flags to the underlying
-    /// `send` call.
-    ///
-    /// [`send`]: #method.send
-    pub fn send_with_flags(&self, buf: &[u8], flags: c_int) -> io::Result<usize> {
-        sys::send(self.inner, buf, flags)
+    /// `send` call, e.g. using [`SendFlags`].
+    ///
+    /// [`send`]: Socket::send
+    /// [`SendFlags`]: crate::SendFlags
+    pub fn send_with_flags(&self, buf: &[u8], flags: impl Into<c_int>) -> io::Result<usize> {
+        sys::send(self.inner, buf, flags.into())
+    }
+
+    /// Sends data on the socket, waiting at most until `deadline`.
+    ///
+    /// This sets the write timeout (`SO_SNDTIMEO`, see [`set_write_timeout`])
+    /// to the time remaining until `deadline` and then calls [`send`]. The
+    /// previous write timeout is restored before this returns. See
+    /// [`recv_deadline`] for the returned errors.
+    ///
+    /// [`set_write_timeout`]: Socket::set_write_timeout
+    /// [`send`]: Socket::send
+    /// [`recv_deadline`]: Socket::recv_deadline
+    pub fn send_deadline(&self, buf: &[u8], deadline: Instant) -> io::Result<usize> {
+        with_deadline(
+            deadline,
+            || self.write_timeout(),
+            |timeout| self.set_write_timeout(timeout),
+            || self.send(buf),
+        )
     }
 
     /// Send data to the connected peer. Returns the amount of bytes written.
@@ -549,9 +933,83 @@ impl Socket {
     pub fn send_vectored_with_flags(
         &self,
         bufs: &[IoSlice<'_>],
-        flags: c_int,
+        flags: impl Into<c_int>,
     ) -> io::Result<usize> {
-        sys::send_vectored(self.inner, bufs, flags)
+        sys::send_vectored(self.inner, bufs, flags.into())
+    }
+
+    /// Sends the entire `buf` to the connected peer.
+    ///
+    /// This is intended for stream sockets. It calls [`send`] until all bytes
+    /// are sent, handling short writes and buffers larger than a single system
+    /// call supports. [`io::ErrorKind::Interrupted`] errors are retried.
+    ///
+    /// If `send` returns zero an error of kind [`io::ErrorKind::WriteZero`] is
+    /// returned. On any error an unspecified part of `buf` may have been sent.
+    ///
+    /// [`send`]: Socket::send
+    pub fn send_all(&self, mut buf: &[u8]) -> io::Result<()> {
+        while !buf.is_empty() {
+            match self.send(buf) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write whole buffer",
+                    ))
+                }
+                Ok(n) => buf = &buf[n..],
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends all `bufs` to the connected peer.
+    ///
+    /// This is the vectored version of [`send_all`], with the same semantics.
+    ///
+    /// [`send_all`]: Socket::send_all
+    #[cfg(not(target_os = "redox"))]
+    pub fn send_all_vectored(&self, mut bufs: &[IoSlice<'_>]) -> io::Result<()> {
+        loop {
+            // Skip empty buffers, otherwise sending zero bytes would be
+            // considered an error below.
+            while let Some((first, rest)) = bufs.split_first() {
+                if !first.is_empty() {
+                    break;
+                }
+                bufs = rest;
+            }
+            if bufs.is_empty() {
+                return Ok(());
+            }
+
+            let mut n = match self.send_vectored(bufs) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write whole buffer",
+                    ))
+                }
+                Ok(n) => n,
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            // Skip the buffers that are sent completely.
+            while let Some((first, rest)) = bufs.split_first() {
+                if n < first.len() {
+                    break;
+                }
+                n -= first.len();
+                bufs = rest;
+            }
+            // Send the remainder of a partially sent buffer.
+            if n != 0 {
+                self.send_all(&bufs[0][n..])?;
+                bufs = &bufs[1..];
+            }
+        }
     }
 
     /// Sends out-of-band (OOB) data on the socket to connected peer
@@ -559,8 +1017,8 @@ impl Socket {
     ///
     /// For more information, see [`send`], [`out_of_band_inline`].
     ///
-    /// [`send`]: #method.send
-    /// [`out_of_band_inline`]: #method.out_of_band_inline
+    /// [`send`]: Socket::send
+    /// [`out_of_band_inline`]: Socket::out_of_band_inline
     pub fn send_out_of_band(&self, buf: &[u8]) -> io::Result<usize> {
         self.send_with_flags(buf, sys::MSG_OOB)
     }
@@ -581,9 +1039,9 @@ impl Socket {
         &self,
         buf: &[u8],
         addr: &SockAddr,
-        flags: c_int,
+        flags: impl Into<c_int>,
     ) -> io::Result<usize> {
-        sys::send_to(self.inner, buf, addr, flags)
+        sys::send_to(self.inner, buf, addr, flags.into())
     }
 
     /// Send data to a peer listening on `addr`. Returns the amount of bytes
@@ -602,12 +1060,112 @@ impl Socket {
         &self,
         bufs: &[IoSlice<'_>],
         addr: &SockAddr,
-        flags: c_int,
+        flags: impl Into<c_int>,
     ) -> io::Result<usize> {
-        sys::send_to_vectored(self.inner, bufs, addr, flags)
+        sys::send_to_vectored(self.inner, bufs, addr, flags.into())
+    }
+
+    /// Get the value of an arbitrary socket option `optname` at `level`.
+    ///
+    /// This can be used to get the value of options this crate doesn't (yet)
+    /// provide a dedicated method for.
+    ///
+    /// # Notes
+    ///
+    /// On Unix this corresponds to calling `getsockopt(2)`.
+    ///
+    /// On Windows this corresponds to calling `getsockopt`.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `T` is the correct type for the option, both
+    /// in size and in the set of valid values, as the value returned by the OS
+    /// is interpreted as `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> std::io::Result<()> {
+    /// use std::os::raw::c_int;
+    ///
+    /// use socket2::{Domain, Socket, Type};
+    ///
+    /// let socket = Socket::new(Domain::IPV4, Type::STREAM, None)?;
+    /// # #[cfg(unix)]
+    /// # let (level, optname) = (libc::SOL_SOCKET, libc::SO_KEEPALIVE);
+    /// # #[cfg(windows)]
+    /// # let (level, optname) = (0xffff, 0x0008);
+    /// // Safety: `SO_KEEPALIVE` is an `int`.
+    /// let keepalive: c_int = unsafe { socket.getsockopt(level, optname)? };
+    /// assert_eq!(keepalive, 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub unsafe fn getsockopt<T>(&self, level: c_int, optname: c_int) -> io::Result<T> {
+        getsockopt::<T>(self.inner, level, optname)
+    }
+
+    /// Set the value of an arbitrary socket option `optname` at `level` to
+    /// `value`.
+    ///
+    /// This can be used to set options this crate doesn't (yet) provide a
+    /// dedicated method for.
+    ///
+    /// # Notes
+    ///
+    /// On Unix this corresponds to calling `setsockopt(2)`.
+    ///
+    /// On Windows this corresponds to calling `setsockopt`.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `T` is the correct type for the option, as
+    /// the OS reads `size_of::<T>()` bytes from `value`.
+    pub unsafe fn setsockopt<T>(&self, level: c_int, optname: c_int, value: T) -> io::Result<()> {
+        setsockopt(self.inner, level, optname, value)
     }
 }
 
+/// Returns the time remaining until `deadline`, or a `TimedOut` error if it
+/// has passed.
+fn remaining(deadline: Instant) -> io::Result<Duration> {
+    let now = Instant::now();
+    if deadline <= now {
+        return Err(io::ErrorKind::TimedOut.into());
+    }
+    // A zero timeout means blocking indefinitely and Windows uses millisecond
+    // timeouts, so wait at least one millisecond.
+    Ok((deadline - now).max(Duration::from_millis(1)))
+}
+
+/// Calls `f` with the timeout, as retrieved by `get` and changed by `set`, set
+/// to the time remaining until `deadline`, restoring the previous timeout
+/// afterwards.
+fn with_deadline<T, G, S, F>(deadline: Instant, get: G, set: S, f: F) -> io::Result<T>
+where
+    G: FnOnce() -> io::Result<Option<Duration>>,
+    S: Fn(Option<Duration>) -> io::Result<()>,
+    F: FnOnce() -> io::Result<T>,
+{
+    let timeout = remaining(deadline)?;
+    let previous = get()?;
+    set(Some(timeout))?;
+    let res = f();
+    set(previous)?;
+    // Unix returns `EAGAIN` if the timeout expires, Windows `WSAETIMEDOUT`.
+    res.map_err(|err| match err.kind() {
+        io::ErrorKind::WouldBlock => io::ErrorKind::TimedOut.into(),
+        _ => err,
+    })
+}
+
+/// Converts a buffer size to a `c_int`, returning an `InvalidInput` error if it
+/// doesn't fit.
+pub(crate) fn buffer_size(size: usize) -> io::Result<c_int> {
+    c_int::try_from(size)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "buffer size too large"))
+}
+
 /// Set `SOCK_CLOEXEC` and `NO_HANDLE_INHERIT` on the `ty`pe on platforms that
 /// support it.
 #[inline(always)]
@@ -749,14 +1307,22 @@ impl Socket {
     /// immediately and the closing is done in the background. When the socket
     /// is closed as part of exit(2), it always lingers in the background.
     ///
+    /// A linger timeout of zero aborts the connection on close: any unsent data
+    /// is discarded and a reset (`RST`) is sent to the peer instead of the
+    /// normal connection termination.
+    ///
     /// # Notes
     ///
-    /// On most OSs the duration only has a precision of seconds and will be
-    /// silently truncated.
+    /// The duration only has a precision of seconds, sub-second precision is
+    /// truncated. If the number of seconds doesn't fit in the OS type (`c_int`
+    /// on Unix and `u16` on Windows) an error of kind
+    /// [`io::ErrorKind::InvalidInput`] is returned.
     ///
-    /// On Apple platforms (e.g. macOS, iOS, etc) this uses `SO_LINGER_SEC`.
+    /// On Apple platforms (e.g. macOS, iOS, etc) this uses `SO_LINGER_SEC`, so
+    /// the timeout is in seconds like on other OSs (`SO_LINGER` uses clock
+    /// ticks on these platforms).
     pub fn set_linger(&self, linger: Option<Duration>) -> io::Result<()> {
-        let linger = into_linger(linger);
+        let linger = into_linger(linger)?;
         unsafe { setsockopt(self.inner, sys::SOL_SOCKET, sys::SO_LINGER, linger) }
     }
 
@@ -806,9 +1372,11 @@ impl Socket {
     /// Set value for the `SO_RCVBUF` option on this socket.
     ///
     /// Changes the size of the operating system's receive buffer associated
-    /// with the socket.
+    /// with the socket. Returns an error of kind [`io::ErrorKind::InvalidInput`]
+    /// if `size` is larger than `c_int::MAX`.
     pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
-        unsafe { setsockopt(self.inner, sys::SOL_SOCKET, sys::SO_RCVBUF, size as c_int) }
+        let size = buffer_size(size)?;
+        unsafe { setsockopt(self.inner, sys::SOL_SOCKET, sys::SO_RCVBUF, size) }
     }
 
     /// Get value for the `SO_RCVTIMEO` option on this socket.
@@ -870,9 +1438,11 @@ impl Socket {
     /// Set value for the `SO_SNDBUF` option on this socket.
     ///
     /// Changes the size of the operating system's send buffer associated with
-    /// the socket.
+    /// the socket. Returns an error of kind [`io::ErrorKind::InvalidInput`] if
+    /// `size` is larger than `c_int::MAX`.
     pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
-        unsafe { setsockopt(self.inner, sys::SOL_SOCKET, sys::SO_SNDBUF, size as c_int) }
+        let size = buffer_size(size)?;
+        unsafe { setsockopt(self.inner, sys::SOL_SOCKET, sys::SO_SNDBUF, size) }
     }
 
     /// Get value for the `SO_SNDTIMEO` option on this socket.
@@ -892,24 +1462,31 @@ impl Socket {
     }
 }
 
+#[allow(clippy::unnecessary_fallible_conversions)] // `l_linger` is `u16` on Windows.
 fn from_linger(linger: sys::linger) -> Option<Duration> {
     if linger.l_onoff == 0 {
         None
     } else {
-        Some(Duration::from_secs(linger.l_linger as u64))
+        // `l_linger` is signed on Unix, treat negative values as zero.
+        Some(Duration::from_secs(
+            u64::try_from(linger.l_linger).unwrap_or(0),
+        ))
     }
 }
 
-fn into_linger(duration: Option<Duration>) -> sys::linger {
+fn into_linger(duration: Option<Duration>) -> io::Result<sys::linger> {
     match duration {
-        Some(duration) => sys::linger {
+        Some(duration) => Ok(sys::linger {
             l_onoff: 1,
-            l_linger: duration.as_secs() as _,
-        },
-        None => sys::linger {
+            // `l_linger` is a `c_int` on Unix and `u16` on Windows.
+            l_linger: duration.as_secs().try_into().map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidInput, "linger duration too large")
+            })?,
+        }),
+        None => Ok(sys::linger {
             l_onoff: 0,
             l_linger: 0,
-        },
+        }),
     }
 }
 
@@ -947,6 +1524,56 @@ impl Socket {
         unsafe { setsockopt(self.inner, sys::IPPROTO_IP, sys::IP_DROP_MEMBERSHIP, mreq) }
     }
 
+    /// Join a multicast group using `IP_ADD_MEMBERSHIP` option on this socket.
+    ///
+    /// Same as [`join_multicast_v4`], but the interface can also be selected
+    /// by index, which is more reliable for interfaces without a (fixed)
+    /// address or with multiple addresses. Using [`InterfaceIndexOrAddress::Index`]
+    /// with `0` lets the system choose an appropriate interface.
+    ///
+    /// This uses `ip_mreqn` on Unix. On Windows the index is passed in
+    /// `ip_mreq` as an address of the form `0.0.0.<index>`, so it must be
+    /// below 2<sup>24</sup>, otherwise an error of kind
+    /// [`io::ErrorKind::InvalidInput`] is returned.
+    ///
+    /// [`join_multicast_v4`]: Socket::join_multicast_v4
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "fuchsia",
+        target_os = "linux",
+        windows,
+    ))]
+    pub fn join_multicast_v4_n(
+        &self,
+        multiaddr: &Ipv4Addr,
+        interface: &InterfaceIndexOrAddress,
+    ) -> io::Result<()> {
+        let mreqn = sys::to_mreqn(multiaddr, interface)?;
+        unsafe { setsockopt(self.inner, sys::IPPROTO_IP, sys::IP_ADD_MEMBERSHIP, mreqn) }
+    }
+
+    /// Leave a multicast group using `IP_DROP_MEMBERSHIP` option on this socket.
+    ///
+    /// For more information about this option, see [`join_multicast_v4_n`].
+    ///
+    /// [`join_multicast_v4_n`]: Socket::join_multicast_v4_n
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "fuchsia",
+        target_os = "linux",
+        windows,
+    ))]
+    pub fn leave_multicast_v4_n(
+        &self,
+        multiaddr: &Ipv4Addr,
+        interface: &InterfaceIndexOrAddress,
+    ) -> io::Result<()> {
+        let mreqn = sys::to_mreqn(multiaddr, interface)?;
+        unsafe { setsockopt(self.inner, sys::IPPROTO_IP, sys::IP_DROP_MEMBERSHIP, mreqn) }
+    }
+
     /// Get the value of the `IP_MULTICAST_IF` option for this socket.
     ///
     /// For more information about this option, see [`set_multicast_if_v4`].
@@ -966,6 +1593,68 @@ impl Socket {
         unsafe { setsockopt(self.inner, sys::IPPROTO_IP, sys::IP_MULTICAST_IF, interface) }
     }
 
+    /// Set the value of the `IP_MULTICAST_IF` option for this socket.
+    ///
+    /// Same as [`set_multicast_if_v4`], but the interface can also be selected
+    /// by index. See [`join_multicast_v4_n`] for how the index is passed.
+    ///
+    /// [`set_multicast_if_v4`]: Socket::set_multicast_if_v4
+    /// [`join_multicast_v4_n`]: Socket::join_multicast_v4_n
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "fuchsia",
+        target_os = "linux",
+        windows,
+    ))]
+    pub fn set_multicast_if_v4_n(&self, interface: &InterfaceIndexOrAddress) -> io::Result<()> {
+        let mreqn = sys::to_mreqn(&Ipv4Addr::UNSPECIFIED, interface)?;
+        // Windows only accepts the interface, not the entire `ip_mreq`.
+        #[cfg(windows)]
+        let mreqn = mreqn.imr_interface;
+        unsafe { setsockopt(self.inner, sys::IPPROTO_IP, sys::IP_MULTICAST_IF, mreqn) }
+    }
+
+    /// Get the value of the `IP_UNICAST_IF` option for this socket.
+    ///
+    /// For more information about this option, see [`set_unicast_if_v4`].
+    ///
+    /// This function is only available on Android, Linux and Windows.
+    ///
+    /// [`set_unicast_if_v4`]: Socket::set_unicast_if_v4
+    #[cfg(all(
+        feature = "all",
+        any(target_os = "android", target_os = "linux", windows)
+    ))]
+    pub fn unicast_if_v4(&self) -> io::Result<u32> {
+        unsafe {
+            getsockopt::<u32>(self.inner, sys::IPPROTO_IP, sys::IP_UNICAST_IF).map(u32::from_be)
+        }
+    }
+
+    /// Set the value of the `IP_UNICAST_IF` option for this socket.
+    ///
+    /// Specifies the interface, by index, to use for sending unicast packets
+    /// on an unconnected socket, overriding the routing table. An index of 0
+    /// restores the default behaviour.
+    ///
+    /// This function is only available on Android, Linux and Windows.
+    #[cfg(all(
+        feature = "all",
+        any(target_os = "android", target_os = "linux", windows)
+    ))]
+    pub fn set_unicast_if_v4(&self, interface: u32) -> io::Result<()> {
+        // The index is passed in network byte order.
+        unsafe {
+            setsockopt(
+                self.inner,
+                sys::IPPROTO_IP,
+                sys::IP_UNICAST_IF,
+                interface.to_be(),
+            )
+        }
+    }
+
     /// Get the value of the `IP_MULTICAST_LOOP` option for this socket.
     ///
     /// For more information about this option, see [`set_multicast_loop_v4`].
@@ -1151,6 +1840,51 @@ impl Socket {
         }
     }
 
+    /// Get the value of the `IPV6_UNICAST_IF` option for this socket.
+    ///
+    /// For more information about this option, see [`set_unicast_if_v6`].
+    ///
+    /// This function is only available on Android, Linux and Windows.
+    ///
+    /// [`set_unicast_if_v6`]: Socket::set_unicast_if_v6
+    #[cfg(all(
+        feature = "all",
+        any(target_os = "android", target_os = "linux", windows)
+    ))]
+    pub fn unicast_if_v6(&self) -> io::Result<u32> {
+        let interface =
+            unsafe { getsockopt::<u32>(self.inner, sys::IPPROTO_IPV6, sys::IPV6_UNICAST_IF)? };
+        #[cfg(unix)]
+        let interface = u32::from_be(interface);
+        Ok(interface)
+    }
+
+    /// Set the value of the `IPV6_UNICAST_IF` option for this socket.
+    ///
+    /// The IPv6 version of [`set_unicast_if_v4`].
+    ///
+    /// This function is only available on Android, Linux and Windows.
+    ///
+    /// [`set_unicast_if_v4`]: Socket::set_unicast_if_v4
+    #[cfg(all(
+        feature = "all",
+        any(target_os = "android", target_os = "linux", windows)
+    ))]
+    pub fn set_unicast_if_v6(&self, interface: u32) -> io::Result<()> {
+        // Linux expects the index in network byte order (as for IPv4), Windows
+        // in host byte order.
+        #[cfg(unix)]
+        let interface = interface.to_be();
+        unsafe {
+            setsockopt(
+                self.inner,
+                sys::IPPROTO_IPV6,
+                sys::IPV6_UNICAST_IF,
+                interface,
+            )
+        }
+    }
+
     /// Get the value of the `IPV6_MULTICAST_LOOP` option for this socket.
     ///
     /// For more information about this option, see [`set_multicast_loop_v6`].
@@ -1202,6 +1936,37 @@ impl Socket {
         }
     }
 
+    /// Get the value of the `IPV6_DONTFRAG` option on this socket.
+    ///
+    /// For more information about this option, see [`set_dontfrag_v6`].
+    ///
+    /// [`set_dontfrag_v6`]: Socket::set_dontfrag_v6
+    #[cfg(not(any(target_os = "fuchsia", target_os = "haiku", target_os = "redox")))]
+    pub fn dontfrag_v6(&self) -> io::Result<bool> {
+        unsafe {
+            getsockopt::<c_int>(self.inner, sys::IPPROTO_IPV6, sys::IPV6_DONTFRAG)
+                .map(|dontfrag| dontfrag != 0)
+        }
+    }
+
+    /// Set the value for the `IPV6_DONTFRAG` option on this socket.
+    ///
+    /// If this is set to `true` packets larger than the path MTU are not
+    /// fragmented by the sender, instead sending them fails with an error (or
+    /// they are dropped). This is required by protocols that perform their own
+    /// path MTU discovery, such as QUIC.
+    #[cfg(not(any(target_os = "fuchsia", target_os = "haiku", target_os = "redox")))]
+    pub fn set_dontfrag_v6(&self, dontfrag: bool) -> io::Result<()> {
+        unsafe {
+            setsockopt(
+                self.inner,
+                sys::IPPROTO_IPV6,
+                sys::IPV6_DONTFRAG,
+                dontfrag as c_int,
+            )
+        }
+    }
+
     /// Get the value of the `IPV6_V6ONLY` option for this socket.
     ///
     /// For more information about this option, see [`set_only_v6`].
@@ -1244,8 +2009,16 @@ impl Socket {
     ///
     /// This returns the value of `SO_KEEPALIVE` on OpenBSD and Haiku,
     /// `TCP_KEEPALIVE` on macOS and iOS, and `TCP_KEEPIDLE` on all other Unix
-    /// operating systems.
-    #[cfg(all(feature = "all", not(windows)))]
+    /// operating systems and Windows.
+    ///
+    /// # Notes
+    ///
+    /// On Windows `TCP_KEEPIDLE` is supported since Windows 10, version 1709.
+    /// Older versions return an error, as the value set by
+    /// [`set_tcp_keepalive`] using `SIO_KEEPALIVE_VALS` can't be read back.
+    ///
+    /// [`set_tcp_keepalive`]: Socket::set_tcp_keepalive
+    #[cfg(feature = "all")]
     pub fn keepalive_time(&self) -> io::Result<Duration> {
         sys::keepalive_time(self.inner)
     }
@@ -1254,7 +2027,11 @@ impl Socket {
     ///
     /// For more information about this option, see [`set_tcp_keepalive`].
     ///
+    /// On Windows this is supported since Windows 10, version 1709, see
+    /// [`keepalive_time`].
+    ///
     /// [`set_tcp_keepalive`]: Socket::set_tcp_keepalive
+    /// [`keepalive_time`]: Socket::keepalive_time
     #[cfg(all(
         feature = "all",
         any(
@@ -1266,6 +2043,7 @@ impl Socket {
             target_os = "linux",
             target_os = "netbsd",
             target_vendor = "apple",
+            windows,
         )
     ))]
     pub fn keepalive_interval(&self) -> io::Result<Duration> {
@@ -1279,7 +2057,11 @@ impl Socket {
     ///
     /// For more information about this option, see [`set_tcp_keepalive`].
     ///
+    /// On Windows this is supported since Windows 10, version 1709, see
+    /// [`keepalive_time`].
+    ///
     /// [`set_tcp_keepalive`]: Socket::set_tcp_keepalive
+    /// [`keepalive_time`]: Socket::keepalive_time
     #[cfg(all(
         feature = "all",
         any(
@@ -1291,6 +2073,7 @@ impl Socket {
             target_os = "linux",
             target_os = "netbsd",
             target_vendor = "apple",
+            windows,
         )
     ))]
     pub fn keepalive_retries(&self) -> io::Result<u32> {
@@ -1393,7 +2176,7 @@ impl Read for Socket {
     }
 }
 
-impl<'a> Read for &'a Socket {
+impl Read for &Socket {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         // Safety: see other `Read::read` impl.
         let buf = unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) };
@@ -1423,7 +2206,7 @@ impl Write for Socket {
     }
 }
 
-impl<'a> Write for &'a Socket {
+impl Write for &Socket {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         self.send(buf)
     }
@@ -7,6 +7,7 @@
 // except according to those terms.
 
 use std::cmp::min;
+use std::ffi::{CStr, OsStr};
 #[cfg(not(target_os = "redox"))]
 use std::io::IoSlice;
 use std::marker::PhantomData;
@@ -23,7 +24,6 @@ use std::net::{Ipv4Addr, Ipv6Addr};
     )
 ))]
 use std::num::NonZeroUsize;
-#[cfg(feature = "all")]
 use std::os::unix::ffi::OsStrExt;
 #[cfg(all(
     feature = "all",
@@ -35,15 +35,14 @@ use std::os::unix::ffi::OsStrExt;
     )
 ))]
 use std::os::unix::io::RawFd;
+use std::os::unix::io::{AsFd, BorrowedFd, OwnedFd};
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
-#[cfg(feature = "all")]
 use std::os::unix::net::{UnixDatagram, UnixListener, UnixStream};
-#[cfg(feature = "all")]
 use std::path::Path;
 #[cfg(not(all(target_os = "redox", not(feature = "all"))))]
 use std::ptr;
 use std::time::{Duration, Instant};
-use std::{io, slice};
+use std::{fmt, io, slice};
 
 #[cfg(not(target_vendor = "apple"))]
 use libc::ssize_t;
@@ -60,19 +59,35 @@ pub(crate) use libc::{AF_INET, AF_INET6};
 // Used in `Type`.
 #[cfg(all(feature = "all", not(target_os = "redox")))]
 pub(crate) use libc::SOCK_RAW;
+#[cfg(all(feature = "all", not(any(target_os = "redox", target_os = "haiku"))))]
+pub(crate) use libc::SOCK_RDM;
 #[cfg(feature = "all")]
 pub(crate) use libc::SOCK_SEQPACKET;
 pub(crate) use libc::{SOCK_DGRAM, SOCK_STREAM};
 // Used in `Protocol`.
+#[cfg(all(feature = "all", not(target_os = "redox")))]
+pub(crate) use libc::IPPROTO_RAW;
 pub(crate) use libc::{IPPROTO_ICMP, IPPROTO_ICMPV6, IPPROTO_TCP, IPPROTO_UDP};
 // Used in `SockAddr`.
 pub(crate) use libc::{
-    sa_family_t, sockaddr, sockaddr_in, sockaddr_in6, sockaddr_storage, socklen_t,
+    sa_family_t, sockaddr, sockaddr_in, sockaddr_in6, sockaddr_storage, socklen_t, AF_UNIX,
 };
+// Used in `getaddrinfo`.
+#[cfg(not(any(target_os = "haiku", target_os = "illumos", target_os = "solaris")))]
+pub(crate) use libc::AI_PASSIVE;
+pub(crate) use libc::{addrinfo, freeaddrinfo};
+// Not defined by libc on Solaris-like OSs, value from `netdb.h`.
+#[cfg(any(target_os = "illumos", target_os = "solaris"))]
+pub(crate) const AI_PASSIVE: c_int = 0x0008;
+// Not defined by libc on Haiku, value from `netdb.h`.
+#[cfg(target_os = "haiku")]
+pub(crate) const AI_PASSIVE: c_int = 0x0001;
 // Used in `RecvFlags`.
 #[cfg(not(target_os = "redox"))]
 pub(crate) use libc::{MSG_TRUNC, SO_OOBINLINE};
 // Used in `Socket`.
+#[cfg(not(any(target_os = "fuchsia", target_os = "haiku", target_os = "redox")))]
+pub(crate) use libc::IPV6_DONTFRAG;
 #[cfg(not(target_vendor = "apple"))]
 pub(crate) use libc::SO_LINGER;
 #[cfg(target_vendor = "apple")]
@@ -81,8 +96,8 @@ pub(crate) use libc::{
     ip_mreq as IpMreq, ipv6_mreq as Ipv6Mreq, linger, IPPROTO_IP, IPPROTO_IPV6,
     IPV6_MULTICAST_HOPS, IPV6_MULTICAST_IF, IPV6_MULTICAST_LOOP, IPV6_UNICAST_HOPS, IPV6_V6ONLY,
     IP_ADD_MEMBERSHIP, IP_DROP_MEMBERSHIP, IP_MULTICAST_IF, IP_MULTICAST_LOOP, IP_MULTICAST_TTL,
-    IP_TTL, MSG_OOB, MSG_PEEK, SOL_SOCKET, SO_BROADCAST, SO_ERROR, SO_KEEPALIVE, SO_RCVBUF,
-    SO_RCVTIMEO, SO_REUSEADDR, SO_SNDBUF, SO_SNDTIMEO, TCP_NODELAY,
+    IP_TTL, MSG_DONTROUTE, MSG_OOB, MSG_PEEK, MSG_WAITALL, SOL_SOCKET, SO_BROADCAST, SO_ERROR,
+    SO_KEEPALIVE, SO_RCVBUF, SO_RCVTIMEO, SO_REUSEADDR, SO_SNDBUF, SO_SNDTIMEO, TCP_NODELAY,
 };
 #[cfg(not(any(
     target_os = "dragonfly",
@@ -108,6 +123,8 @@ pub(crate) use libc::{IPV6_ADD_MEMBERSHIP, IPV6_DROP_MEMBERSHIP};
 pub(crate) use libc::{
     IPV6_JOIN_GROUP as IPV6_ADD_MEMBERSHIP, IPV6_LEAVE_GROUP as IPV6_DROP_MEMBERSHIP,
 };
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+pub(crate) use libc::{IPV6_UNICAST_IF, IP_UNICAST_IF};
 #[cfg(all(
     feature = "all",
     any(
@@ -133,21 +150,36 @@ use libc::TCP_KEEPALIVE as KEEPALIVE_TIME;
 use libc::TCP_KEEPIDLE as KEEPALIVE_TIME;
 
 /// Helper macro to execute a system call that returns an `io::Result`.
+///
+/// The first argument of the system call must be the socket (file descriptor)
+/// it operates on, for other system calls prefix the call with `@no_socket`.
 macro_rules! syscall {
-    ($fn: ident ( $($arg: expr),* $(,)* ) ) => {{
+    (@no_socket $fn: ident ( $($arg: expr),* $(,)* ) ) => {{
+        syscall!(@call $fn ( $($arg),* ), None)
+    }};
+    ($fn: ident ( $socket: expr $(, $arg: expr)* $(,)* ) ) => {{
+        let socket = $socket;
+        syscall!(@call $fn ( socket $(, $arg)* ), Some(socket))
+    }};
+    (@call $fn: ident ( $($arg: expr),* ), $socket: expr) => {{
         #[allow(unused_unsafe)]
         let res = unsafe { libc::$fn($($arg, )*) };
-        if res == -1 {
+        let res = if res == -1 {
             Err(std::io::Error::last_os_error())
         } else {
             Ok(res)
-        }
+        };
+        #[cfg(feature = "error-context")]
+        crate::error::record(stringify!($fn), $socket, res.as_ref().err());
+        #[cfg(feature = "tracing")]
+        tracing::trace!(syscall = stringify!($fn), result = ?res);
+        res
     }};
 }
 
 /// Maximum size of a buffer passed to system call like `recv` and `send`.
 #[cfg(not(target_vendor = "apple"))]
-const MAX_BUF_LEN: usize = <ssize_t>::max_value() as usize;
+const MAX_BUF_LEN: usize = <ssize_t>::MAX as usize;
 
 // The maximum read limit on most posix-like systems is `SSIZE_MAX`, with the
 // man page quoting that if the count of bytes to read is greater than
@@ -158,7 +190,7 @@ const MAX_BUF_LEN: usize = <ssize_t>::max_value() as usize;
 // than or equal to INT_MAX. To handle both of these the read size is capped on
 // both platforms.
 #[cfg(target_vendor = "apple")]
-const MAX_BUF_LEN: usize = <c_int>::max_value() as usize - 1;
+const MAX_BUF_LEN: usize = <c_int>::MAX as usize - 1;
 
 #[cfg(any(target_os = "android", all(target_os = "linux", target_env = "gnu")))]
 type IovLen = usize;
@@ -199,6 +231,23 @@ impl Domain {
     /// This function is only available on Linux.
     #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
     pub const VSOCK: Domain = Domain(libc::AF_VSOCK);
+
+    /// Domain for communication with the kernel, corresponding to
+    /// `AF_NETLINK`.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub const NETLINK: Domain = Domain(libc::AF_NETLINK);
+
+    /// Domain for Reliable Datagram Sockets, corresponding to `AF_RDS`.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub const RDS: Domain = Domain(libc::AF_RDS);
 }
 
 impl_debug!(
@@ -210,6 +259,10 @@ impl_debug!(
     libc::AF_PACKET,
     #[cfg(any(target_os = "android", target_os = "linux"))]
     libc::AF_VSOCK,
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    libc::AF_NETLINK,
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    libc::AF_RDS,
     libc::AF_UNSPEC, // = 0.
 );
 
@@ -309,12 +362,29 @@ impl_debug!(
     */
 );
 
+/// Unix only API.
+impl Protocol {
+    /// Protocol corresponding to `SCTP`.
+    ///
+    /// # Notes
+    ///
+    /// This is only available on Android, FreeBSD and Linux.
+    #[cfg(all(
+        feature = "all",
+        any(target_os = "android", target_os = "freebsd", target_os = "linux")
+    ))]
+    pub const SCTP: Protocol = Protocol(libc::IPPROTO_SCTP);
+}
+
 impl_debug!(
     Protocol,
     libc::IPPROTO_ICMP,
     libc::IPPROTO_ICMPV6,
     libc::IPPROTO_TCP,
     libc::IPPROTO_UDP,
+    libc::IPPROTO_RAW,
+    #[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
+    libc::IPPROTO_SCTP,
 );
 
 /// Unix-only API.
@@ -340,6 +410,90 @@ impl RecvFlags {
     pub const fn is_out_of_band(self) -> bool {
         self.0 & libc::MSG_OOB != 0
     }
+
+    /// Check if the control (ancillary) data was truncated.
+    ///
+    /// This happens if the buffer for the control data was too small to hold
+    /// all control messages, in which case some were discarded.
+    ///
+    /// On Unix this corresponds to the MSG_CTRUNC flag.
+    pub const fn is_ctrunc(self) -> bool {
+        self.0 & libc::MSG_CTRUNC != 0
+    }
+}
+
+/// Unix only API.
+impl crate::RecvOptions {
+    /// Don't block if no data is available, corresponding to `MSG_DONTWAIT`.
+    pub const DONT_WAIT: crate::RecvOptions = crate::RecvOptions(libc::MSG_DONTWAIT);
+}
+
+/// Unix only API.
+impl crate::SendFlags {
+    /// Don't block if the data can't be send immediately, corresponding to
+    /// `MSG_DONTWAIT`.
+    pub const DONT_WAIT: crate::SendFlags = crate::SendFlags(libc::MSG_DONTWAIT);
+
+    /// Terminate a record, corresponding to `MSG_EOR`.
+    ///
+    /// Used with record-oriented socket types, such as
+    /// [`SEQPACKET`][crate::Type::SEQPACKET] or SCTP sockets, to mark the end
+    /// of a record. On the receiving side this is reported by
+    /// [`RecvFlags::is_end_of_record`].
+    ///
+    /// [`RecvFlags::is_end_of_record`]: crate::RecvFlags::is_end_of_record
+    pub const END_OF_RECORD: crate::SendFlags = crate::SendFlags(libc::MSG_EOR);
+
+    /// Don't generate a `SIGPIPE` signal if the peer closed the connection,
+    /// corresponding to `MSG_NOSIGNAL`.
+    ///
+    /// # Notes
+    ///
+    /// This is only available on Android, DragonFlyBSD, FreeBSD, Fuchsia,
+    /// illumos, Linux, NetBSD, OpenBSD and Solaris. On Apple platforms use
+    /// [`Socket::set_nosigpipe`] instead.
+    ///
+    /// [`Socket::set_nosigpipe`]: crate::Socket::set_nosigpipe
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "fuchsia",
+        target_os = "illumos",
+        target_os = "linux",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "solaris",
+    ))]
+    pub const NO_SIGNAL: crate::SendFlags = crate::SendFlags(libc::MSG_NOSIGNAL);
+
+    /// More data will follow this call, corresponding to `MSG_MORE`.
+    ///
+    /// The kernel coalesces the data with the data of the following send
+    /// call(s) until a call without this flag is made, e.g. to send a header
+    /// and body in one TCP segment. This is the per-call equivalent of the
+    /// `TCP_CORK` option.
+    ///
+    /// # Notes
+    ///
+    /// This is only available on Android, Fuchsia and Linux. On other OSs set
+    /// the `TCP_CORK` or `TCP_NOPUSH` option for the duration of the writes
+    /// instead.
+    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+    pub const MORE: crate::SendFlags = crate::SendFlags(libc::MSG_MORE);
+
+    /// Tell the link layer that the peer is reachable, corresponding to
+    /// `MSG_CONFIRM`.
+    ///
+    /// This is useful for datagram protocols that know they got a reply from
+    /// the peer, so the kernel doesn't need to send ARP or neighbour discovery
+    /// probes to confirm it's still reachable.
+    ///
+    /// # Notes
+    ///
+    /// This is only available on Android, Fuchsia and Linux.
+    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+    pub const CONFIRM: crate::SendFlags = crate::SendFlags(libc::MSG_CONFIRM);
 }
 
 #[cfg(not(target_os = "redox"))]
@@ -348,6 +502,7 @@ impl std::fmt::Debug for RecvFlags {
         f.debug_struct("RecvFlags")
             .field("is_end_of_record", &self.is_end_of_record())
             .field("is_out_of_band", &self.is_out_of_band())
+            .field("is_ctrunc", &self.is_ctrunc())
             .field("is_truncated", &self.is_truncated())
             .finish()
     }
@@ -417,7 +572,7 @@ impl SockAddr {
                 unsafe {
                     ptr::copy_nonoverlapping(
                         bytes.as_ptr(),
-                        storage.sun_path.as_mut_ptr() as *mut u8,
+                        storage.sun_path.as_mut_ptr().cast::<u8>(),
                         bytes.len(),
                     )
                 };
@@ -478,18 +633,209 @@ impl SockAddr {
             None
         }
     }
+
+    /// Constructs a `SockAddr` with the family `AF_NETLINK` and the provided
+    /// port ID and multicast groups.
+    ///
+    /// A port ID of zero addresses the kernel, or when binding lets the kernel
+    /// assign one. `groups` is a bit mask of the multicast groups 1 to 32 to
+    /// subscribe to, see [`Socket::add_netlink_membership`] for other groups.
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`Socket::add_netlink_membership`]: crate::Socket::add_netlink_membership
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn netlink(pid: u32, groups: u32) -> SockAddr {
+        // Safety: all zeroes is a valid `sockaddr_storage`.
+        let mut storage: sockaddr_storage = unsafe { mem::zeroed() };
+        // Safety: a `sockaddr_nl` fits in a `sockaddr_storage`.
+        let addr =
+            unsafe { &mut *(&mut storage as *mut sockaddr_storage).cast::<libc::sockaddr_nl>() };
+        addr.nl_family = libc::AF_NETLINK as sa_family_t;
+        addr.nl_pid = pid;
+        addr.nl_groups = groups;
+        unsafe { SockAddr::new(storage, size_of::<libc::sockaddr_nl>() as socklen_t) }
+    }
+
+    /// Returns this address netlink port ID/multicast groups if it is in the
+    /// `AF_NETLINK` family, otherwise return `None`.
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn netlink_address(&self) -> Option<(u32, u32)> {
+        if self.family() == libc::AF_NETLINK as sa_family_t {
+            // Safety: if the ss_family field is AF_NETLINK then storage must be a sockaddr_nl.
+            let addr = unsafe { &*(self.as_ptr() as *const libc::sockaddr_nl) };
+            Some((addr.nl_pid, addr.nl_groups))
+        } else {
+            None
+        }
+    }
+}
+
+/// Formats an `AF_UNIX` address, see `SockAddr`'s `Display` implementation.
+pub(crate) fn fmt_unix_addr(addr: &SockAddr, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    // Safety: caller ensures the address is in the `AF_UNIX` family, which
+    // means the storage must be a `sockaddr_un`.
+    let storage = unsafe { &*(addr.as_ptr() as *const libc::sockaddr_un) };
+    let base = storage as *const _ as usize;
+    let path = &storage.sun_path as *const _ as usize;
+    let sun_path_offset = path - base;
+    let len = (addr.len() as usize)
+        .saturating_sub(sun_path_offset)
+        .min(storage.sun_path.len());
+    // Safety: `len` is within the bounds of `sun_path`.
+    let bytes: &[u8] = unsafe { slice::from_raw_parts(storage.sun_path.as_ptr().cast(), len) };
+    // Address in the abstract namespace.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    if let Some((0, name)) = bytes.split_first() {
+        return write!(f, "@{}", name.escape_ascii());
+    }
+    let path = bytes.split(|b| *b == 0).next().unwrap_or(bytes);
+    if path.is_empty() {
+        f.write_str("(unnamed)")
+    } else {
+        fmt::Display::fmt(&Path::new(OsStr::from_bytes(path)).display(), f)
+    }
 }
 
 pub(crate) type Socket = c_int;
 
+#[allow(clippy::io_other_error)] // `io::Error::other` requires Rust 1.74.
+pub(crate) fn getaddrinfo(
+    host: Option<&CStr>,
+    service: Option<&CStr>,
+    hints: &addrinfo,
+) -> io::Result<*mut addrinfo> {
+    let mut list = ptr::null_mut();
+    let res = unsafe {
+        libc::getaddrinfo(
+            host.map_or(ptr::null(), CStr::as_ptr),
+            service.map_or(ptr::null(), CStr::as_ptr),
+            hints,
+            &mut list,
+        )
+    };
+    match res {
+        0 => Ok(list),
+        libc::EAI_SYSTEM => Err(io::Error::last_os_error()),
+        err => {
+            let msg = unsafe { CStr::from_ptr(libc::gai_strerror(err)) };
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!(
+                    "failed to lookup address information: {}",
+                    msg.to_string_lossy()
+                ),
+            ))
+        }
+    }
+}
+
+#[cfg(not(target_os = "redox"))]
+pub(crate) fn interfaces() -> io::Result<Vec<crate::Interface>> {
+    let mut list: *mut libc::ifaddrs = ptr::null_mut();
+    syscall!(@no_socket getifaddrs(&mut list))?;
+
+    let mut interfaces: Vec<crate::Interface> = Vec::new();
+    let mut next = list;
+    while !next.is_null() {
+        // Safety: `getifaddrs` returned a valid linked list.
+        let ifa = unsafe { &*next };
+        next = ifa.ifa_next;
+
+        let name = unsafe { CStr::from_ptr(ifa.ifa_name) };
+        let interface = match interfaces
+            .iter_mut()
+            .position(|interface| interface.name.as_bytes() == name.to_bytes())
+        {
+            Some(i) => &mut interfaces[i],
+            None => {
+                let flags = ifa.ifa_flags as c_int;
+                interfaces.push(crate::Interface {
+                    name: name.to_string_lossy().into_owned(),
+                    index: unsafe { libc::if_nametoindex(ifa.ifa_name) },
+                    flags: ifa.ifa_flags as u32,
+                    up: flags & libc::IFF_UP != 0,
+                    loopback: flags & libc::IFF_LOOPBACK != 0,
+                    multicast: flags & libc::IFF_MULTICAST != 0,
+                    addresses: Vec::new(),
+                });
+                interfaces.last_mut().unwrap()
+            }
+        };
+
+        if ifa.ifa_addr.is_null() {
+            continue;
+        }
+        // Safety: checked the pointer above.
+        let len = match c_int::from(unsafe { (*ifa.ifa_addr).sa_family }) {
+            libc::AF_INET => size_of::<libc::sockaddr_in>(),
+            libc::AF_INET6 => size_of::<libc::sockaddr_in6>(),
+            _ => continue,
+        };
+        let mut storage: sockaddr_storage = unsafe { mem::zeroed() };
+        // Safety: `ifa_addr` is valid for at least `len` bytes for the
+        // families above.
+        unsafe {
+            ptr::copy_nonoverlapping(
+                ifa.ifa_addr.cast::<u8>(),
+                (&mut storage as *mut sockaddr_storage).cast::<u8>(),
+                len,
+            );
+        }
+        let address = unsafe { SockAddr::new(storage, len as socklen_t) };
+        interface.addresses.push(address);
+    }
+
+    unsafe { libc::freeifaddrs(list) };
+    Ok(interfaces)
+}
+
+#[cfg(all(
+    feature = "all",
+    any(target_os = "android", target_os = "linux", target_vendor = "apple")
+))]
+use libc::SIOCGIFMTU;
+// Value from `sys/sockio.h`, not defined by libc.
+#[cfg(all(feature = "all", target_os = "freebsd"))]
+const SIOCGIFMTU: libc::c_ulong = 0xc020_6933;
+
+#[cfg(all(
+    feature = "all",
+    any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_vendor = "apple",
+    )
+))]
+pub(crate) fn interface_mtu(name: &str) -> io::Result<u32> {
+    // Safety: all zeroes is a valid `ifreq`.
+    let mut request: libc::ifreq = unsafe { mem::zeroed() };
+    // Need at least one byte for the null terminator.
+    if name.len() >= request.ifr_name.len() || name.as_bytes().contains(&0) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "invalid interface name",
+        ));
+    }
+    for (dst, src) in request.ifr_name.iter_mut().zip(name.as_bytes()) {
+        *dst = *src as libc::c_char;
+    }
+
+    let socket = crate::Socket::new(crate::Domain::IPV4, crate::Type::DGRAM, None)?;
+    syscall!(ioctl(socket.inner, SIOCGIFMTU as _, &mut request))?;
+    // Safety: `SIOCGIFMTU` sets the MTU field.
+    Ok(unsafe { request.ifr_ifru.ifru_mtu } as u32)
+}
+
 pub(crate) fn socket(family: c_int, ty: c_int, protocol: c_int) -> io::Result<Socket> {
-    syscall!(socket(family, ty, protocol))
+    syscall!(@no_socket socket(family, ty, protocol))
 }
 
 #[cfg(feature = "all")]
 pub(crate) fn socketpair(family: c_int, ty: c_int, protocol: c_int) -> io::Result<[Socket; 2]> {
     let mut fds = [0, 0];
-    syscall!(socketpair(family, ty, protocol, fds.as_mut_ptr())).map(|_| fds)
+    syscall!(@no_socket socketpair(family, ty, protocol, fds.as_mut_ptr())).map(|_| fds)
 }
 
 pub(crate) fn bind(fd: Socket, addr: &SockAddr) -> io::Result<()> {
@@ -497,9 +843,11 @@ pub(crate) fn bind(fd: Socket, addr: &SockAddr) -> io::Result<()> {
 }
 
 pub(crate) fn connect(fd: Socket, addr: &SockAddr) -> io::Result<()> {
+    syscall_span!("connect", fd, addr = ?addr);
     syscall!(connect(fd, addr.as_ptr(), addr.len())).map(|_| ())
 }
 
+#[allow(clippy::io_other_error)] // `io::Error::other` requires Rust 1.74.
 pub(crate) fn poll_connect(socket: &crate::Socket, timeout: Duration) -> io::Result<()> {
     let start = Instant::now();
 
@@ -516,9 +864,9 @@ pub(crate) fn poll_connect(socket: &crate::Socket, timeout: Duration) -> io::Res
         }
 
         let timeout = (timeout - elapsed).as_millis();
-        let timeout = clamp(timeout, 1, c_int::max_value() as u128) as c_int;
+        let timeout = clamp(timeout, 1, c_int::MAX as u128) as c_int;
 
-        match syscall!(poll(&mut pollfd, 1, timeout)) {
+        match syscall!(@no_socket poll(&mut pollfd, 1, timeout)) {
             Ok(0) => return Err(io::ErrorKind::TimedOut.into()),
             Ok(_) => {
                 // Error or hang up indicates an error (or failure to connect).
@@ -563,18 +911,18 @@ pub(crate) fn listen(fd: Socket, backlog: c_int) -> io::Result<()> {
 
 pub(crate) fn accept(fd: Socket) -> io::Result<(Socket, SockAddr)> {
     // Safety: `accept` initialises the `SockAddr` for us.
-    unsafe { SockAddr::init(|storage, len| syscall!(accept(fd, storage.cast(), len))) }
+    unsafe { SockAddr::init_prefix(|storage, len| syscall!(accept(fd, storage.cast(), len))) }
 }
 
 pub(crate) fn getsockname(fd: Socket) -> io::Result<SockAddr> {
     // Safety: `accept` initialises the `SockAddr` for us.
-    unsafe { SockAddr::init(|storage, len| syscall!(getsockname(fd, storage.cast(), len))) }
+    unsafe { SockAddr::init_prefix(|storage, len| syscall!(getsockname(fd, storage.cast(), len))) }
         .map(|(_, addr)| addr)
 }
 
 pub(crate) fn getpeername(fd: Socket) -> io::Result<SockAddr> {
     // Safety: `accept` initialises the `SockAddr` for us.
-    unsafe { SockAddr::init(|storage, len| syscall!(getpeername(fd, storage.cast(), len))) }
+    unsafe { SockAddr::init_prefix(|storage, len| syscall!(getpeername(fd, storage.cast(), len))) }
         .map(|(_, addr)| addr)
 }
 
@@ -582,6 +930,10 @@ pub(crate) fn try_clone(fd: Socket) -> io::Result<Socket> {
     syscall!(fcntl(fd, libc::F_DUPFD_CLOEXEC, 0))
 }
 
+pub(crate) fn try_clone_raw(fd: Socket) -> io::Result<Socket> {
+    syscall!(fcntl(fd, libc::F_DUPFD, 0))
+}
+
 pub(crate) fn set_nonblocking(fd: Socket, nonblocking: bool) -> io::Result<()> {
     if nonblocking {
         fcntl_add(fd, libc::F_GETFL, libc::F_SETFL, libc::O_NONBLOCK)
@@ -590,6 +942,44 @@ pub(crate) fn set_nonblocking(fd: Socket, nonblocking: bool) -> io::Result<()> {
     }
 }
 
+pub(crate) fn bytes_available(fd: Socket) -> io::Result<usize> {
+    let mut available: c_int = 0;
+    syscall!(ioctl(fd, libc::FIONREAD, &mut available)).map(|_| available as usize)
+}
+
+#[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+pub(crate) fn peek_size(fd: Socket) -> io::Result<usize> {
+    // With `MSG_TRUNC` the real length of the datagram is returned, even
+    // though nothing is copied into the empty buffer.
+    recv(fd, &mut [], libc::MSG_PEEK | libc::MSG_TRUNC)
+}
+
+#[cfg(target_vendor = "apple")]
+pub(crate) fn peek_size(fd: Socket) -> io::Result<usize> {
+    unsafe { getsockopt::<c_int>(fd, libc::SOL_SOCKET, libc::SO_NREAD).map(|n| n as usize) }
+}
+
+#[cfg(not(any(
+    target_os = "android",
+    target_os = "fuchsia",
+    target_os = "linux",
+    target_vendor = "apple",
+)))]
+pub(crate) fn peek_size(fd: Socket) -> io::Result<usize> {
+    bytes_available(fd)
+}
+
+pub(crate) fn at_mark(fd: Socket) -> io::Result<bool> {
+    // Not defined in the libc crate.
+    extern "C" {
+        fn sockatmark(fd: c_int) -> c_int;
+    }
+    match unsafe { sockatmark(fd) } {
+        -1 => Err(io::Error::last_os_error()),
+        res => Ok(res != 0),
+    }
+}
+
 pub(crate) fn shutdown(fd: Socket, how: Shutdown) -> io::Result<()> {
     let how = match how {
         Shutdown::Write => libc::SHUT_WR,
@@ -600,6 +990,7 @@ pub(crate) fn shutdown(fd: Socket, how: Shutdown) -> io::Result<()> {
 }
 
 pub(crate) fn recv(fd: Socket, buf: &mut [MaybeUninit<u8>], flags: c_int) -> io::Result<usize> {
+    syscall_span!("recv", fd, len = buf.len(), flags);
     syscall!(recv(
         fd,
         buf.as_mut_ptr().cast(),
@@ -614,19 +1005,28 @@ pub(crate) fn recv_from(
     buf: &mut [MaybeUninit<u8>],
     flags: c_int,
 ) -> io::Result<(usize, SockAddr)> {
-    // Safety: `recvfrom` initialises the `SockAddr` for us.
+    syscall_span!("recv_from", fd, len = buf.len(), flags);
+    let empty = buf.is_empty();
+    let recvfrom = |addr: *mut sockaddr_storage, addrlen: *mut socklen_t| {
+        syscall!(recvfrom(
+            fd,
+            buf.as_mut_ptr().cast(),
+            min(buf.len(), MAX_BUF_LEN),
+            flags,
+            addr.cast(),
+            addrlen
+        ))
+        .map(|n| n as usize)
+    };
+    // Safety: `recvfrom` initialises the `SockAddr` for us. Except when called
+    // with an empty buffer, then the OS might not initialise it, see
+    // `SockAddr::init`.
     unsafe {
-        SockAddr::init(|addr, addrlen| {
-            syscall!(recvfrom(
-                fd,
-                buf.as_mut_ptr().cast(),
-                min(buf.len(), MAX_BUF_LEN),
-                flags,
-                addr.cast(),
-                addrlen
-            ))
-            .map(|n| n as usize)
-        })
+        if empty {
+            SockAddr::init(recvfrom)
+        } else {
+            SockAddr::init_prefix(recvfrom)
+        }
     }
 }
 
@@ -636,6 +1036,7 @@ pub(crate) fn recv_vectored(
     bufs: &mut [crate::MaybeUninitSlice<'_>],
     flags: c_int,
 ) -> io::Result<(usize, RecvFlags)> {
+    syscall_span!("recv_vectored", fd, bufs = bufs.len(), flags);
     recvmsg(fd, ptr::null_mut(), bufs, flags).map(|(n, _, recv_flags)| (n, recv_flags))
 }
 
@@ -645,6 +1046,7 @@ pub(crate) fn recv_from_vectored(
     bufs: &mut [crate::MaybeUninitSlice<'_>],
     flags: c_int,
 ) -> io::Result<(usize, RecvFlags, SockAddr)> {
+    syscall_span!("recv_from_vectored", fd, bufs = bufs.len(), flags);
     // Safety: `recvmsg` initialises the address storage and we set the length
     // manually.
     unsafe {
@@ -661,6 +1063,7 @@ pub(crate) fn recv_from_vectored(
 
 /// Returns the (bytes received, sending address len, `RecvFlags`).
 #[cfg(not(target_os = "redox"))]
+#[allow(clippy::unnecessary_cast)] // `IovLen` can be `usize`.
 fn recvmsg(
     fd: Socket,
     msg_name: *mut sockaddr_storage,
@@ -682,7 +1085,355 @@ fn recvmsg(
         .map(|n| (n as usize, msg.msg_namelen, RecvFlags(msg.msg_flags)))
 }
 
+/// Receives a message, calling `decode` with the level, type and data of every
+/// control message received.
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+fn recv_from_with_control<F>(
+    fd: Socket,
+    buf: &mut [MaybeUninit<u8>],
+    mut decode: F,
+) -> io::Result<(usize, SockAddr)>
+where
+    F: FnMut(c_int, c_int, &[u8]),
+{
+    let mut bufs = [crate::MaybeUninitSlice::new(buf)];
+    // Enough space for a couple of `c_int` sized control messages, `u64` for
+    // alignment.
+    let mut control = [0u64; 8];
+    // Safety: `recvmsg` initialises the address storage and we set the length
+    // manually.
+    unsafe {
+        SockAddr::init(|storage, len| {
+            // libc::msghdr contains unexported padding fields on Fuchsia.
+            let mut msg: libc::msghdr = mem::zeroed();
+            msg.msg_name = storage.cast();
+            msg.msg_namelen = *len;
+            msg.msg_iov = bufs.as_mut_ptr().cast();
+            msg.msg_iovlen = 1;
+            msg.msg_control = control.as_mut_ptr().cast();
+            msg.msg_controllen = mem::size_of_val(&control) as _;
+            let n = syscall!(recvmsg(fd, &mut msg, 0))? as usize;
+            *len = msg.msg_namelen;
+
+            let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+            while !cmsg.is_null() {
+                let hdr = &*cmsg;
+                let data_len = hdr.cmsg_len as usize - libc::CMSG_LEN(0) as usize;
+                let data = std::slice::from_raw_parts(libc::CMSG_DATA(cmsg), data_len);
+                decode(hdr.cmsg_level, hdr.cmsg_type, data);
+                cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+            }
+            Ok(n)
+        })
+    }
+}
+
+// Not defined by libc, values from `linux/icmpv6.h` and `netinet/icmp6.h`.
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+const ICMP6_FILTER: c_int = 1;
+#[cfg(all(feature = "all", any(target_os = "freebsd", target_vendor = "apple")))]
+const ICMP6_FILTER: c_int = 18;
+
+// Values from `linux/in6.h`, not defined by libc.
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+const IPV6_FL_A_GET: u8 = 0;
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+const IPV6_FL_A_PUT: u8 = 1;
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+const IPV6_FL_F_CREATE: u16 = 1;
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+const IPV6_FL_S_NONE: u8 = 0;
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+const IPV6_FL_S_EXCL: u8 = 1;
+
+// Values from `linux/rds.h`, not defined by libc.
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+const SOL_RDS: c_int = 276;
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+const RDS_RECVERR: c_int = 5;
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+const RDS_CONG_MONITOR: c_int = 6;
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+const SO_RDS_TRANSPORT: c_int = 8;
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+const SIOCRDSSETTOS: libc::c_ulong = 0x89E0; // `SIOCPROTOPRIVATE`.
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+const SIOCRDSGETTOS: libc::c_ulong = 0x89E1; // `SIOCPROTOPRIVATE + 1`.
+
+// Values from `linux/tcp.h`, not defined by libc.
+#[cfg(all(feature = "all", target_os = "linux"))]
+const TCP_AO_ADD_KEY: c_int = 38;
+#[cfg(all(feature = "all", target_os = "linux"))]
+const TCP_AO_DEL_KEY: c_int = 39;
+#[cfg(all(feature = "all", target_os = "linux"))]
+const TCP_AO_MAXKEYLEN: usize = 80;
+
+/// Sets the `IPV6_FLOWLABEL_MGR` option, returning the (possibly kernel
+/// assigned) flow label.
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+fn flowlabel_mgr(
+    fd: Socket,
+    destination: &Ipv6Addr,
+    label: u32,
+    action: u8,
+    share: u8,
+    flags: u16,
+) -> io::Result<u32> {
+    // `struct in6_flowlabel_req` in `linux/in6.h`.
+    #[repr(C)]
+    struct in6_flowlabel_req {
+        flr_dst: in6_addr,
+        flr_label: u32,
+        flr_action: u8,
+        flr_share: u8,
+        flr_flags: u16,
+        flr_expires: u16,
+        flr_linger: u16,
+        flr_pad: u32,
+    }
+
+    let mut req = in6_flowlabel_req {
+        flr_dst: to_in6_addr(destination),
+        flr_label: label.to_be(),
+        flr_action: action,
+        flr_share: share,
+        flr_flags: flags,
+        flr_expires: 0,
+        flr_linger: 0,
+        flr_pad: 0,
+    };
+    // If no label is given the kernel writes the assigned label into `req`.
+    syscall!(setsockopt(
+        fd,
+        libc::IPPROTO_IPV6,
+        libc::IPV6_FLOWLABEL_MGR,
+        (&mut req as *mut in6_flowlabel_req).cast(),
+        size_of::<in6_flowlabel_req>() as libc::socklen_t,
+    ))?;
+    Ok(u32::from_be(req.flr_label))
+}
+
+/// Adds or drops a packet socket membership using the `PACKET_ADD_MEMBERSHIP`
+/// or `PACKET_DROP_MEMBERSHIP` option.
+#[cfg(all(feature = "all", target_os = "linux"))]
+fn packet_membership(
+    fd: Socket,
+    ifindex: u32,
+    mr_type: c_int,
+    address: &[u8],
+    add: bool,
+) -> io::Result<()> {
+    let mut mreq = libc::packet_mreq {
+        mr_ifindex: ifindex as c_int,
+        mr_type: mr_type as u16,
+        mr_alen: address.len() as u16,
+        mr_address: [0; 8],
+    };
+    if address.len() > mreq.mr_address.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "hardware address too long",
+        ));
+    }
+    mreq.mr_address[..address.len()].copy_from_slice(address);
+    let opt = if add {
+        libc::PACKET_ADD_MEMBERSHIP
+    } else {
+        libc::PACKET_DROP_MEMBERSHIP
+    };
+    unsafe { setsockopt(fd, libc::SOL_PACKET, opt, mreq) }
+}
+
+/// Sets the `TLS_TX` or `TLS_RX` option.
+#[cfg(all(feature = "all", target_os = "linux"))]
+fn set_tls_crypto_info(fd: Socket, opt: c_int, info: &TlsCryptoInfo) -> io::Result<()> {
+    let tls_info = |version: TlsVersion, cipher_type: u16| libc::tls_crypto_info {
+        version: match version {
+            TlsVersion::Tls12 => libc::TLS_1_2_VERSION,
+            TlsVersion::Tls13 => libc::TLS_1_3_VERSION,
+        },
+        cipher_type,
+    };
+    unsafe {
+        match *info {
+            TlsCryptoInfo::Aes128Gcm {
+                version,
+                iv,
+                key,
+                salt,
+                rec_seq,
+            } => setsockopt(
+                fd,
+                libc::SOL_TLS,
+                opt,
+                libc::tls12_crypto_info_aes_gcm_128 {
+                    info: tls_info(version, libc::TLS_CIPHER_AES_GCM_128),
+                    iv,
+                    key,
+                    salt,
+                    rec_seq,
+                },
+            ),
+            TlsCryptoInfo::Aes256Gcm {
+                version,
+                iv,
+                key,
+                salt,
+                rec_seq,
+            } => setsockopt(
+                fd,
+                libc::SOL_TLS,
+                opt,
+                libc::tls12_crypto_info_aes_gcm_256 {
+                    info: tls_info(version, libc::TLS_CIPHER_AES_GCM_256),
+                    iv,
+                    key,
+                    salt,
+                    rec_seq,
+                },
+            ),
+            TlsCryptoInfo::Chacha20Poly1305 {
+                version,
+                iv,
+                key,
+                rec_seq,
+            } => setsockopt(
+                fd,
+                libc::SOL_TLS,
+                opt,
+                libc::tls12_crypto_info_chacha20_poly1305 {
+                    info: tls_info(version, libc::TLS_CIPHER_CHACHA20_POLY1305),
+                    iv,
+                    key,
+                    salt: [],
+                    rec_seq,
+                },
+            ),
+        }
+    }
+}
+
+/// Sets the `TCP_AO_ADD_KEY` option.
+#[cfg(all(feature = "all", target_os = "linux"))]
+fn add_tcp_ao_key(fd: Socket, key: &TcpAoKey<'_>) -> io::Result<()> {
+    // `struct tcp_ao_add` in `linux/tcp.h`.
+    #[repr(C, align(8))]
+    struct tcp_ao_add {
+        addr: sockaddr_storage,
+        alg_name: [libc::c_char; 64],
+        ifindex: i32,
+        // Bitfield of `set_current`, `set_rnext` and reserved bits.
+        flags: u32,
+        reserved2: u16,
+        prefix: u8,
+        sndid: u8,
+        rcvid: u8,
+        maclen: u8,
+        keyflags: u8,
+        keylen: u8,
+        key: [u8; TCP_AO_MAXKEYLEN],
+    }
+
+    let algorithm = key.algorithm.as_bytes();
+    // Must leave room for the null byte.
+    if algorithm.len() >= 64 || algorithm.contains(&0) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "TCP-AO algorithm name too long or contains a null byte",
+        ));
+    }
+    if key.key.len() > TCP_AO_MAXKEYLEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "TCP-AO key too long",
+        ));
+    }
+
+    let mut add = tcp_ao_add {
+        addr: tcp_ao_addr(key.peer),
+        alg_name: [0; 64],
+        ifindex: 0,
+        flags: 0,
+        reserved2: 0,
+        prefix: key.prefix,
+        sndid: key.send_id,
+        rcvid: key.recv_id,
+        maclen: key.mac_len,
+        keyflags: 0,
+        keylen: key.key.len() as u8,
+        key: [0; TCP_AO_MAXKEYLEN],
+    };
+    for (dst, src) in add.alg_name.iter_mut().zip(algorithm) {
+        *dst = *src as libc::c_char;
+    }
+    add.key[..key.key.len()].copy_from_slice(key.key);
+    unsafe { setsockopt(fd, libc::IPPROTO_TCP, TCP_AO_ADD_KEY, add) }
+}
+
+/// Sets the `TCP_AO_DEL_KEY` option.
+#[cfg(all(feature = "all", target_os = "linux"))]
+fn delete_tcp_ao_key(fd: Socket, key: &TcpAoKey<'_>) -> io::Result<()> {
+    // `struct tcp_ao_del` in `linux/tcp.h`.
+    #[repr(C, align(8))]
+    struct tcp_ao_del {
+        addr: sockaddr_storage,
+        ifindex: i32,
+        // Bitfield of `set_current`, `set_rnext`, `del_async` and reserved
+        // bits.
+        flags: u32,
+        reserved2: u16,
+        prefix: u8,
+        sndid: u8,
+        rcvid: u8,
+        current_key: u8,
+        rnext: u8,
+        keyflags: u8,
+    }
+
+    let del = tcp_ao_del {
+        addr: tcp_ao_addr(key.peer),
+        ifindex: 0,
+        flags: 0,
+        reserved2: 0,
+        prefix: key.prefix,
+        sndid: key.send_id,
+        rcvid: key.recv_id,
+        current_key: 0,
+        rnext: 0,
+        keyflags: 0,
+    };
+    unsafe { setsockopt(fd, libc::IPPROTO_TCP, TCP_AO_DEL_KEY, del) }
+}
+
+/// Returns the address of a TCP-AO key, with the port set to zero as the
+/// kernel rejects keys with a port.
+#[cfg(all(feature = "all", target_os = "linux"))]
+fn tcp_ao_addr(peer: &SockAddr) -> sockaddr_storage {
+    let mut storage = peer.as_storage();
+    match c_int::from(storage.ss_family) {
+        // Safety: `ss_family` guarantees the storage holds a `sockaddr_in`.
+        libc::AF_INET => unsafe {
+            (*(&mut storage as *mut sockaddr_storage).cast::<libc::sockaddr_in>()).sin_port = 0;
+        },
+        // Safety: `ss_family` guarantees the storage holds a `sockaddr_in6`.
+        libc::AF_INET6 => unsafe {
+            (*(&mut storage as *mut sockaddr_storage).cast::<libc::sockaddr_in6>()).sin6_port = 0;
+        },
+        _ => {}
+    }
+    storage
+}
+
+/// Decodes the data of a `c_int` sized control message.
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+fn cmsg_int(data: &[u8]) -> Option<c_int> {
+    let mut value = [0; size_of::<c_int>()];
+    value.copy_from_slice(data.get(..size_of::<c_int>())?);
+    Some(c_int::from_ne_bytes(value))
+}
+
 pub(crate) fn send(fd: Socket, buf: &[u8], flags: c_int) -> io::Result<usize> {
+    syscall_span!("send", fd, len = buf.len(), flags);
     syscall!(send(
         fd,
         buf.as_ptr().cast(),
@@ -694,10 +1445,12 @@ pub(crate) fn send(fd: Socket, buf: &[u8], flags: c_int) -> io::Result<usize> {
 
 #[cfg(not(target_os = "redox"))]
 pub(crate) fn send_vectored(fd: Socket, bufs: &[IoSlice<'_>], flags: c_int) -> io::Result<usize> {
+    syscall_span!("send_vectored", fd, bufs = bufs.len(), flags);
     sendmsg(fd, ptr::null(), 0, bufs, flags)
 }
 
 pub(crate) fn send_to(fd: Socket, buf: &[u8], addr: &SockAddr, flags: c_int) -> io::Result<usize> {
+    syscall_span!("send_to", fd, len = buf.len(), addr = ?addr, flags);
     syscall!(sendto(
         fd,
         buf.as_ptr().cast(),
@@ -716,11 +1469,13 @@ pub(crate) fn send_to_vectored(
     addr: &SockAddr,
     flags: c_int,
 ) -> io::Result<usize> {
+    syscall_span!("send_to_vectored", fd, bufs = bufs.len(), addr = ?addr, flags);
     sendmsg(fd, addr.as_storage_ptr(), addr.len(), bufs, flags)
 }
 
 /// Returns the (bytes received, sending address len, `RecvFlags`).
 #[cfg(not(target_os = "redox"))]
+#[allow(clippy::unnecessary_cast)] // `IovLen` can be `usize`.
 fn sendmsg(
     fd: Socket,
     msg_name: *const sockaddr_storage,
@@ -772,7 +1527,7 @@ fn into_timeval(duration: Option<Duration>) -> libc::timeval {
         // https://github.com/rust-lang/libc/issues/1848
         #[cfg_attr(target_env = "musl", allow(deprecated))]
         Some(duration) => libc::timeval {
-            tv_sec: min(duration.as_secs(), libc::time_t::max_value() as u64) as libc::time_t,
+            tv_sec: min(duration.as_secs(), libc::time_t::MAX as u64) as libc::time_t,
             tv_usec: duration.subsec_micros() as libc::suseconds_t,
         },
         None => libc::timeval {
@@ -813,7 +1568,10 @@ pub(crate) fn set_tcp_keepalive(fd: Socket, keepalive: &TcpKeepalive) -> io::Res
         }
 
         if let Some(retries) = keepalive.retries {
-            unsafe { setsockopt(fd, libc::IPPROTO_TCP, libc::TCP_KEEPCNT, retries as c_int)? }
+            let retries: c_int = std::convert::TryFrom::try_from(retries).map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidInput, "keepalive retries too large")
+            })?;
+            unsafe { setsockopt(fd, libc::IPPROTO_TCP, libc::TCP_KEEPCNT, retries)? }
         }
     }
 
@@ -821,7 +1579,7 @@ pub(crate) fn set_tcp_keepalive(fd: Socket, keepalive: &TcpKeepalive) -> io::Res
 }
 
 fn into_secs(duration: Duration) -> c_int {
-    min(duration.as_secs(), c_int::max_value() as u64) as c_int
+    min(duration.as_secs(), c_int::MAX as u64) as c_int
 }
 
 /// Add `flag` to the current set flags of `F_GETFD`.
@@ -852,6 +1610,7 @@ fn fcntl_remove(fd: Socket, get_cmd: c_int, set_cmd: c_int, flag: c_int) -> io::
 pub(crate) unsafe fn getsockopt<T>(fd: Socket, opt: c_int, val: c_int) -> io::Result<T> {
     let mut payload: MaybeUninit<T> = MaybeUninit::uninit();
     let mut len = size_of::<T>() as libc::socklen_t;
+    syscall_span!("getsockopt", fd, level = opt, optname = val, len);
     syscall!(getsockopt(
         fd,
         opt,
@@ -859,6 +1618,11 @@ pub(crate) unsafe fn getsockopt<T>(fd: Socket, opt: c_int, val: c_int) -> io::Re
         payload.as_mut_ptr().cast(),
         &mut len,
     ))
+    .map_err(|err| {
+        #[cfg(feature = "error-context")]
+        crate::error::record_option("getsockopt", fd, opt, val, &err);
+        err
+    })
     .map(|_| {
         debug_assert_eq!(len as usize, size_of::<T>());
         // Safety: `getsockopt` initialised `payload` for us.
@@ -873,6 +1637,13 @@ pub(crate) unsafe fn setsockopt<T>(
     val: c_int,
     payload: T,
 ) -> io::Result<()> {
+    syscall_span!(
+        "setsockopt",
+        fd,
+        level = opt,
+        optname = val,
+        len = size_of::<T>()
+    );
     let payload = &payload as *const T as *const c_void;
     syscall!(setsockopt(
         fd,
@@ -881,6 +1652,11 @@ pub(crate) unsafe fn setsockopt<T>(
         payload,
         mem::size_of::<T>() as libc::socklen_t,
     ))
+    .map_err(|err| {
+        #[cfg(feature = "error-context")]
+        crate::error::record_option("setsockopt", fd, opt, val, &err);
+        err
+    })
     .map(|_| ())
 }
 
@@ -899,6 +1675,30 @@ pub(crate) fn to_in_addr(addr: &Ipv4Addr) -> in_addr {
     }
 }
 
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "fuchsia",
+    target_os = "linux",
+))]
+pub(crate) fn to_mreqn(
+    multiaddr: &Ipv4Addr,
+    interface: &crate::InterfaceIndexOrAddress,
+) -> io::Result<libc::ip_mreqn> {
+    Ok(match interface {
+        crate::InterfaceIndexOrAddress::Index(index) => libc::ip_mreqn {
+            imr_multiaddr: to_in_addr(multiaddr),
+            imr_address: to_in_addr(&Ipv4Addr::UNSPECIFIED),
+            imr_ifindex: *index as _,
+        },
+        crate::InterfaceIndexOrAddress::Address(address) => libc::ip_mreqn {
+            imr_multiaddr: to_in_addr(multiaddr),
+            imr_address: to_in_addr(address),
+            imr_ifindex: 0,
+        },
+    })
+}
+
 pub(crate) fn from_in_addr(in_addr: in_addr) -> Ipv4Addr {
     Ipv4Addr::from(in_addr.s_addr.to_ne_bytes())
 }
@@ -952,13 +1752,23 @@ impl crate::Socket {
     pub(crate) fn _accept4(&self, flags: c_int) -> io::Result<(crate::Socket, SockAddr)> {
         // Safety: `accept4` initialises the `SockAddr` for us.
         unsafe {
-            SockAddr::init(|storage, len| {
+            SockAddr::init_prefix(|storage, len| {
                 syscall!(accept4(self.inner, storage.cast(), len, flags))
                     .map(|inner| crate::Socket { inner })
             })
         }
     }
 
+    /// Returns `true` if `CLOEXEC` is set on the socket.
+    ///
+    /// For more information about this option, see [`set_cloexec`].
+    ///
+    /// [`set_cloexec`]: crate::Socket::set_cloexec
+    #[cfg(feature = "all")]
+    pub fn cloexec(&self) -> io::Result<bool> {
+        syscall!(fcntl(self.inner, libc::F_GETFD)).map(|flags| flags & libc::FD_CLOEXEC != 0)
+    }
+
     /// Sets `CLOEXEC` on the socket.
     ///
     /// # Notes
@@ -977,33 +1787,203 @@ impl crate::Socket {
         }
     }
 
-    /// Sets `SO_NOSIGPIPE` on the socket.
+    /// Sends the file descriptor `fd`, e.g. a listening socket, to the peer of
+    /// this Unix domain socket using a `SCM_RIGHTS` control message.
     ///
-    /// # Notes
+    /// Together with [`recv_fd`] this allows handing a socket to another
+    /// process, e.g. for a graceful restart where a new version of a server
+    /// takes over the listener of the old version. `fd` itself remains open
+    /// and owned by the caller.
     ///
-    /// Only supported on Apple platforms (`target_vendor = "apple"`).
-    #[cfg(all(feature = "all", target_vendor = "apple"))]
-    pub fn set_nosigpipe(&self, nosigpipe: bool) -> io::Result<()> {
-        self._set_nosigpipe(nosigpipe)
-    }
-
-    #[cfg(target_vendor = "apple")]
-    pub(crate) fn _set_nosigpipe(&self, nosigpipe: bool) -> io::Result<()> {
+    /// [`recv_fd`]: crate::Socket::recv_fd
+    #[cfg(all(feature = "all", not(target_os = "redox")))]
+    pub fn send_fd(&self, fd: BorrowedFd<'_>) -> io::Result<()> {
+        let fd = fd.as_raw_fd();
+        // At least one byte of data needs to be sent along with the control
+        // message.
+        let mut data = [0u8; 1];
+        let mut iov = libc::iovec {
+            iov_base: data.as_mut_ptr().cast(),
+            iov_len: data.len(),
+        };
+        // Enough space for one `c_int` sized control message, `u64` for
+        // alignment.
+        let mut control = [0u64; 4];
         unsafe {
-            setsockopt(
-                self.inner,
-                libc::SOL_SOCKET,
-                libc::SO_NOSIGPIPE,
-                nosigpipe as c_int,
-            )
+            // libc::msghdr contains unexported padding fields on Fuchsia.
+            let mut msg: libc::msghdr = mem::zeroed();
+            msg.msg_iov = &mut iov;
+            msg.msg_iovlen = 1;
+            msg.msg_control = control.as_mut_ptr().cast();
+            msg.msg_controllen = libc::CMSG_SPACE(size_of::<c_int>() as _) as _;
+            let cmsg = &mut *libc::CMSG_FIRSTHDR(&msg);
+            cmsg.cmsg_level = libc::SOL_SOCKET;
+            cmsg.cmsg_type = libc::SCM_RIGHTS;
+            cmsg.cmsg_len = libc::CMSG_LEN(size_of::<c_int>() as _) as _;
+            ptr::write_unaligned(libc::CMSG_DATA(cmsg).cast::<c_int>(), fd);
+            syscall!(sendmsg(self.inner, &msg, 0)).map(|_| ())
         }
     }
 
-    /// Gets the value of the `TCP_MAXSEG` option on this socket.
+    /// Receives a file descriptor sent by [`send_fd`] on this Unix domain
+    /// socket and returns it as a `Socket`.
     ///
-    /// For more information about this option, see [`set_mss`].
+    /// This receives a single byte of data along with the file descriptor, so
+    /// it should not be mixed with other data on a stream socket.
     ///
-    /// [`set_mss`]: crate::Socket::set_mss
+    /// The returned socket has `CLOEXEC` set. Returns an error of kind
+    /// [`io::ErrorKind::UnexpectedEof`] if the peer closed the connection and
+    /// [`io::ErrorKind::InvalidData`] if the message didn't contain a file
+    /// descriptor, or contained more file descriptors than fit in the control
+    /// buffer. If multiple file descriptors are received only the first is
+    /// returned, the others are closed.
+    ///
+    /// [`send_fd`]: crate::Socket::send_fd
+    #[cfg(all(feature = "all", not(target_os = "redox")))]
+    pub fn recv_fd(&self) -> io::Result<crate::Socket> {
+        #[cfg(any(
+            target_os = "android",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "fuchsia",
+            target_os = "illumos",
+            target_os = "linux",
+            target_os = "netbsd",
+        ))]
+        let flags = libc::MSG_CMSG_CLOEXEC;
+        #[cfg(not(any(
+            target_os = "android",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "fuchsia",
+            target_os = "illumos",
+            target_os = "linux",
+            target_os = "netbsd",
+        )))]
+        let flags = 0;
+
+        let mut data = [0u8; 1];
+        let mut iov = libc::iovec {
+            iov_base: data.as_mut_ptr().cast(),
+            iov_len: data.len(),
+        };
+        let mut control = [0u64; 4];
+        unsafe {
+            // libc::msghdr contains unexported padding fields on Fuchsia.
+            let mut msg: libc::msghdr = mem::zeroed();
+            msg.msg_iov = &mut iov;
+            msg.msg_iovlen = 1;
+            msg.msg_control = control.as_mut_ptr().cast();
+            msg.msg_controllen = mem::size_of_val(&control) as _;
+            if syscall!(recvmsg(self.inner, &mut msg, flags))? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+
+            // Take ownership of all received file descriptors, so that any
+            // we don't return are closed.
+            let mut socket = None;
+            let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+            while !cmsg.is_null() {
+                let hdr = &*cmsg;
+                if hdr.cmsg_level == libc::SOL_SOCKET && hdr.cmsg_type == libc::SCM_RIGHTS {
+                    let data = libc::CMSG_DATA(cmsg).cast::<c_int>();
+                    let len = hdr.cmsg_len as usize - libc::CMSG_LEN(0) as usize;
+                    for i in 0..len / size_of::<c_int>() {
+                        let fd = ptr::read_unaligned(data.add(i));
+                        let received = crate::Socket::from_raw_fd(fd);
+                        if socket.is_none() {
+                            socket = Some(received);
+                        }
+                    }
+                }
+                cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+            }
+
+            if msg.msg_flags & libc::MSG_CTRUNC != 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "too many file descriptors received",
+                ));
+            }
+            match socket {
+                Some(socket) => {
+                    // On platforms that don't have `MSG_CMSG_CLOEXEC` use
+                    // `FD_CLOEXEC`.
+                    if flags == 0 {
+                        socket._set_cloexec(true)?;
+                    }
+                    Ok(socket)
+                }
+                None => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "no file descriptor received",
+                )),
+            }
+        }
+    }
+
+    /// Sends this socket to another process over `channel`, a connected Unix
+    /// domain socket, where [`Socket::receive_from_parent`] receives it.
+    ///
+    /// This is the portable way to hand a socket to another process, e.g. for
+    /// a graceful restart where a new version of a server takes over the
+    /// listener of the old version. On Unix this uses [`send_fd`], on Windows
+    /// the channel is any writer (e.g. a pipe) and the id of the receiving
+    /// process is also required.
+    ///
+    /// [`Socket::receive_from_parent`]: crate::Socket::receive_from_parent
+    /// [`send_fd`]: crate::Socket::send_fd
+    #[cfg(all(feature = "all", not(target_os = "redox")))]
+    pub fn send_to_process<C>(&self, channel: &C) -> io::Result<()>
+    where
+        C: AsRawFd,
+    {
+        crate::SockRef::from(channel).send_fd(self.as_fd())
+    }
+
+    /// Receives a socket sent by [`Socket::send_to_process`] over `channel`, a
+    /// connected Unix domain socket.
+    ///
+    /// On Unix this uses [`recv_fd`], see it for the returned errors. On
+    /// Windows the channel is any reader (e.g. a pipe).
+    ///
+    /// [`Socket::send_to_process`]: crate::Socket::send_to_process
+    /// [`recv_fd`]: crate::Socket::recv_fd
+    #[cfg(all(feature = "all", not(target_os = "redox")))]
+    pub fn receive_from_parent<C>(channel: &C) -> io::Result<crate::Socket>
+    where
+        C: AsRawFd,
+    {
+        crate::SockRef::from(channel).recv_fd()
+    }
+
+    /// Sets `SO_NOSIGPIPE` on the socket.
+    ///
+    /// # Notes
+    ///
+    /// Only supported on Apple platforms (`target_vendor = "apple"`).
+    #[cfg(all(feature = "all", target_vendor = "apple"))]
+    pub fn set_nosigpipe(&self, nosigpipe: bool) -> io::Result<()> {
+        self._set_nosigpipe(nosigpipe)
+    }
+
+    #[cfg(target_vendor = "apple")]
+    pub(crate) fn _set_nosigpipe(&self, nosigpipe: bool) -> io::Result<()> {
+        unsafe {
+            setsockopt(
+                self.inner,
+                libc::SOL_SOCKET,
+                libc::SO_NOSIGPIPE,
+                nosigpipe as c_int,
+            )
+        }
+    }
+
+    /// Gets the value of the `TCP_MAXSEG` option on this socket.
+    ///
+    /// For more information about this option, see [`set_mss`].
+    ///
+    /// [`set_mss`]: crate::Socket::set_mss
     #[cfg(all(feature = "all", not(target_os = "redox")))]
     pub fn mss(&self) -> io::Result<u32> {
         unsafe {
@@ -1028,6 +2008,209 @@ impl crate::Socket {
         }
     }
 
+    /// Get the value of the `TCP_SYNCNT` option on this socket.
+    ///
+    /// For more information about this option, see [`set_syn_count`].
+    ///
+    /// This function is only available on Android, Fuchsia and Linux.
+    ///
+    /// [`set_syn_count`]: crate::Socket::set_syn_count
+    #[cfg(all(
+        feature = "all",
+        any(target_os = "android", target_os = "fuchsia", target_os = "linux")
+    ))]
+    pub fn syn_count(&self) -> io::Result<u8> {
+        unsafe {
+            getsockopt::<c_int>(self.inner, libc::IPPROTO_TCP, libc::TCP_SYNCNT)
+                .map(|count| count as u8)
+        }
+    }
+
+    /// Set the value of the `TCP_SYNCNT` option on this socket.
+    ///
+    /// Sets the number of SYN retransmits that TCP should send before aborting
+    /// the attempt to connect. This can be used to fail a `connect` much faster
+    /// than the system default of (about) two minutes. The value must be
+    /// between 1 and 127, as enforced by the kernel.
+    ///
+    /// This function is only available on Android, Fuchsia and Linux.
+    #[cfg(all(
+        feature = "all",
+        any(target_os = "android", target_os = "fuchsia", target_os = "linux")
+    ))]
+    pub fn set_syn_count(&self, count: u8) -> io::Result<()> {
+        unsafe {
+            setsockopt(
+                self.inner,
+                libc::IPPROTO_TCP,
+                libc::TCP_SYNCNT,
+                count as c_int,
+            )
+        }
+    }
+
+    /// Get the value of the `TCP_REPAIR` option on this socket.
+    ///
+    /// For more information about this option, see [`set_tcp_repair`].
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_tcp_repair`]: crate::Socket::set_tcp_repair
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn tcp_repair(&self) -> io::Result<bool> {
+        unsafe {
+            getsockopt::<c_int>(self.inner, libc::IPPROTO_TCP, libc::TCP_REPAIR)
+                .map(|repair| repair > 0)
+        }
+    }
+
+    /// Set the value of the `TCP_REPAIR` option on this socket.
+    ///
+    /// In repair mode connect and close don't cause any packets to be send and
+    /// the queues and sequence numbers of the connection can be read and
+    /// restored, see [`set_tcp_repair_queue`] and [`set_tcp_queue_seq`]. This
+    /// is used to checkpoint and restore, or migrate, TCP connections.
+    ///
+    /// Setting this option requires the `CAP_NET_ADMIN` capability.
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_tcp_repair_queue`]: crate::Socket::set_tcp_repair_queue
+    /// [`set_tcp_queue_seq`]: crate::Socket::set_tcp_queue_seq
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn set_tcp_repair(&self, repair: bool) -> io::Result<()> {
+        unsafe {
+            setsockopt(
+                self.inner,
+                libc::IPPROTO_TCP,
+                libc::TCP_REPAIR,
+                repair as c_int,
+            )
+        }
+    }
+
+    /// Get the value of the `TCP_REPAIR_QUEUE` option on this socket.
+    ///
+    /// For more information about this option, see [`set_tcp_repair_queue`].
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_tcp_repair_queue`]: crate::Socket::set_tcp_repair_queue
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn tcp_repair_queue(&self) -> io::Result<TcpRepairQueue> {
+        unsafe {
+            getsockopt::<c_int>(self.inner, libc::IPPROTO_TCP, libc::TCP_REPAIR_QUEUE)
+                .map(TcpRepairQueue::from_raw)
+        }
+    }
+
+    /// Set the value of the `TCP_REPAIR_QUEUE` option on this socket.
+    ///
+    /// Selects the queue that is read by `recv` or written by `send`, and
+    /// whose sequence number is get or set by [`tcp_queue_seq`] and
+    /// [`set_tcp_queue_seq`], while the socket is in repair mode.
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`tcp_queue_seq`]: crate::Socket::tcp_queue_seq
+    /// [`set_tcp_queue_seq`]: crate::Socket::set_tcp_queue_seq
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn set_tcp_repair_queue(&self, queue: TcpRepairQueue) -> io::Result<()> {
+        unsafe {
+            setsockopt(
+                self.inner,
+                libc::IPPROTO_TCP,
+                libc::TCP_REPAIR_QUEUE,
+                queue as c_int,
+            )
+        }
+    }
+
+    /// Get the value of the `TCP_QUEUE_SEQ` option on this socket.
+    ///
+    /// Returns the sequence number of the queue selected using
+    /// [`set_tcp_repair_queue`].
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_tcp_repair_queue`]: crate::Socket::set_tcp_repair_queue
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn tcp_queue_seq(&self) -> io::Result<u32> {
+        unsafe { getsockopt::<u32>(self.inner, libc::IPPROTO_TCP, libc::TCP_QUEUE_SEQ) }
+    }
+
+    /// Set the value of the `TCP_QUEUE_SEQ` option on this socket.
+    ///
+    /// Sets the sequence number of the queue selected using
+    /// [`set_tcp_repair_queue`]. This can only be set on a socket in repair
+    /// mode that is not yet connected.
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_tcp_repair_queue`]: crate::Socket::set_tcp_repair_queue
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn set_tcp_queue_seq(&self, seq: u32) -> io::Result<()> {
+        unsafe { setsockopt(self.inner, libc::IPPROTO_TCP, libc::TCP_QUEUE_SEQ, seq) }
+    }
+
+    /// Receive data without copying it by mapping the pages of the receive
+    /// queue into `address`, using the `TCP_ZEROCOPY_RECEIVE` option.
+    ///
+    /// At most `length` bytes are mapped. Only whole pages can be mapped, the
+    /// returned [`TcpZeroCopyReceive::recv_skip_hint`] are the bytes that must
+    /// be read using a regular `recv` before the next pages can be mapped.
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// # Safety
+    ///
+    /// `address` must point to a region of at least `length` bytes, created by
+    /// calling `mmap(2)` on this socket with `PROT_READ` and `MAP_SHARED`.
+    /// `length` must be a multiple of the page size. The mapped data can be
+    /// released by calling `munmap(2)` or `madvise(2)` with `MADV_DONTNEED`
+    /// on the region.
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub unsafe fn tcp_zerocopy_receive(
+        &self,
+        address: *mut u8,
+        length: u32,
+    ) -> io::Result<TcpZeroCopyReceive> {
+        // Prefix of `struct tcp_zerocopy_receive` in `linux/tcp.h`, the kernel
+        // accepts older (shorter) versions of the structure.
+        #[repr(C)]
+        struct tcp_zerocopy_receive {
+            address: u64,
+            length: u32,
+            recv_skip_hint: u32,
+            inq: u32,
+            err: i32,
+        }
+
+        let mut zc = tcp_zerocopy_receive {
+            address: address as u64,
+            length,
+            recv_skip_hint: 0,
+            inq: 0,
+            err: 0,
+        };
+        let mut len = size_of::<tcp_zerocopy_receive>() as libc::socklen_t;
+        syscall!(getsockopt(
+            self.inner,
+            libc::IPPROTO_TCP,
+            libc::TCP_ZEROCOPY_RECEIVE,
+            (&mut zc as *mut tcp_zerocopy_receive).cast(),
+            &mut len,
+        ))?;
+        if zc.err != 0 {
+            return Err(io::Error::from_raw_os_error(zc.err));
+        }
+        Ok(TcpZeroCopyReceive {
+            length: zc.length,
+            recv_skip_hint: zc.recv_skip_hint,
+            inq: zc.inq,
+        })
+    }
+
     /// Returns `true` if `listen(2)` was called on this socket by checking the
     /// `SO_ACCEPTCONN` option on this socket.
     #[cfg(all(
@@ -1129,104 +2312,550 @@ impl crate::Socket {
         unsafe { setsockopt::<c_int>(self.inner, libc::SOL_SOCKET, libc::SO_MARK, mark as c_int) }
     }
 
-    /// Gets the value for the `SO_BINDTODEVICE` option on this socket.
+    /// Sets the value for the `SO_SETFIB` option on this socket.
     ///
-    /// This value gets the socket binded device's interface name.
+    /// This sets the routing table (FIB) used by this socket, allowing sockets
+    /// to use alternate routing tables on multi-FIB systems. `fib` must be
+    /// less than the `net.fibs` sysctl.
     ///
-    /// This function is only available on Fuchsia and Linux.
-    #[cfg(all(
-        feature = "all",
-        any(target_os = "android", target_os = "fuchsia", target_os = "linux")
-    ))]
-    pub fn device(&self) -> io::Result<Option<Vec<u8>>> {
-        // TODO: replace with `MaybeUninit::uninit_array` once stable.
-        let mut buf: [MaybeUninit<u8>; libc::IFNAMSIZ] =
-            unsafe { MaybeUninit::uninit().assume_init() };
-        let mut len = buf.len() as libc::socklen_t;
-        unsafe {
-            syscall!(getsockopt(
-                self.inner,
-                libc::SOL_SOCKET,
-                libc::SO_BINDTODEVICE,
-                buf.as_mut_ptr().cast(),
-                &mut len,
-            ))?;
+    /// This function is only available on FreeBSD.
+    #[cfg(all(feature = "all", target_os = "freebsd"))]
+    pub fn set_fib(&self, fib: u32) -> io::Result<()> {
+        unsafe { setsockopt::<c_int>(self.inner, libc::SOL_SOCKET, libc::SO_SETFIB, fib as c_int) }
+    }
+
+    /// Sets the value for the `SO_USER_COOKIE` option on this socket.
+    ///
+    /// This tags the socket with a cookie that can be matched on by `ipfw(8)`
+    /// rules, e.g. for classification using `dummynet(4)`. This is similar to
+    /// `SO_MARK` on Linux.
+    ///
+    /// This function is only available on FreeBSD.
+    #[cfg(all(feature = "all", target_os = "freebsd"))]
+    pub fn set_user_cookie(&self, cookie: u32) -> io::Result<()> {
+        unsafe { setsockopt::<u32>(self.inner, libc::SOL_SOCKET, libc::SO_USER_COOKIE, cookie) }
+    }
+
+    /// Sets the value for the `SO_ACCEPTFILTER` option on this socket.
+    ///
+    /// This installs the accept filter `name`, e.g. `"dataready"` or
+    /// `"httpready"`, with the (possibly empty) argument `arg` on a listening
+    /// socket, deferring `accept` until the filter's condition is met, e.g. a
+    /// complete HTTP request has been received. The filter's kernel module,
+    /// e.g. `accf_data(9)` or `accf_http(9)`, must be loaded.
+    ///
+    /// This must be called after [`listen`]. Returns an error of kind
+    /// [`io::ErrorKind::InvalidInput`] if `name` or `arg` are too long or
+    /// contain a null byte.
+    ///
+    /// This function is only available on FreeBSD.
+    ///
+    /// [`listen`]: crate::Socket::listen
+    #[cfg(all(feature = "all", target_os = "freebsd"))]
+    pub fn set_accept_filter(&self, name: &str, arg: &str) -> io::Result<()> {
+        // `struct accept_filter_arg` in `sys/socket.h`, `af_arg` is private in
+        // libc.
+        #[repr(C)]
+        struct accept_filter_arg {
+            af_name: [libc::c_char; 16],
+            af_arg: [libc::c_char; 256 - 16],
         }
-        if len == 0 {
-            Ok(None)
-        } else {
-            let buf = &buf[..len as usize - 1];
-            // TODO: use `MaybeUninit::slice_assume_init_ref` once stable.
-            Ok(Some(unsafe { &*(buf as *const [_] as *const [u8]) }.into()))
+
+        let mut filter = accept_filter_arg {
+            af_name: [0; 16],
+            af_arg: [0; 256 - 16],
+        };
+        // Both need at least one byte for the null terminator.
+        if name.len() >= filter.af_name.len()
+            || arg.len() >= filter.af_arg.len()
+            || name.as_bytes().contains(&0)
+            || arg.as_bytes().contains(&0)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid accept filter name or argument",
+            ));
         }
+        for (dst, src) in filter.af_name.iter_mut().zip(name.as_bytes()) {
+            *dst = *src as libc::c_char;
+        }
+        for (dst, src) in filter.af_arg.iter_mut().zip(arg.as_bytes()) {
+            *dst = *src as libc::c_char;
+        }
+        unsafe { setsockopt(self.inner, libc::SOL_SOCKET, libc::SO_ACCEPTFILTER, filter) }
     }
 
-    /// Sets the value for the `SO_BINDTODEVICE` option on this socket.
+    /// Binds this socket to the network `network`, using
+    /// `android_setsocknetwork(3)`.
     ///
-    /// If a socket is bound to an interface, only packets received from that
-    /// particular interface are processed by the socket. Note that this only
-    /// works for some socket types, particularly `AF_INET` sockets.
+    /// All traffic of the socket will be sent over the network, regardless of
+    /// the process' default network. `network` is the `net_handle_t` of the
+    /// network, as returned by `Network.getNetworkHandle()` in Java, zero
+    /// (`NETWORK_UNSPECIFIED`) unbinds the socket. This is the equivalent of
+    /// `Network.bindSocket()` in Java.
     ///
-    /// If `interface` is `None` or an empty string it removes the binding.
+    /// `android_setsocknetwork` is provided by `libandroid.so` since API level
+    /// 23, which is loaded at runtime. If it's not available an error with
+    /// `ENOSYS` is returned.
     ///
-    /// This function is only available on Fuchsia and Linux.
-    #[cfg(all(
-        feature = "all",
-        any(target_os = "android", target_os = "fuchsia", target_os = "linux")
-    ))]
-    pub fn bind_device(&self, interface: Option<&[u8]>) -> io::Result<()> {
-        let (value, len) = if let Some(interface) = interface {
-            (interface.as_ptr(), interface.len())
+    /// This function is only available on Android.
+    #[cfg(all(feature = "all", target_os = "android"))]
+    pub fn bind_to_network(&self, network: u64) -> io::Result<()> {
+        type SetSockNetwork = unsafe extern "C" fn(network: u64, fd: c_int) -> c_int;
+
+        // Safety: the library name is null terminated.
+        let lib = unsafe { libc::dlopen(b"libandroid.so\0".as_ptr().cast(), libc::RTLD_NOW) };
+        if lib.is_null() {
+            return Err(io::Error::from_raw_os_error(libc::ENOSYS));
+        }
+        // Safety: the symbol name is null terminated.
+        let sym = unsafe { libc::dlsym(lib, b"android_setsocknetwork\0".as_ptr().cast()) };
+        let res = if sym.is_null() {
+            Err(io::Error::from_raw_os_error(libc::ENOSYS))
         } else {
-            (ptr::null(), 0)
+            // Safety: `android_setsocknetwork` has the signature of
+            // `SetSockNetwork`.
+            let set_sock_network: SetSockNetwork = unsafe { mem::transmute(sym) };
+            match unsafe { set_sock_network(network, self.inner) } {
+                -1 => Err(io::Error::last_os_error()),
+                _ => Ok(()),
+            }
         };
-        syscall!(setsockopt(
-            self.inner,
-            libc::SOL_SOCKET,
-            libc::SO_BINDTODEVICE,
-            value.cast(),
-            len as libc::socklen_t,
-        ))
-        .map(|_| ())
+        unsafe { libc::dlclose(lib) };
+        res
     }
 
-    /// Get the value of the `SO_INCOMING_CPU` option on this socket.
+    /// Join the netlink multicast group `group` using the
+    /// `NETLINK_ADD_MEMBERSHIP` option on this socket.
     ///
-    /// For more information about this option, see [`set_cpu_affinity`].
-    ///
-    /// This function is only available on Linux.
+    /// Unlike the groups in the address passed to `bind` this supports groups
+    /// above 32.
     ///
-    /// [`set_cpu_affinity`]: crate::Socket::set_cpu_affinity
-    #[cfg(all(feature = "all", any(target_os = "linux")))]
-    pub fn cpu_affinity(&self) -> io::Result<usize> {
+    /// This function is only available on Android and Linux.
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn add_netlink_membership(&self, group: u32) -> io::Result<()> {
         unsafe {
-            getsockopt::<c_int>(self.inner, libc::SOL_SOCKET, libc::SO_INCOMING_CPU)
-                .map(|cpu| cpu as usize)
+            setsockopt(
+                self.inner,
+                libc::SOL_NETLINK,
+                libc::NETLINK_ADD_MEMBERSHIP,
+                group,
+            )
         }
     }
 
-    /// Set value for the `SO_INCOMING_CPU` option on this socket.
+    /// Leave the netlink multicast group `group` using the
+    /// `NETLINK_DROP_MEMBERSHIP` option on this socket.
     ///
-    /// Sets the CPU affinity of the socket.
+    /// For more information about this option, see
+    /// [`add_netlink_membership`].
     ///
-    /// This function is only available on Linux.
-    #[cfg(all(feature = "all", any(target_os = "linux")))]
-    pub fn set_cpu_affinity(&self, cpu: usize) -> io::Result<()> {
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`add_netlink_membership`]: crate::Socket::add_netlink_membership
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn drop_netlink_membership(&self, group: u32) -> io::Result<()> {
         unsafe {
             setsockopt(
                 self.inner,
-                libc::SOL_SOCKET,
-                libc::SO_INCOMING_CPU,
-                cpu as c_int,
+                libc::SOL_NETLINK,
+                libc::NETLINK_DROP_MEMBERSHIP,
+                group,
             )
         }
     }
 
-    /// Get the value of the `SO_REUSEPORT` option on this socket.
-    ///
-    /// For more information about this option, see [`set_reuse_port`].
+    /// Send the netlink message `msg` to the kernel.
     ///
-    /// This function is only available on Unix.
+    /// This function is only available on Android and Linux.
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn send_netlink(&self, msg: &NetlinkMessage<'_>) -> io::Result<()> {
+        self.send_to(&msg.encode(), &SockAddr::netlink(0, 0))
+            .map(|_| ())
+    }
+
+    /// Receive a single datagram, which may contain multiple netlink messages.
+    ///
+    /// `buf` is cleared and grown to fit the entire datagram, so the messages
+    /// are never truncated. Returns an iterator over the messages in `buf`.
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn recv_netlink<'a>(&self, buf: &'a mut Vec<u8>) -> io::Result<NetlinkMessages<'a>> {
+        // With `MSG_TRUNC` the full size of the datagram is returned.
+        let size = self.recv_with_flags(&mut [], libc::MSG_PEEK | libc::MSG_TRUNC)?;
+        buf.clear();
+        buf.reserve(size);
+        let n = self.recv(&mut buf.spare_capacity_mut()[..size])?;
+        // Safety: `recv` initialised `n` bytes.
+        unsafe { buf.set_len(n) };
+        Ok(NetlinkMessages { buf: &buf[..] })
+    }
+
+    /// Receive a, possibly multipart, netlink response, calling `f` for each
+    /// message.
+    ///
+    /// This keeps receiving datagrams until the end of a multipart response
+    /// (`NLMSG_DONE`), or until a datagram without multipart messages is
+    /// received. An error message (`NLMSG_ERROR`) is returned as error, an
+    /// acknowledgement ends the response.
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn recv_netlink_multipart<F>(&self, buf: &mut Vec<u8>, mut f: F) -> io::Result<()>
+    where
+        F: FnMut(NetlinkMessage<'_>) -> io::Result<()>,
+    {
+        loop {
+            let mut multipart = false;
+            for msg in self.recv_netlink(buf)? {
+                if msg.is_done() {
+                    return Ok(());
+                } else if msg.msg_type() == libc::NLMSG_ERROR as u16 {
+                    return match msg.error() {
+                        Some(err) => Err(err),
+                        None => Ok(()),
+                    };
+                }
+                multipart = msg.is_multipart();
+                f(msg)?;
+            }
+            if !multipart {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Enable or disable promiscuous mode on the interface with index
+    /// `ifindex` using the `PACKET_ADD_MEMBERSHIP`/`PACKET_DROP_MEMBERSHIP`
+    /// option with `PACKET_MR_PROMISC` on this packet socket.
+    ///
+    /// Unlike setting `IFF_PROMISC` on the interface, the kernel keeps a count
+    /// of memberships and drops the membership when the socket is closed.
+    ///
+    /// This function is only available on Linux.
+    #[cfg(all(feature = "all", target_os = "linux"))]
+    pub fn set_promiscuous(&self, ifindex: u32, promiscuous: bool) -> io::Result<()> {
+        packet_membership(
+            self.inner,
+            ifindex,
+            libc::PACKET_MR_PROMISC,
+            &[],
+            promiscuous,
+        )
+    }
+
+    /// Enable or disable receiving all multicast packets on the interface with
+    /// index `ifindex` using `PACKET_MR_ALLMULTI` on this packet socket.
+    ///
+    /// For more information, see [`set_promiscuous`].
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`set_promiscuous`]: crate::Socket::set_promiscuous
+    #[cfg(all(feature = "all", target_os = "linux"))]
+    pub fn set_all_multicast(&self, ifindex: u32, all_multicast: bool) -> io::Result<()> {
+        packet_membership(
+            self.inner,
+            ifindex,
+            libc::PACKET_MR_ALLMULTI,
+            &[],
+            all_multicast,
+        )
+    }
+
+    /// Join the link layer multicast group with hardware `address` (at most 8
+    /// bytes) on the interface with index `ifindex` using
+    /// `PACKET_MR_MULTICAST` on this packet socket.
+    ///
+    /// For more information, see [`set_promiscuous`].
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`set_promiscuous`]: crate::Socket::set_promiscuous
+    #[cfg(all(feature = "all", target_os = "linux"))]
+    pub fn join_packet_multicast(&self, ifindex: u32, address: &[u8]) -> io::Result<()> {
+        packet_membership(
+            self.inner,
+            ifindex,
+            libc::PACKET_MR_MULTICAST,
+            address,
+            true,
+        )
+    }
+
+    /// Leave a link layer multicast group joined with
+    /// [`join_packet_multicast`].
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`join_packet_multicast`]: crate::Socket::join_packet_multicast
+    #[cfg(all(feature = "all", target_os = "linux"))]
+    pub fn leave_packet_multicast(&self, ifindex: u32, address: &[u8]) -> io::Result<()> {
+        packet_membership(
+            self.inner,
+            ifindex,
+            libc::PACKET_MR_MULTICAST,
+            address,
+            false,
+        )
+    }
+
+    /// Sets the value of the `TCP_ULP` option on this socket.
+    ///
+    /// This attaches the upper layer protocol `name` to the TCP socket, e.g.
+    /// `"tls"` to enable kernel TLS, see [`set_tls_tx`]. The socket must be
+    /// connected and the protocol's kernel module loaded.
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`set_tls_tx`]: crate::Socket::set_tls_tx
+    #[cfg(all(feature = "all", target_os = "linux"))]
+    pub fn set_tcp_ulp(&self, name: &str) -> io::Result<()> {
+        syscall!(setsockopt(
+            self.inner,
+            libc::SOL_TCP,
+            libc::TCP_ULP,
+            name.as_ptr().cast(),
+            name.len() as libc::socklen_t,
+        ))
+        .map(|_| ())
+    }
+
+    /// Sets the value of the `TLS_TX` option on this socket.
+    ///
+    /// This enables kernel TLS (kTLS) for sending, after which the kernel
+    /// encrypts the data written to the socket into TLS records. This makes it
+    /// possible to use `sendfile(2)` on a TLS connection. The TLS handshake
+    /// must be done in user space and the `"tls"` upper layer protocol must be
+    /// set first using [`set_tcp_ulp`].
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`set_tcp_ulp`]: crate::Socket::set_tcp_ulp
+    #[cfg(all(feature = "all", target_os = "linux"))]
+    pub fn set_tls_tx(&self, info: &TlsCryptoInfo) -> io::Result<()> {
+        set_tls_crypto_info(self.inner, libc::TLS_TX, info)
+    }
+
+    /// Sets the value of the `TLS_RX` option on this socket.
+    ///
+    /// This enables kernel TLS (kTLS) for receiving, after which the kernel
+    /// decrypts the received TLS records. For more information, see
+    /// [`set_tls_tx`].
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`set_tls_tx`]: crate::Socket::set_tls_tx
+    #[cfg(all(feature = "all", target_os = "linux"))]
+    pub fn set_tls_rx(&self, info: &TlsCryptoInfo) -> io::Result<()> {
+        set_tls_crypto_info(self.inner, libc::TLS_RX, info)
+    }
+
+    /// Add a TCP Authentication Option (TCP-AO, RFC 5925) key using the
+    /// `TCP_AO_ADD_KEY` option on this socket.
+    ///
+    /// TCP-AO is the replacement for TCP MD5 signatures (RFC 2385), mostly
+    /// used to protect BGP sessions. Keys must be added before connecting or,
+    /// for listeners, before accepting connections from the peer. The first
+    /// key added becomes the current key.
+    ///
+    /// This function is only available on Linux 6.7 and later.
+    #[cfg(all(feature = "all", target_os = "linux"))]
+    pub fn add_tcp_ao_key(&self, key: &TcpAoKey<'_>) -> io::Result<()> {
+        add_tcp_ao_key(self.inner, key)
+    }
+
+    /// Delete a TCP-AO key using the `TCP_AO_DEL_KEY` option on this socket.
+    ///
+    /// The key is identified by its peer address, prefix length, send and
+    /// receive id, the algorithm and key itself are ignored. For more
+    /// information, see [`add_tcp_ao_key`].
+    ///
+    /// This function is only available on Linux 6.7 and later.
+    ///
+    /// [`add_tcp_ao_key`]: crate::Socket::add_tcp_ao_key
+    #[cfg(all(feature = "all", target_os = "linux"))]
+    pub fn delete_tcp_ao_key(&self, key: &TcpAoKey<'_>) -> io::Result<()> {
+        delete_tcp_ao_key(self.inner, key)
+    }
+
+    /// Get the value of the `SO_RDS_TRANSPORT` option on this socket.
+    ///
+    /// Returns `None` if no transport was explicitly selected, in which case
+    /// the kernel picks one when the socket is bound.
+    ///
+    /// For more information about this option, see [`set_rds_transport`].
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_rds_transport`]: crate::Socket::set_rds_transport
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn rds_transport(&self) -> io::Result<Option<RdsTransport>> {
+        unsafe {
+            getsockopt::<c_int>(self.inner, SOL_RDS, SO_RDS_TRANSPORT).map(RdsTransport::from_raw)
+        }
+    }
+
+    /// Set the value of the `SO_RDS_TRANSPORT` option on this socket.
+    ///
+    /// Selects the transport used by an `AF_RDS` socket. This must be set
+    /// before the socket is bound, and can only be set once.
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn set_rds_transport(&self, transport: RdsTransport) -> io::Result<()> {
+        unsafe { setsockopt(self.inner, SOL_RDS, SO_RDS_TRANSPORT, transport as c_int) }
+    }
+
+    /// Set the value of the `RDS_RECVERR` option on this socket.
+    ///
+    /// If enabled the kernel queues a notification for every message that
+    /// could not be delivered, which is returned by a subsequent receive.
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn set_rds_recverr(&self, recverr: bool) -> io::Result<()> {
+        unsafe { setsockopt(self.inner, SOL_RDS, RDS_RECVERR, recverr as c_int) }
+    }
+
+    /// Set the value of the `RDS_CONG_MONITOR` option on this socket.
+    ///
+    /// If enabled the kernel notifies the socket when a congested destination
+    /// port becomes uncongested again.
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn set_rds_cong_monitor(&self, monitor: bool) -> io::Result<()> {
+        unsafe { setsockopt(self.inner, SOL_RDS, RDS_CONG_MONITOR, monitor as c_int) }
+    }
+
+    /// Get the type-of-service of this `AF_RDS` socket, using the
+    /// `SIOCRDSGETTOS` ioctl.
+    ///
+    /// For more information about this option, see [`set_rds_tos`].
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_rds_tos`]: crate::Socket::set_rds_tos
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn rds_tos(&self) -> io::Result<u8> {
+        let mut tos: u8 = 0;
+        syscall!(ioctl(self.inner, SIOCRDSGETTOS as _, &mut tos)).map(|_| tos)
+    }
+
+    /// Set the type-of-service of this `AF_RDS` socket, using the
+    /// `SIOCRDSSETTOS` ioctl.
+    ///
+    /// The transport must be selected first, see [`set_rds_transport`], and
+    /// the type-of-service can only be set once, before the socket is used to
+    /// send or connect.
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_rds_transport`]: crate::Socket::set_rds_transport
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn set_rds_tos(&self, tos: u8) -> io::Result<()> {
+        let mut tos = tos;
+        syscall!(ioctl(self.inner, SIOCRDSSETTOS as _, &mut tos)).map(|_| ())
+    }
+
+    /// Gets the value for the `SO_BINDTODEVICE` option on this socket.
+    ///
+    /// This value gets the socket binded device's interface name.
+    ///
+    /// This function is only available on Fuchsia and Linux.
+    #[cfg(all(
+        feature = "all",
+        any(target_os = "android", target_os = "fuchsia", target_os = "linux")
+    ))]
+    pub fn device(&self) -> io::Result<Option<Vec<u8>>> {
+        // TODO: replace with `MaybeUninit::uninit_array` once stable.
+        let mut buf: [MaybeUninit<u8>; libc::IFNAMSIZ] =
+            unsafe { MaybeUninit::uninit().assume_init() };
+        let mut len = buf.len() as libc::socklen_t;
+        syscall!(getsockopt(
+            self.inner,
+            libc::SOL_SOCKET,
+            libc::SO_BINDTODEVICE,
+            buf.as_mut_ptr().cast(),
+            &mut len,
+        ))?;
+        if len == 0 {
+            Ok(None)
+        } else {
+            let buf = &buf[..len as usize - 1];
+            // TODO: use `MaybeUninit::slice_assume_init_ref` once stable.
+            Ok(Some(unsafe { &*(buf as *const [_] as *const [u8]) }.into()))
+        }
+    }
+
+    /// Sets the value for the `SO_BINDTODEVICE` option on this socket.
+    ///
+    /// If a socket is bound to an interface, only packets received from that
+    /// particular interface are processed by the socket. Note that this only
+    /// works for some socket types, particularly `AF_INET` sockets.
+    ///
+    /// If `interface` is `None` or an empty string it removes the binding.
+    ///
+    /// This function is only available on Fuchsia and Linux.
+    #[cfg(all(
+        feature = "all",
+        any(target_os = "android", target_os = "fuchsia", target_os = "linux")
+    ))]
+    pub fn bind_device(&self, interface: Option<&[u8]>) -> io::Result<()> {
+        let (value, len) = if let Some(interface) = interface {
+            (interface.as_ptr(), interface.len())
+        } else {
+            (ptr::null(), 0)
+        };
+        syscall!(setsockopt(
+            self.inner,
+            libc::SOL_SOCKET,
+            libc::SO_BINDTODEVICE,
+            value.cast(),
+            len as libc::socklen_t,
+        ))
+        .map(|_| ())
+    }
+
+    /// Get the value of the `SO_INCOMING_CPU` option on this socket.
+    ///
+    /// For more information about this option, see [`set_cpu_affinity`].
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`set_cpu_affinity`]: crate::Socket::set_cpu_affinity
+    #[cfg(all(feature = "all", target_os = "linux"))]
+    pub fn cpu_affinity(&self) -> io::Result<usize> {
+        unsafe {
+            getsockopt::<c_int>(self.inner, libc::SOL_SOCKET, libc::SO_INCOMING_CPU)
+                .map(|cpu| cpu as usize)
+        }
+    }
+
+    /// Set value for the `SO_INCOMING_CPU` option on this socket.
+    ///
+    /// Sets the CPU affinity of the socket.
+    ///
+    /// This function is only available on Linux.
+    #[cfg(all(feature = "all", target_os = "linux"))]
+    pub fn set_cpu_affinity(&self, cpu: usize) -> io::Result<()> {
+        unsafe {
+            setsockopt(
+                self.inner,
+                libc::SOL_SOCKET,
+                libc::SO_INCOMING_CPU,
+                cpu as c_int,
+            )
+        }
+    }
+
+    /// Get the value of the `SO_REUSEPORT` option on this socket.
+    ///
+    /// For more information about this option, see [`set_reuse_port`].
+    ///
+    /// This function is only available on Unix.
     ///
     /// [`set_reuse_port`]: crate::Socket::set_reuse_port
     #[cfg(all(
@@ -1293,17 +2922,784 @@ impl crate::Socket {
         feature = "all",
         any(target_os = "android", target_os = "fuchsia", target_os = "linux")
     ))]
-    pub fn set_freebind(&self, reuse: bool) -> io::Result<()> {
+    pub fn set_freebind(&self, reuse: bool) -> io::Result<()> {
+        unsafe {
+            setsockopt(
+                self.inner,
+                libc::SOL_SOCKET,
+                libc::IP_FREEBIND,
+                reuse as c_int,
+            )
+        }
+    }
+
+    /// Get the value of the `IP_BIND_ADDRESS_NO_PORT` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_bind_address_no_port`].
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_bind_address_no_port`]: crate::Socket::set_bind_address_no_port
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn bind_address_no_port(&self) -> io::Result<bool> {
+        unsafe {
+            getsockopt::<c_int>(self.inner, libc::IPPROTO_IP, libc::IP_BIND_ADDRESS_NO_PORT)
+                .map(|no_port| no_port != 0)
+        }
+    }
+
+    /// Set value for the `IP_BIND_ADDRESS_NO_PORT` option on this socket.
+    ///
+    /// If enabled, binding to an address with port 0 doesn't reserve an
+    /// ephemeral port yet, the port is only chosen when the socket is
+    /// connected. This allows binding to a specific source address before
+    /// connecting without using up an ephemeral port for every socket, as the
+    /// same port can then be shared by connections to different destinations.
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn set_bind_address_no_port(&self, no_port: bool) -> io::Result<()> {
+        unsafe {
+            setsockopt(
+                self.inner,
+                libc::IPPROTO_IP,
+                libc::IP_BIND_ADDRESS_NO_PORT,
+                no_port as c_int,
+            )
+        }
+    }
+
+    /// Set value for the `SO_RCVBUFFORCE` option on this socket.
+    ///
+    /// Same as [`set_recv_buffer_size`], but a process with the
+    /// `CAP_NET_ADMIN` capability can exceed the `rmem_max` limit.
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_recv_buffer_size`]: crate::Socket::set_recv_buffer_size
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn set_recv_buffer_size_force(&self, size: usize) -> io::Result<()> {
+        let size = crate::socket::buffer_size(size)?;
+        unsafe { setsockopt(self.inner, libc::SOL_SOCKET, libc::SO_RCVBUFFORCE, size) }
+    }
+
+    /// Set value for the `SO_SNDBUFFORCE` option on this socket.
+    ///
+    /// Same as [`set_send_buffer_size`], but a process with the
+    /// `CAP_NET_ADMIN` capability can exceed the `wmem_max` limit.
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_send_buffer_size`]: crate::Socket::set_send_buffer_size
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn set_send_buffer_size_force(&self, size: usize) -> io::Result<()> {
+        let size = crate::socket::buffer_size(size)?;
+        unsafe { setsockopt(self.inner, libc::SOL_SOCKET, libc::SO_SNDBUFFORCE, size) }
+    }
+
+    /// Get the value of the `SO_PASSCRED` option on this socket.
+    ///
+    /// For more information about this option, see [`set_passcred`].
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_passcred`]: crate::Socket::set_passcred
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn passcred(&self) -> io::Result<bool> {
+        unsafe {
+            getsockopt::<c_int>(self.inner, libc::SOL_SOCKET, libc::SO_PASSCRED)
+                .map(|passcred| passcred != 0)
+        }
+    }
+
+    /// Set value for the `SO_PASSCRED` option on this socket.
+    ///
+    /// If enabled, this enables the receiving of `SCM_CREDENTIALS` control
+    /// messages on Unix sockets, containing the process, user and group id of
+    /// the sending process.
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn set_passcred(&self, passcred: bool) -> io::Result<()> {
+        unsafe {
+            setsockopt(
+                self.inner,
+                libc::SOL_SOCKET,
+                libc::SO_PASSCRED,
+                passcred as c_int,
+            )
+        }
+    }
+
+    /// Get the value of the `SO_PEERSEC` option on this socket.
+    ///
+    /// This returns the security context (e.g. the SELinux or SMACK label) of
+    /// the peer of a connected Unix socket, without the trailing nul byte.
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn peer_security_context(&self) -> io::Result<Vec<u8>> {
+        let mut buf: Vec<u8> = Vec::with_capacity(256);
+        loop {
+            let mut len = buf.capacity() as libc::socklen_t;
+            let res = syscall!(getsockopt(
+                self.inner,
+                libc::SOL_SOCKET,
+                libc::SO_PEERSEC,
+                buf.as_mut_ptr().cast(),
+                &mut len,
+            ));
+            match res {
+                Ok(_) => {
+                    // Safety: the kernel initialised `len` bytes.
+                    unsafe { buf.set_len(len as usize) };
+                    if buf.last() == Some(&0) {
+                        let _ = buf.pop();
+                    }
+                    return Ok(buf);
+                }
+                // The buffer was too small, `len` is set to the required size.
+                Err(ref err) if err.raw_os_error() == Some(libc::ERANGE) => {
+                    buf.reserve(len as usize);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Get the value of the `SO_COOKIE` option on this socket.
+    ///
+    /// The socket cookie is a unique, kernel-managed identifier tied to the
+    /// socket, it stays the same for the lifetime of the socket. It can be used
+    /// to correlate the socket with eBPF maps and `sock_diag` output.
+    ///
+    /// This function is only available on Linux.
+    #[cfg(all(feature = "all", target_os = "linux"))]
+    pub fn cookie(&self) -> io::Result<u64> {
+        unsafe { getsockopt::<u64>(self.inner, libc::SOL_SOCKET, libc::SO_COOKIE) }
+    }
+
+    /// Get the value of the `SO_INCOMING_NAPI_ID` option on this socket.
+    ///
+    /// This returns the id of the NAPI context (i.e. the receive queue of the
+    /// network device) that last received a packet for this socket, or 0 if no
+    /// packet was received yet.
+    ///
+    /// This function is only available on Linux.
+    #[cfg(all(feature = "all", target_os = "linux"))]
+    pub fn incoming_napi_id(&self) -> io::Result<u32> {
+        unsafe {
+            getsockopt::<c_int>(self.inner, libc::SOL_SOCKET, libc::SO_INCOMING_NAPI_ID)
+                .map(|id| id as u32)
+        }
+    }
+
+    /// Get the value of the `SO_MAX_PACING_RATE` option on this socket.
+    ///
+    /// For more information about this option, see [`set_max_pacing_rate`].
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`set_max_pacing_rate`]: crate::Socket::set_max_pacing_rate
+    #[cfg(all(feature = "all", target_os = "linux"))]
+    #[allow(clippy::useless_conversion)] // `c_ulong` is `u64` on 64 bit platforms.
+    pub fn max_pacing_rate(&self) -> io::Result<u64> {
+        unsafe {
+            getsockopt::<libc::c_ulong>(self.inner, libc::SOL_SOCKET, libc::SO_MAX_PACING_RATE)
+                .map(u64::from)
+        }
+    }
+
+    /// Set value for the `SO_MAX_PACING_RATE` option on this socket.
+    ///
+    /// This caps the rate, in bytes per second, at which the transport layer
+    /// (or the `fq` packet scheduler) sends packets on this socket. Use
+    /// `u64::MAX` to remove the limit.
+    ///
+    /// This function is only available on Linux.
+    #[cfg(all(feature = "all", target_os = "linux"))]
+    #[allow(clippy::useless_conversion)] // `c_ulong` is `u64` on 64 bit platforms.
+    pub fn set_max_pacing_rate(&self, rate: u64) -> io::Result<()> {
+        // On 32 bit platforms `~0UL` means unlimited.
+        let rate = std::convert::TryFrom::try_from(rate).unwrap_or(libc::c_ulong::MAX);
+        unsafe { setsockopt(self.inner, libc::SOL_SOCKET, libc::SO_MAX_PACING_RATE, rate) }
+    }
+
+    /// Get the value of the `SO_ORIGINAL_DST` option on this socket.
+    ///
+    /// This returns the original destination address of a connection that
+    /// was redirected using iptables' `REDIRECT` or `TPROXY` targets, for use
+    /// in transparent proxies. Only works for IPv4 sockets, see
+    /// [`original_dst_ipv6`] for IPv6.
+    ///
+    /// This function is only available on Android, Fuchsia and Linux.
+    ///
+    /// [`original_dst_ipv6`]: crate::Socket::original_dst_ipv6
+    #[cfg(all(
+        feature = "all",
+        any(target_os = "android", target_os = "fuchsia", target_os = "linux")
+    ))]
+    pub fn original_dst(&self) -> io::Result<SockAddr> {
+        // Safety: `getsockopt` initialises the `SockAddr` for us.
+        unsafe {
+            SockAddr::init(|storage, len| {
+                syscall!(getsockopt(
+                    self.inner,
+                    libc::SOL_IP,
+                    libc::SO_ORIGINAL_DST,
+                    storage.cast(),
+                    len
+                ))
+            })
+        }
+        .map(|(_, addr)| addr)
+    }
+
+    /// Get the value of the `IP6T_SO_ORIGINAL_DST` option on this socket.
+    ///
+    /// This is the IPv6 version of [`original_dst`], which uses the
+    /// `SOL_IPV6` level rather than `SOL_IP`. Only works for IPv6 sockets.
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`original_dst`]: crate::Socket::original_dst
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn original_dst_ipv6(&self) -> io::Result<SockAddr> {
+        // Safety: `getsockopt` initialises the `SockAddr` for us.
+        unsafe {
+            SockAddr::init(|storage, len| {
+                syscall!(getsockopt(
+                    self.inner,
+                    libc::SOL_IPV6,
+                    libc::IP6T_SO_ORIGINAL_DST,
+                    storage.cast(),
+                    len
+                ))
+            })
+        }
+        .map(|(_, addr)| addr)
+    }
+
+    /// Receives a message, returning the full length of the message even if it
+    /// is larger than `buf`.
+    ///
+    /// This sets the `MSG_TRUNC` flag on the `recvmsg(2)` call, which makes the
+    /// kernel return the real length of the datagram (or packet) rather than
+    /// the number of bytes written to `buf`. If the returned length is larger
+    /// than `buf.len()` the message was truncated, only `buf.len()` bytes were
+    /// written and [`RecvFlags::is_truncated`] returns `true`. This allows the
+    /// caller to grow the buffer for future messages.
+    ///
+    /// This function is only available on Android, Fuchsia and Linux.
+    ///
+    /// [`RecvFlags::is_truncated`]: crate::RecvFlags::is_truncated
+    #[cfg(all(
+        feature = "all",
+        any(target_os = "android", target_os = "fuchsia", target_os = "linux")
+    ))]
+    pub fn recv_trunc(&self, buf: &mut [MaybeUninit<u8>]) -> io::Result<(usize, RecvFlags)> {
+        let mut bufs = [crate::MaybeUninitSlice::new(buf)];
+        recv_vectored(self.inner, &mut bufs, libc::MSG_TRUNC)
+    }
+
+    /// Get the value of the `IP_RECVERR` option on this socket.
+    ///
+    /// For more information about this option, see [`set_recverr`].
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_recverr`]: crate::Socket::set_recverr
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn recverr(&self) -> io::Result<bool> {
+        unsafe {
+            getsockopt::<c_int>(self.inner, libc::IPPROTO_IP, libc::IP_RECVERR)
+                .map(|recverr| recverr != 0)
+        }
+    }
+
+    /// Set the value of the `IP_RECVERR` option on this socket.
+    ///
+    /// If enabled, extended error information, such as ICMP errors, is queued
+    /// on the error queue of the socket. The errors can be read using
+    /// [`recv_error`].
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`recv_error`]: crate::Socket::recv_error
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn set_recverr(&self, recverr: bool) -> io::Result<()> {
+        unsafe {
+            setsockopt(
+                self.inner,
+                libc::IPPROTO_IP,
+                libc::IP_RECVERR,
+                recverr as c_int,
+            )
+        }
+    }
+
+    /// Get the value of the `IPV6_RECVERR` option on this socket.
+    ///
+    /// For more information about this option, see [`set_recverr_v6`].
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_recverr_v6`]: crate::Socket::set_recverr_v6
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn recverr_v6(&self) -> io::Result<bool> {
+        unsafe {
+            getsockopt::<c_int>(self.inner, libc::IPPROTO_IPV6, libc::IPV6_RECVERR)
+                .map(|recverr| recverr != 0)
+        }
+    }
+
+    /// Set the value of the `IPV6_RECVERR` option on this socket.
+    ///
+    /// This is the IPv6 version of [`set_recverr`].
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_recverr`]: crate::Socket::set_recverr
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn set_recverr_v6(&self, recverr: bool) -> io::Result<()> {
+        unsafe {
+            setsockopt(
+                self.inner,
+                libc::IPPROTO_IPV6,
+                libc::IPV6_RECVERR,
+                recverr as c_int,
+            )
+        }
+    }
+
+    /// Receives a message from the error queue of the socket by setting the
+    /// `MSG_ERRQUEUE` flag.
+    ///
+    /// The original payload of the message that caused the error is written to
+    /// `buf`, returning the number of bytes written. The extended error, if any,
+    /// is decoded from the `IP_RECVERR` or `IPV6_RECVERR` control message.
+    ///
+    /// Errors are only queued if [`set_recverr`] or [`set_recverr_v6`] is
+    /// enabled, or for zerocopy completions. Reading from the error queue never
+    /// blocks, if the queue is empty an error of kind
+    /// [`io::ErrorKind::WouldBlock`] is returned.
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_recverr`]: crate::Socket::set_recverr
+    /// [`set_recverr_v6`]: crate::Socket::set_recverr_v6
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn recv_error(
+        &self,
+        buf: &mut [MaybeUninit<u8>],
+    ) -> io::Result<(usize, Option<SockExtendedErr>)> {
+        let mut bufs = [crate::MaybeUninitSlice::new(buf)];
+        // Enough space for `sock_extended_err` followed by an IPv6 address, `u64`
+        // for alignment.
+        let mut control = [0u64; 16];
+        // libc::msghdr contains unexported padding fields on Fuchsia.
+        let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+        msg.msg_iov = bufs.as_mut_ptr().cast();
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr().cast();
+        msg.msg_controllen = mem::size_of_val(&control) as _;
+        let n = syscall!(recvmsg(self.inner, &mut msg, libc::MSG_ERRQUEUE))? as usize;
+
+        let mut err = None;
+        // Safety: `recvmsg` initialised the control messages.
+        let mut cmsg = unsafe { libc::CMSG_FIRSTHDR(&msg) };
+        while !cmsg.is_null() {
+            let hdr = unsafe { &*cmsg };
+            if (hdr.cmsg_level == libc::IPPROTO_IP && hdr.cmsg_type == libc::IP_RECVERR)
+                || (hdr.cmsg_level == libc::IPPROTO_IPV6 && hdr.cmsg_type == libc::IPV6_RECVERR)
+            {
+                let data_len = hdr.cmsg_len as usize - unsafe { libc::CMSG_LEN(0) } as usize;
+                err = unsafe { SockExtendedErr::from_raw(libc::CMSG_DATA(cmsg), data_len) };
+            }
+            cmsg = unsafe { libc::CMSG_NXTHDR(&msg, cmsg) };
+        }
+        Ok((n, err))
+    }
+
+    /// Get the value of the `IP_OPTIONS` option on this socket.
+    ///
+    /// For more information about this option, see [`set_ip_options`].
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_ip_options`]: crate::Socket::set_ip_options
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn ip_options(&self) -> io::Result<Vec<u8>> {
+        // IPv4 options are at most 40 bytes.
+        let mut buf = [0u8; 40];
+        let mut len = buf.len() as libc::socklen_t;
+        syscall!(getsockopt(
+            self.inner,
+            libc::IPPROTO_IP,
+            libc::IP_OPTIONS,
+            buf.as_mut_ptr().cast(),
+            &mut len,
+        ))?;
+        Ok(buf[..len as usize].to_vec())
+    }
+
+    /// Set the value of the `IP_OPTIONS` option on this socket.
+    ///
+    /// Sets the raw IPv4 options, such as source routing or router alert, to
+    /// send with every packet on this socket. `options` must be correctly
+    /// formatted as described in RFC 791, with a maximum length of 40 bytes.
+    /// An empty slice removes all options.
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn set_ip_options(&self, options: &[u8]) -> io::Result<()> {
+        syscall!(setsockopt(
+            self.inner,
+            libc::IPPROTO_IP,
+            libc::IP_OPTIONS,
+            options.as_ptr().cast(),
+            options.len() as libc::socklen_t,
+        ))
+        .map(|_| ())
+    }
+
+    /// Get the value of the `ICMP6_FILTER` option on this socket.
+    ///
+    /// For more information about this option, see [`set_icmpv6_filter`].
+    ///
+    /// This function is only available on Android, FreeBSD, iOS, Linux and
+    /// macOS.
+    ///
+    /// [`set_icmpv6_filter`]: crate::Socket::set_icmpv6_filter
+    #[cfg(all(
+        feature = "all",
+        any(
+            target_os = "android",
+            target_os = "freebsd",
+            target_os = "linux",
+            target_vendor = "apple",
+        )
+    ))]
+    pub fn icmpv6_filter(&self) -> io::Result<Icmpv6Filter> {
+        unsafe { getsockopt(self.inner, libc::IPPROTO_ICMPV6, ICMP6_FILTER) }
+    }
+
+    /// Set the value of the `ICMP6_FILTER` option on this socket.
+    ///
+    /// This sets which ICMPv6 message types are passed to a raw ICMPv6 socket,
+    /// allowing the kernel to filter out messages the application isn't
+    /// interested in.
+    ///
+    /// This function is only available on Android, FreeBSD, iOS, Linux and
+    /// macOS.
+    #[cfg(all(
+        feature = "all",
+        any(
+            target_os = "android",
+            target_os = "freebsd",
+            target_os = "linux",
+            target_vendor = "apple",
+        )
+    ))]
+    pub fn set_icmpv6_filter(&self, filter: &Icmpv6Filter) -> io::Result<()> {
+        unsafe { setsockopt(self.inner, libc::IPPROTO_ICMPV6, ICMP6_FILTER, *filter) }
+    }
+
+    /// Get the value of the `IP_RECVTTL` option on this socket.
+    ///
+    /// For more information about this option, see [`set_recvttl`].
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_recvttl`]: crate::Socket::set_recvttl
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn recvttl(&self) -> io::Result<bool> {
+        unsafe {
+            getsockopt::<c_int>(self.inner, libc::IPPROTO_IP, libc::IP_RECVTTL)
+                .map(|recvttl| recvttl != 0)
+        }
+    }
+
+    /// Set the value of the `IP_RECVTTL` option on this socket.
+    ///
+    /// If enabled, the TTL of received packets is passed along as `IP_TTL`
+    /// control message. It can be read using [`recv_from_with_ttl`].
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`recv_from_with_ttl`]: crate::Socket::recv_from_with_ttl
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn set_recvttl(&self, recvttl: bool) -> io::Result<()> {
+        unsafe {
+            setsockopt(
+                self.inner,
+                libc::IPPROTO_IP,
+                libc::IP_RECVTTL,
+                recvttl as c_int,
+            )
+        }
+    }
+
+    /// Get the value of the `IPV6_RECVHOPLIMIT` option on this socket.
+    ///
+    /// For more information about this option, see [`set_recvhoplimit_v6`].
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_recvhoplimit_v6`]: crate::Socket::set_recvhoplimit_v6
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn recvhoplimit_v6(&self) -> io::Result<bool> {
+        unsafe {
+            getsockopt::<c_int>(self.inner, libc::IPPROTO_IPV6, libc::IPV6_RECVHOPLIMIT)
+                .map(|recvhoplimit| recvhoplimit != 0)
+        }
+    }
+
+    /// Set the value of the `IPV6_RECVHOPLIMIT` option on this socket.
+    ///
+    /// This is the IPv6 version of [`set_recvttl`]. If enabled, the hop limit
+    /// of received packets is passed along as `IPV6_HOPLIMIT` control message.
+    /// It can be read using [`recv_from_with_ttl`].
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_recvttl`]: crate::Socket::set_recvttl
+    /// [`recv_from_with_ttl`]: crate::Socket::recv_from_with_ttl
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn set_recvhoplimit_v6(&self, recvhoplimit: bool) -> io::Result<()> {
+        unsafe {
+            setsockopt(
+                self.inner,
+                libc::IPPROTO_IPV6,
+                libc::IPV6_RECVHOPLIMIT,
+                recvhoplimit as c_int,
+            )
+        }
+    }
+
+    /// Receives data from the socket, returning the address it came from and
+    /// the TTL of the received packet.
+    ///
+    /// The TTL is decoded from the `IP_TTL` control message, or for IPv6 the
+    /// hop limit from the `IPV6_HOPLIMIT` control message. These are only
+    /// passed along if [`set_recvttl`] or [`set_recvhoplimit_v6`] is enabled
+    /// respectively, otherwise `None` is returned.
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_recvttl`]: crate::Socket::set_recvttl
+    /// [`set_recvhoplimit_v6`]: crate::Socket::set_recvhoplimit_v6
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn recv_from_with_ttl(
+        &self,
+        buf: &mut [MaybeUninit<u8>],
+    ) -> io::Result<(usize, SockAddr, Option<u32>)> {
+        let mut ttl = None;
+        let (n, addr) = recv_from_with_control(self.inner, buf, |level, ty, data| {
+            if (level == libc::IPPROTO_IP && ty == libc::IP_TTL)
+                || (level == libc::IPPROTO_IPV6 && ty == libc::IPV6_HOPLIMIT)
+            {
+                ttl = cmsg_int(data).map(|ttl| ttl as u32);
+            }
+        })?;
+        Ok((n, addr, ttl))
+    }
+
+    /// Get the value of the `IPV6_FLOWINFO_SEND` option on this socket.
+    ///
+    /// For more information about this option, see [`set_flowinfo_send_v6`].
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_flowinfo_send_v6`]: crate::Socket::set_flowinfo_send_v6
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn flowinfo_send_v6(&self) -> io::Result<bool> {
+        unsafe {
+            getsockopt::<c_int>(self.inner, libc::IPPROTO_IPV6, libc::IPV6_FLOWINFO_SEND)
+                .map(|flowinfo_send| flowinfo_send != 0)
+        }
+    }
+
+    /// Set the value of the `IPV6_FLOWINFO_SEND` option on this socket.
+    ///
+    /// If enabled, the flow information (traffic class and flow label) of the
+    /// destination address passed to `connect(2)` or `sendto(2)` is used in
+    /// the sent packets. The flow label must first be acquired using
+    /// [`request_flow_label_v6`].
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`request_flow_label_v6`]: crate::Socket::request_flow_label_v6
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn set_flowinfo_send_v6(&self, flowinfo_send: bool) -> io::Result<()> {
+        unsafe {
+            setsockopt(
+                self.inner,
+                libc::IPPROTO_IPV6,
+                libc::IPV6_FLOWINFO_SEND,
+                flowinfo_send as c_int,
+            )
+        }
+    }
+
+    /// Acquire the flow label `label` for sending to `destination`, using the
+    /// `IPV6_FLOWLABEL_MGR` option.
+    ///
+    /// If `label` is zero the kernel picks a random, unused, flow label. The
+    /// flow label is exclusive to this socket and returned in host byte order.
+    /// The label can be released using [`release_flow_label_v6`].
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`release_flow_label_v6`]: crate::Socket::release_flow_label_v6
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn request_flow_label_v6(&self, destination: &Ipv6Addr, label: u32) -> io::Result<u32> {
+        flowlabel_mgr(
+            self.inner,
+            destination,
+            label,
+            IPV6_FL_A_GET,
+            IPV6_FL_S_EXCL,
+            IPV6_FL_F_CREATE,
+        )
+    }
+
+    /// Release the flow label `label` previously acquired using
+    /// [`request_flow_label_v6`], using the `IPV6_FLOWLABEL_MGR` option.
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`request_flow_label_v6`]: crate::Socket::request_flow_label_v6
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn release_flow_label_v6(&self, destination: &Ipv6Addr, label: u32) -> io::Result<()> {
+        flowlabel_mgr(
+            self.inner,
+            destination,
+            label,
+            IPV6_FL_A_PUT,
+            IPV6_FL_S_NONE,
+            0,
+        )
+        .map(|_| ())
+    }
+
+    /// Get the value of the `IPV6_FLOWINFO` option on this socket.
+    ///
+    /// For more information about this option, see [`set_recvflowinfo_v6`].
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_recvflowinfo_v6`]: crate::Socket::set_recvflowinfo_v6
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn recvflowinfo_v6(&self) -> io::Result<bool> {
+        unsafe {
+            getsockopt::<c_int>(self.inner, libc::IPPROTO_IPV6, libc::IPV6_FLOWINFO)
+                .map(|recvflowinfo| recvflowinfo != 0)
+        }
+    }
+
+    /// Set the value of the `IPV6_FLOWINFO` option on this socket.
+    ///
+    /// If enabled, the flow information of received packets is passed along as
+    /// `IPV6_FLOWINFO` control message. It can be read using
+    /// [`recv_from_with_flowinfo_v6`].
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`recv_from_with_flowinfo_v6`]: crate::Socket::recv_from_with_flowinfo_v6
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn set_recvflowinfo_v6(&self, recvflowinfo: bool) -> io::Result<()> {
+        unsafe {
+            setsockopt(
+                self.inner,
+                libc::IPPROTO_IPV6,
+                libc::IPV6_FLOWINFO,
+                recvflowinfo as c_int,
+            )
+        }
+    }
+
+    /// Receives data from the socket, returning the address it came from and
+    /// the flow information of the received packet.
+    ///
+    /// The flow information, the traffic class and flow label (the lower 20
+    /// bits), is returned in host byte order. It's decoded from the
+    /// `IPV6_FLOWINFO` control message, which is only passed along if
+    /// [`set_recvflowinfo_v6`] is enabled, otherwise `None` is returned.
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_recvflowinfo_v6`]: crate::Socket::set_recvflowinfo_v6
+    #[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+    pub fn recv_from_with_flowinfo_v6(
+        &self,
+        buf: &mut [MaybeUninit<u8>],
+    ) -> io::Result<(usize, SockAddr, Option<u32>)> {
+        let mut flowinfo = None;
+        let (n, addr) = recv_from_with_control(self.inner, buf, |level, ty, data| {
+            if level == libc::IPPROTO_IPV6 && ty == libc::IPV6_FLOWINFO {
+                flowinfo = cmsg_int(data).map(|flowinfo| u32::from_be(flowinfo as u32));
+            }
+        })?;
+        Ok((n, addr, flowinfo))
+    }
+
+    /// Returns the number of bytes in the send queue that have not yet been
+    /// sent, or for TCP not yet acknowledged, by the peer.
+    ///
+    /// This can be used to wait for the send queue to drain before closing a
+    /// connection, or as back-pressure heuristic.
+    ///
+    /// On Android and Linux this uses `SIOCOUTQ`, on FreeBSD and NetBSD
+    /// `FIONWRITE` and on macOS and iOS the `SO_NWRITE` option.
+    ///
+    /// This function is only available on Android, FreeBSD, iOS, Linux, macOS
+    /// and NetBSD.
+    #[cfg(all(
+        feature = "all",
+        any(
+            target_os = "android",
+            target_os = "freebsd",
+            target_os = "linux",
+            target_os = "netbsd",
+            target_vendor = "apple",
+        )
+    ))]
+    pub fn bytes_pending_send(&self) -> io::Result<usize> {
+        #[cfg(not(target_vendor = "apple"))]
+        {
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            let request = libc::TIOCOUTQ; // Same as `SIOCOUTQ`.
+            #[cfg(any(target_os = "freebsd", target_os = "netbsd"))]
+            let request = libc::FIONWRITE;
+            let mut pending: c_int = 0;
+            syscall!(ioctl(self.inner, request, &mut pending)).map(|_| pending as usize)
+        }
+        #[cfg(target_vendor = "apple")]
         unsafe {
-            setsockopt(
-                self.inner,
-                libc::SOL_SOCKET,
-                libc::IP_FREEBIND,
-                reuse as c_int,
-            )
+            getsockopt::<c_int>(self.inner, libc::SOL_SOCKET, libc::SO_NWRITE)
+                .map(|pending| pending as usize)
         }
     }
 
+    /// Issue the `ioctl(2)` `request` on the socket, passing `arg` as argument.
+    ///
+    /// This can be used for requests, such as `SIOC*` requests, this crate
+    /// doesn't provide a dedicated method for. Returns the value returned by
+    /// `ioctl(2)`.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `T` is the correct argument type for
+    /// `request`.
+    #[cfg(feature = "all")]
+    pub unsafe fn ioctl<T>(&self, request: libc::c_ulong, arg: &mut T) -> io::Result<c_int> {
+        // The type of `request` differs per platform, but all requests fit in
+        // 32 bits.
+        syscall!(ioctl(self.inner, request as _, arg as *mut T))
+    }
+
     /// Copies data between a `file` and this socket using the `sendfile(2)`
     /// system call. Because this copying is done within the kernel,
     /// `sendfile()` is more efficient than the combination of `read(2)` and
@@ -1357,7 +3753,7 @@ impl crate::Socket {
             // A value of `0` means send all bytes.
             None => 0,
         };
-        syscall!(sendfile(
+        syscall!(@no_socket sendfile(
             file,
             self.inner,
             offset,
@@ -1397,7 +3793,7 @@ impl crate::Socket {
             None => 0,
         };
         let mut sbytes: libc::off_t = 0;
-        syscall!(sendfile(
+        syscall!(@no_socket sendfile(
             file,
             self.inner,
             offset,
@@ -1408,6 +3804,748 @@ impl crate::Socket {
         ))
         .map(|_| sbytes as usize)
     }
+
+    /// Prepare a `send` operation of `buf` for submission to a completion based
+    /// API such as `io_uring`.
+    ///
+    /// See [`PreparedOp`] for more information.
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`PreparedOp`]: crate::PreparedOp
+    #[cfg(all(feature = "all", target_os = "linux"))]
+    pub fn prepare_send<'a>(&'a self, buf: &'a [u8]) -> crate::PreparedOp<'a> {
+        PreparedOp {
+            fd: self.inner,
+            buf: buf.as_ptr() as *mut u8,
+            buf_len: buf.len(),
+            addr: ptr::null_mut(),
+            addr_len: ptr::null_mut(),
+            _lifetime: PhantomData,
+        }
+    }
+
+    /// Prepare a `recv` operation into `buf` for submission to a completion
+    /// based API such as `io_uring`.
+    ///
+    /// See [`PreparedOp`] for more information.
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`PreparedOp`]: crate::PreparedOp
+    #[cfg(all(feature = "all", target_os = "linux"))]
+    pub fn prepare_recv<'a>(&'a self, buf: &'a mut [MaybeUninit<u8>]) -> crate::PreparedOp<'a> {
+        PreparedOp {
+            fd: self.inner,
+            buf: buf.as_mut_ptr().cast(),
+            buf_len: buf.len(),
+            addr: ptr::null_mut(),
+            addr_len: ptr::null_mut(),
+            _lifetime: PhantomData,
+        }
+    }
+
+    /// Prepare an `accept` operation, writing the peer's address into `addr`,
+    /// for submission to a completion based API such as `io_uring`.
+    ///
+    /// The length of `addr` is set to the size of the storage, the operation
+    /// updates it to the length of the peer's address.
+    ///
+    /// See [`PreparedOp`] for more information.
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`PreparedOp`]: crate::PreparedOp
+    #[cfg(all(feature = "all", target_os = "linux"))]
+    pub fn prepare_accept<'a>(&'a self, addr: &'a mut SockAddr) -> crate::PreparedOp<'a> {
+        let addr_len = addr.as_mut_len_ptr();
+        // Safety: `addr_len` points to the length field of `addr`.
+        unsafe { *addr_len = size_of::<libc::sockaddr_storage>() as libc::socklen_t };
+        PreparedOp {
+            fd: self.inner,
+            buf: ptr::null_mut(),
+            buf_len: 0,
+            addr: addr.as_mut_ptr(),
+            addr_len,
+            _lifetime: PhantomData,
+        }
+    }
+
+    /// Prepare a `connect` operation to `addr` for submission to a completion
+    /// based API such as `io_uring`.
+    ///
+    /// See [`PreparedOp`] for more information.
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`PreparedOp`]: crate::PreparedOp
+    #[cfg(all(feature = "all", target_os = "linux"))]
+    pub fn prepare_connect<'a>(&'a self, addr: &'a SockAddr) -> crate::PreparedOp<'a> {
+        PreparedOp {
+            fd: self.inner,
+            buf: ptr::null_mut(),
+            buf_len: 0,
+            addr: addr.as_storage_ref() as *const _ as *mut _,
+            addr_len: addr.len_ptr() as *mut _,
+            _lifetime: PhantomData,
+        }
+    }
+}
+
+/// A socket operation prepared for submission to a completion based API such
+/// as `io_uring`.
+///
+/// This holds the raw arguments of the system call: the file descriptor, the
+/// buffer (as used in an `iovec`) and the address storage with its length.
+/// The buffer and address are borrowed for the lifetime `'a`, so the pointers
+/// are stable and valid for as long as the `PreparedOp` is alive. The caller
+/// must keep it alive until the operation completes.
+///
+/// Created by [`Socket::prepare_send`], [`Socket::prepare_recv`],
+/// [`Socket::prepare_accept`] and [`Socket::prepare_connect`]. Arguments not
+/// used by the operation are null pointers (with a length of zero). The buffer
+/// of a `send` and the address of a `connect` must only be read.
+///
+/// [`Socket::prepare_send`]: crate::Socket::prepare_send
+/// [`Socket::prepare_recv`]: crate::Socket::prepare_recv
+/// [`Socket::prepare_accept`]: crate::Socket::prepare_accept
+/// [`Socket::prepare_connect`]: crate::Socket::prepare_connect
+#[cfg(all(feature = "all", target_os = "linux"))]
+#[derive(Debug)]
+pub struct PreparedOp<'a> {
+    fd: RawFd,
+    buf: *mut u8,
+    buf_len: usize,
+    addr: *mut libc::sockaddr_storage,
+    addr_len: *mut libc::socklen_t,
+    _lifetime: PhantomData<&'a mut ()>,
+}
+
+#[cfg(all(feature = "all", target_os = "linux"))]
+impl<'a> PreparedOp<'a> {
+    /// Returns the file descriptor of the socket.
+    pub const fn fd(&self) -> RawFd {
+        self.fd
+    }
+
+    /// Returns a pointer to the buffer.
+    pub const fn buf_ptr(&self) -> *mut u8 {
+        self.buf
+    }
+
+    /// Returns the length of the buffer.
+    pub const fn buf_len(&self) -> usize {
+        self.buf_len
+    }
+
+    /// Returns a pointer to the address storage.
+    pub const fn addr_ptr(&self) -> *mut libc::sockaddr {
+        self.addr.cast()
+    }
+
+    /// Returns a pointer to the length of the address.
+    ///
+    /// For an `accept` operation this is a value-result argument, the kernel
+    /// writes the length of the peer's address to it.
+    pub const fn addr_len_ptr(&self) -> *mut libc::socklen_t {
+        self.addr_len
+    }
+
+    /// Returns the length of the address, or zero if the operation has no
+    /// address.
+    pub fn addr_len(&self) -> libc::socklen_t {
+        if self.addr_len.is_null() {
+            0
+        } else {
+            // Safety: the length is borrowed for `'a`.
+            unsafe { *self.addr_len }
+        }
+    }
+}
+
+/// Extended error received from the error queue of a socket, wrapping
+/// `struct sock_extended_err`.
+///
+/// See [`Socket::recv_error`].
+///
+/// [`Socket::recv_error`]: crate::Socket::recv_error
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+#[derive(Debug)]
+pub struct SockExtendedErr {
+    errno: u32,
+    origin: u8,
+    r#type: u8,
+    code: u8,
+    info: u32,
+    data: u32,
+    offender: Option<SockAddr>,
+}
+
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+impl SockExtendedErr {
+    /// Decode a `sock_extended_err` and the offending address that follows it
+    /// from control message data of `len` bytes.
+    unsafe fn from_raw(data: *const u8, len: usize) -> Option<SockExtendedErr> {
+        const ERR_LEN: usize = size_of::<libc::sock_extended_err>();
+        if len < ERR_LEN {
+            return None;
+        }
+        let err = ptr::read_unaligned(data.cast::<libc::sock_extended_err>());
+
+        let addr = data.add(ERR_LEN);
+        let offender = if len >= ERR_LEN + size_of::<libc::sockaddr>() {
+            let addr_len =
+                match c_int::from(ptr::read_unaligned(addr.cast::<libc::sockaddr>()).sa_family) {
+                    libc::AF_INET => size_of::<libc::sockaddr_in>(),
+                    libc::AF_INET6 => size_of::<libc::sockaddr_in6>(),
+                    _ => 0,
+                };
+            if addr_len != 0 && len >= ERR_LEN + addr_len {
+                let mut storage: sockaddr_storage = mem::zeroed();
+                ptr::copy_nonoverlapping(
+                    addr,
+                    (&mut storage as *mut sockaddr_storage).cast::<u8>(),
+                    addr_len,
+                );
+                Some(SockAddr::new(storage, addr_len as socklen_t))
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        Some(SockExtendedErr {
+            errno: err.ee_errno,
+            origin: err.ee_origin,
+            r#type: err.ee_type,
+            code: err.ee_code,
+            info: err.ee_info,
+            data: err.ee_data,
+            offender,
+        })
+    }
+
+    /// Returns the error as an [`io::Error`], from `ee_errno`.
+    pub fn error(&self) -> io::Error {
+        io::Error::from_raw_os_error(self.errno as c_int)
+    }
+
+    /// Returns the origin of the error (`ee_origin`), e.g.
+    /// `SO_EE_ORIGIN_ICMP` or `SO_EE_ORIGIN_ZEROCOPY`.
+    pub const fn origin(&self) -> u8 {
+        self.origin
+    }
+
+    /// Returns the type of the error (`ee_type`), e.g. the ICMP type.
+    pub const fn r#type(&self) -> u8 {
+        self.r#type
+    }
+
+    /// Returns the code of the error (`ee_code`), e.g. the ICMP code.
+    pub const fn code(&self) -> u8 {
+        self.code
+    }
+
+    /// Returns additional information (`ee_info`), e.g. the discovered path
+    /// MTU for `EMSGSIZE` errors.
+    pub const fn info(&self) -> u32 {
+        self.info
+    }
+
+    /// Returns additional data (`ee_data`), e.g. the end of the completed
+    /// range for zerocopy completions.
+    pub const fn data(&self) -> u32 {
+        self.data
+    }
+
+    /// Returns the address of the node that caused the error, if known
+    /// (`SO_EE_OFFENDER`).
+    pub const fn offender(&self) -> Option<&SockAddr> {
+        self.offender.as_ref()
+    }
+}
+
+/// Queue of a TCP socket in repair mode, used in the `TCP_REPAIR_QUEUE`
+/// option.
+///
+/// See [`Socket::set_tcp_repair_queue`].
+///
+/// [`Socket::set_tcp_repair_queue`]: crate::Socket::set_tcp_repair_queue
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TcpRepairQueue {
+    /// No queue selected, `TCP_NO_QUEUE`.
+    None = 0,
+    /// The receive queue, `TCP_RECV_QUEUE`.
+    Recv = 1,
+    /// The send queue, `TCP_SEND_QUEUE`.
+    Send = 2,
+}
+
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+impl TcpRepairQueue {
+    fn from_raw(queue: c_int) -> TcpRepairQueue {
+        match queue {
+            1 => TcpRepairQueue::Recv,
+            2 => TcpRepairQueue::Send,
+            _ => TcpRepairQueue::None,
+        }
+    }
+}
+
+/// Transport of an `AF_RDS` socket, used in the `SO_RDS_TRANSPORT` option.
+///
+/// See [`Socket::set_rds_transport`].
+///
+/// [`Socket::set_rds_transport`]: crate::Socket::set_rds_transport
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RdsTransport {
+    /// InfiniBand, `RDS_TRANS_IB`.
+    InfiniBand = 0,
+    /// TCP, `RDS_TRANS_TCP`.
+    Tcp = 2,
+}
+
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+impl RdsTransport {
+    fn from_raw(transport: c_int) -> Option<RdsTransport> {
+        match transport {
+            0 => Some(RdsTransport::InfiniBand),
+            2 => Some(RdsTransport::Tcp),
+            // `RDS_TRANS_NONE`.
+            _ => None,
+        }
+    }
+}
+
+/// Result of [`Socket::tcp_zerocopy_receive`].
+///
+/// [`Socket::tcp_zerocopy_receive`]: crate::Socket::tcp_zerocopy_receive
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+#[derive(Copy, Clone, Debug)]
+pub struct TcpZeroCopyReceive {
+    length: u32,
+    recv_skip_hint: u32,
+    inq: u32,
+}
+
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+impl TcpZeroCopyReceive {
+    /// Returns the number of bytes mapped at the start of the region.
+    pub const fn length(&self) -> u32 {
+        self.length
+    }
+
+    /// Returns the number of bytes that couldn't be mapped and must be read
+    /// using a regular `recv` call.
+    pub const fn recv_skip_hint(&self) -> u32 {
+        self.recv_skip_hint
+    }
+
+    /// Returns the number of bytes remaining in the receive queue.
+    pub const fn inq(&self) -> u32 {
+        self.inq
+    }
+}
+
+/// TLS protocol version, used in [`TlsCryptoInfo`].
+#[cfg(all(feature = "all", target_os = "linux"))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TlsVersion {
+    /// TLS 1.2.
+    Tls12,
+    /// TLS 1.3.
+    Tls13,
+}
+
+/// Kernel TLS crypto parameters, used in the `TLS_TX` and `TLS_RX` options.
+///
+/// The parameters are the result of the TLS handshake: the traffic key, the
+/// IV (split into the salt, or implicit nonce, and the explicit IV for the
+/// AES-GCM ciphers) and the record sequence number, all in network byte order.
+///
+/// See [`Socket::set_tls_tx`] and [`Socket::set_tls_rx`].
+///
+/// [`Socket::set_tls_tx`]: crate::Socket::set_tls_tx
+/// [`Socket::set_tls_rx`]: crate::Socket::set_tls_rx
+#[cfg(all(feature = "all", target_os = "linux"))]
+#[derive(Clone)]
+pub enum TlsCryptoInfo {
+    /// AES-128-GCM, `TLS_CIPHER_AES_GCM_128`.
+    Aes128Gcm {
+        /// TLS protocol version.
+        version: TlsVersion,
+        /// Explicit IV, the part of the IV sent with each record.
+        iv: [u8; 8],
+        /// Traffic key.
+        key: [u8; 16],
+        /// Salt, the implicit part of the IV.
+        salt: [u8; 4],
+        /// Record sequence number.
+        rec_seq: [u8; 8],
+    },
+    /// AES-256-GCM, `TLS_CIPHER_AES_GCM_256`.
+    Aes256Gcm {
+        /// TLS protocol version.
+        version: TlsVersion,
+        /// Explicit IV, the part of the IV sent with each record.
+        iv: [u8; 8],
+        /// Traffic key.
+        key: [u8; 32],
+        /// Salt, the implicit part of the IV.
+        salt: [u8; 4],
+        /// Record sequence number.
+        rec_seq: [u8; 8],
+    },
+    /// ChaCha20-Poly1305, `TLS_CIPHER_CHACHA20_POLY1305`.
+    Chacha20Poly1305 {
+        /// TLS protocol version.
+        version: TlsVersion,
+        /// IV, used entirely as the implicit nonce.
+        iv: [u8; 12],
+        /// Traffic key.
+        key: [u8; 32],
+        /// Record sequence number.
+        rec_seq: [u8; 8],
+    },
+}
+
+#[cfg(all(feature = "all", target_os = "linux"))]
+impl fmt::Debug for TlsCryptoInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Don't print the key material.
+        let (cipher, version) = match self {
+            TlsCryptoInfo::Aes128Gcm { version, .. } => ("Aes128Gcm", version),
+            TlsCryptoInfo::Aes256Gcm { version, .. } => ("Aes256Gcm", version),
+            TlsCryptoInfo::Chacha20Poly1305 { version, .. } => ("Chacha20Poly1305", version),
+        };
+        f.debug_struct("TlsCryptoInfo")
+            .field("cipher", &cipher)
+            .field("version", version)
+            .finish()
+    }
+}
+
+/// TCP Authentication Option (TCP-AO) master key.
+///
+/// See [`Socket::add_tcp_ao_key`] and [`Socket::delete_tcp_ao_key`].
+///
+/// [`Socket::add_tcp_ao_key`]: crate::Socket::add_tcp_ao_key
+/// [`Socket::delete_tcp_ao_key`]: crate::Socket::delete_tcp_ao_key
+#[cfg(all(feature = "all", target_os = "linux"))]
+#[derive(Clone)]
+pub struct TcpAoKey<'a> {
+    peer: &'a SockAddr,
+    prefix: u8,
+    algorithm: &'a str,
+    key: &'a [u8],
+    send_id: u8,
+    recv_id: u8,
+    mac_len: u8,
+}
+
+#[cfg(all(feature = "all", target_os = "linux"))]
+impl<'a> TcpAoKey<'a> {
+    /// Create a new key for connections with `peer`, the port is ignored.
+    ///
+    /// `algorithm` is the name of the kernel crypto algorithm to use, for
+    /// example `hmac(sha1)` or `cmac(aes128)`. `send_id` and `recv_id` are the
+    /// SendID and RecvID of the key, the peer must use the same key with the
+    /// ids swapped.
+    ///
+    /// The key only matches `peer` exactly, or any address if `peer` is the
+    /// unspecified address, use [`TcpAoKey::with_prefix`] to match a range of
+    /// addresses.
+    pub fn new(
+        peer: &'a SockAddr,
+        algorithm: &'a str,
+        key: &'a [u8],
+        send_id: u8,
+        recv_id: u8,
+    ) -> TcpAoKey<'a> {
+        let prefix = match peer.as_socket() {
+            Some(addr) if addr.ip().is_unspecified() => 0,
+            Some(std::net::SocketAddr::V4(..)) => 32,
+            _ => 128,
+        };
+        TcpAoKey {
+            peer,
+            prefix,
+            algorithm,
+            key,
+            send_id,
+            recv_id,
+            mac_len: 0,
+        }
+    }
+
+    /// Match all peer addresses in the network of the peer address with the
+    /// prefix length `prefix`.
+    pub const fn with_prefix(self, prefix: u8) -> TcpAoKey<'a> {
+        TcpAoKey { prefix, ..self }
+    }
+
+    /// Set the length of the message authentication code to `mac_len` bytes.
+    ///
+    /// By default the length of the algorithm's output is used.
+    pub const fn with_mac_len(self, mac_len: u8) -> TcpAoKey<'a> {
+        TcpAoKey { mac_len, ..self }
+    }
+}
+
+#[cfg(all(feature = "all", target_os = "linux"))]
+impl fmt::Debug for TcpAoKey<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Don't print the key material.
+        f.debug_struct("TcpAoKey")
+            .field("peer", &self.peer)
+            .field("prefix", &self.prefix)
+            .field("algorithm", &self.algorithm)
+            .field("send_id", &self.send_id)
+            .field("recv_id", &self.recv_id)
+            .field("mac_len", &self.mac_len)
+            .finish()
+    }
+}
+
+/// Netlink message, as sent by [`Socket::send_netlink`] and received by
+/// [`Socket::recv_netlink`].
+///
+/// The payload isn't parsed, that is left to protocol specific crates.
+///
+/// [`Socket::send_netlink`]: crate::Socket::send_netlink
+/// [`Socket::recv_netlink`]: crate::Socket::recv_netlink
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct NetlinkMessage<'a> {
+    msg_type: u16,
+    flags: u16,
+    seq: u32,
+    pid: u32,
+    payload: &'a [u8],
+}
+
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+impl<'a> NetlinkMessage<'a> {
+    /// Create a new message to send to the kernel.
+    pub const fn new(msg_type: u16, flags: u16, seq: u32, payload: &'a [u8]) -> NetlinkMessage<'a> {
+        NetlinkMessage {
+            msg_type,
+            flags,
+            seq,
+            pid: 0,
+            payload,
+        }
+    }
+
+    /// Returns the type of the message, `nlmsg_type`.
+    pub const fn msg_type(&self) -> u16 {
+        self.msg_type
+    }
+
+    /// Returns the flags of the message, `nlmsg_flags`.
+    pub const fn flags(&self) -> u16 {
+        self.flags
+    }
+
+    /// Returns the sequence number of the message, `nlmsg_seq`.
+    pub const fn seq(&self) -> u32 {
+        self.seq
+    }
+
+    /// Returns the port ID of the sender, `nlmsg_pid`.
+    pub const fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// Returns the payload of the message, without the header.
+    pub const fn payload(&self) -> &'a [u8] {
+        self.payload
+    }
+
+    /// Returns `true` if the message is part of a multipart response, i.e.
+    /// `NLM_F_MULTI` is set.
+    pub const fn is_multipart(&self) -> bool {
+        self.flags & libc::NLM_F_MULTI as u16 != 0
+    }
+
+    /// Returns `true` if the message ends a multipart response, i.e. the type
+    /// is `NLMSG_DONE`.
+    pub const fn is_done(&self) -> bool {
+        self.msg_type == libc::NLMSG_DONE as u16
+    }
+
+    /// Returns the error of an error message (`NLMSG_ERROR`).
+    ///
+    /// Returns `None` if the message is not an error message, or if it's an
+    /// acknowledgement, i.e. the error code is zero.
+    pub fn error(&self) -> Option<io::Error> {
+        if self.msg_type != libc::NLMSG_ERROR as u16 {
+            return None;
+        }
+        let mut code = [0; size_of::<c_int>()];
+        code.copy_from_slice(self.payload.get(..size_of::<c_int>())?);
+        // The kernel uses negative error codes.
+        match c_int::from_ne_bytes(code) {
+            0 => None,
+            code => Some(io::Error::from_raw_os_error(-code)),
+        }
+    }
+
+    /// Encode the message, including the header, using the native byte
+    /// order.
+    fn encode(&self) -> Vec<u8> {
+        let len = size_of::<libc::nlmsghdr>() + self.payload.len();
+        let mut buf = Vec::with_capacity(nlmsg_align(len));
+        buf.extend_from_slice(&(len as u32).to_ne_bytes());
+        buf.extend_from_slice(&self.msg_type.to_ne_bytes());
+        buf.extend_from_slice(&self.flags.to_ne_bytes());
+        buf.extend_from_slice(&self.seq.to_ne_bytes());
+        buf.extend_from_slice(&self.pid.to_ne_bytes());
+        buf.extend_from_slice(self.payload);
+        buf.resize(nlmsg_align(len), 0);
+        buf
+    }
+}
+
+/// Iterator over the netlink messages in a datagram, see
+/// [`Socket::recv_netlink`].
+///
+/// [`Socket::recv_netlink`]: crate::Socket::recv_netlink
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+#[derive(Clone, Debug)]
+pub struct NetlinkMessages<'a> {
+    buf: &'a [u8],
+}
+
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+impl<'a> NetlinkMessages<'a> {
+    /// Create an iterator over the netlink messages in `buf`.
+    pub const fn new(buf: &'a [u8]) -> NetlinkMessages<'a> {
+        NetlinkMessages { buf }
+    }
+}
+
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+impl<'a> Iterator for NetlinkMessages<'a> {
+    type Item = NetlinkMessage<'a>;
+
+    fn next(&mut self) -> Option<NetlinkMessage<'a>> {
+        let buf = self.buf;
+        if buf.len() < size_of::<libc::nlmsghdr>() {
+            return None;
+        }
+        let len = u32::from_ne_bytes([buf[0], buf[1], buf[2], buf[3]]) as usize;
+        if len < size_of::<libc::nlmsghdr>() || len > buf.len() {
+            // Invalid or truncated message.
+            self.buf = &[];
+            return None;
+        }
+        let msg = NetlinkMessage {
+            msg_type: u16::from_ne_bytes([buf[4], buf[5]]),
+            flags: u16::from_ne_bytes([buf[6], buf[7]]),
+            seq: u32::from_ne_bytes([buf[8], buf[9], buf[10], buf[11]]),
+            pid: u32::from_ne_bytes([buf[12], buf[13], buf[14], buf[15]]),
+            payload: &buf[size_of::<libc::nlmsghdr>()..len],
+        };
+        self.buf = &buf[min(nlmsg_align(len), buf.len())..];
+        Some(msg)
+    }
+}
+
+/// Aligns `len` to `NLMSG_ALIGNTO` (4) bytes.
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+const fn nlmsg_align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+/// Filter of ICMPv6 message types, used in the `ICMP6_FILTER` option.
+///
+/// See [`Socket::set_icmpv6_filter`].
+///
+/// [`Socket::set_icmpv6_filter`]: crate::Socket::set_icmpv6_filter
+#[cfg(all(
+    feature = "all",
+    any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_vendor = "apple",
+    )
+))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(C)] // Same layout as `struct icmp6_filter`, not defined by libc.
+pub struct Icmpv6Filter {
+    /// Bitmap of message types in the format used by the OS, where a set bit
+    /// means pass on BSD (and macOS) and block on Linux.
+    data: [u32; 8],
+}
+
+#[cfg(all(
+    feature = "all",
+    any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_vendor = "apple",
+    )
+))]
+impl Icmpv6Filter {
+    /// Whether a set bit passes the message type.
+    const SET_PASSES: bool = cfg!(not(any(target_os = "android", target_os = "linux")));
+
+    /// Create a filter that passes all message types, the default for new
+    /// sockets.
+    pub const fn pass_all() -> Icmpv6Filter {
+        let value = if Icmpv6Filter::SET_PASSES {
+            u32::MAX
+        } else {
+            0
+        };
+        Icmpv6Filter { data: [value; 8] }
+    }
+
+    /// Create a filter that blocks all message types.
+    pub const fn block_all() -> Icmpv6Filter {
+        let value = if Icmpv6Filter::SET_PASSES {
+            0
+        } else {
+            u32::MAX
+        };
+        Icmpv6Filter { data: [value; 8] }
+    }
+
+    /// Pass messages of type `r#type`.
+    pub fn pass(&mut self, r#type: u8) {
+        self.set(r#type, Icmpv6Filter::SET_PASSES);
+    }
+
+    /// Block messages of type `r#type`.
+    pub fn block(&mut self, r#type: u8) {
+        self.set(r#type, !Icmpv6Filter::SET_PASSES);
+    }
+
+    /// Returns `true` if messages of type `r#type` are passed.
+    pub const fn will_pass(&self, r#type: u8) -> bool {
+        let set = self.data[(r#type >> 5) as usize] & (1 << (r#type & 31)) != 0;
+        set == Icmpv6Filter::SET_PASSES
+    }
+
+    /// Returns `true` if messages of type `r#type` are blocked.
+    pub const fn will_block(&self, r#type: u8) -> bool {
+        !self.will_pass(r#type)
+    }
+
+    fn set(&mut self, r#type: u8, bit: bool) {
+        let word = &mut self.data[(r#type >> 5) as usize];
+        if bit {
+            *word |= 1 << (r#type & 31);
+        } else {
+            *word &= !(1 << (r#type & 31));
+        }
+    }
 }
 
 impl AsRawFd for crate::Socket {
@@ -1430,20 +4568,76 @@ impl FromRawFd for crate::Socket {
     }
 }
 
-#[cfg(feature = "all")]
+impl AsFd for crate::Socket {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // SAFETY: lifetime is bound by the socket, which owns the fd.
+        unsafe { BorrowedFd::borrow_raw(self.inner) }
+    }
+}
+
+impl From<OwnedFd> for crate::Socket {
+    fn from(fd: OwnedFd) -> crate::Socket {
+        crate::Socket {
+            inner: fd.into_raw_fd(),
+        }
+    }
+}
+
+impl From<crate::Socket> for OwnedFd {
+    fn from(socket: crate::Socket) -> OwnedFd {
+        // SAFETY: `into_raw_fd` gives up ownership of the fd.
+        unsafe { OwnedFd::from_raw_fd(socket.into_raw_fd()) }
+    }
+}
+
 from!(UnixStream, crate::Socket);
-#[cfg(feature = "all")]
 from!(UnixListener, crate::Socket);
-#[cfg(feature = "all")]
 from!(UnixDatagram, crate::Socket);
-#[cfg(feature = "all")]
 from!(crate::Socket, UnixStream);
-#[cfg(feature = "all")]
 from!(crate::Socket, UnixListener);
-#[cfg(feature = "all")]
 from!(crate::Socket, UnixDatagram);
 
+#[cfg(feature = "mio")]
+impl mio::event::Source for crate::Socket {
+    fn register(
+        &mut self,
+        registry: &mio::Registry,
+        token: mio::Token,
+        interests: mio::Interest,
+    ) -> io::Result<()> {
+        mio::unix::SourceFd(&self.inner).register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &mio::Registry,
+        token: mio::Token,
+        interests: mio::Interest,
+    ) -> io::Result<()> {
+        mio::unix::SourceFd(&self.inner).reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &mio::Registry) -> io::Result<()> {
+        mio::unix::SourceFd(&self.inner).deregister(registry)
+    }
+}
+
 #[test]
+#[cfg(not(target_os = "redox"))]
+fn recv_flags() {
+    let flags = RecvFlags(libc::MSG_EOR | libc::MSG_CTRUNC);
+    assert!(flags.is_end_of_record());
+    assert!(!flags.is_out_of_band());
+    assert!(flags.is_ctrunc());
+    assert!(!flags.is_truncated());
+    assert_eq!(
+        format!("{:?}", flags),
+        "RecvFlags { is_end_of_record: true, is_out_of_band: false, is_ctrunc: true, is_truncated: false }"
+    );
+}
+
+#[test]
+#[allow(clippy::identity_op)] // Shows the byte positions.
 fn in_addr_convertion() {
     let ip = Ipv4Addr::new(127, 0, 0, 1);
     let raw = to_in_addr(&ip);
@@ -1467,3 +4661,26 @@ fn in6_addr_convertion() {
     assert_eq!(raw.s6_addr, want);
     assert_eq!(from_in6_addr(raw), ip);
 }
+
+#[test]
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+fn netlink_messages() {
+    let msg = NetlinkMessage::new(18, 0x301, 7, &[1, 2, 3]);
+    let mut buf = msg.encode();
+    assert_eq!(buf.len(), 20);
+    assert_eq!(&buf[..4], &19u32.to_ne_bytes());
+    // Error message with `ENOENT`.
+    buf.extend_from_slice(&20u32.to_ne_bytes());
+    buf.extend_from_slice(&(libc::NLMSG_ERROR as u16).to_ne_bytes());
+    buf.extend_from_slice(&[0; 10]);
+    buf.extend_from_slice(&(-libc::ENOENT).to_ne_bytes());
+    // Truncated message.
+    buf.extend_from_slice(&100u32.to_ne_bytes());
+
+    let mut msgs = NetlinkMessages::new(&buf);
+    assert_eq!(msgs.next(), Some(msg));
+    let err = msgs.next().unwrap();
+    assert_eq!(err.error().unwrap().raw_os_error(), Some(libc::ENOENT));
+    assert_eq!(msgs.next(), None);
+    assert!(msg.error().is_none());
+}
@@ -7,25 +7,59 @@
 // except according to those terms.
 
 use std::cmp::min;
+use std::ffi::CStr;
 use std::io::{self, IoSlice};
 use std::marker::PhantomData;
 use std::mem::{self, size_of, MaybeUninit};
-use std::net::{self, Ipv4Addr, Ipv6Addr, Shutdown};
+use std::net::{Ipv4Addr, Ipv6Addr, Shutdown};
+use std::os::windows::io::{AsSocket, BorrowedSocket, OwnedSocket};
 use std::os::windows::prelude::*;
 use std::sync::Once;
 use std::time::{Duration, Instant};
 use std::{ptr, slice};
 
 use winapi::ctypes::c_long;
+#[cfg(feature = "all")]
+use winapi::shared::guiddef::GUID;
+use winapi::shared::ifdef::IfOperStatusUp;
 use winapi::shared::in6addr::*;
 use winapi::shared::inaddr::*;
+use winapi::shared::ipifcons::IF_TYPE_SOFTWARE_LOOPBACK;
 use winapi::shared::minwindef::DWORD;
 use winapi::shared::minwindef::ULONG;
 use winapi::shared::mstcpip::{tcp_keepalive, SIO_KEEPALIVE_VALS};
+#[cfg(feature = "all")]
+use winapi::shared::mstcpip::{
+    RCVALL_IPLEVEL, RCVALL_OFF, RCVALL_ON, RCVALL_SOCKETLEVELONLY, RCVALL_VALUE,
+    SIO_LOOPBACK_FAST_PATH, SIO_RCVALL, SIO_TCP_INITIAL_RTO,
+    TCP_INITIAL_RTO_DEFAULT_MAX_SYN_RETRANSMISSIONS, TCP_INITIAL_RTO_DEFAULT_RTT,
+    TCP_INITIAL_RTO_PARAMETERS,
+};
 use winapi::shared::ntdef::HANDLE;
+#[cfg(feature = "all")]
+use winapi::shared::winerror::ERROR_IO_PENDING;
+pub(crate) use winapi::shared::winerror::WSAEOPNOTSUPP;
+use winapi::shared::winerror::{
+    ERROR_BUFFER_OVERFLOW, ERROR_NO_DATA, ERROR_SUCCESS, WSAEINVAL, WSAENOPROTOOPT,
+};
 use winapi::shared::ws2def;
+use winapi::shared::ws2def::AF_UNSPEC;
 use winapi::shared::ws2def::WSABUF;
+#[cfg(feature = "all")]
+use winapi::um::handleapi::GetHandleInformation;
 use winapi::um::handleapi::SetHandleInformation;
+use winapi::um::iphlpapi::GetAdaptersAddresses;
+use winapi::um::iptypes::{
+    GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER, GAA_FLAG_SKIP_MULTICAST,
+    IP_ADAPTER_ADDRESSES_LH, IP_ADAPTER_NO_MULTICAST,
+};
+#[cfg(feature = "all")]
+use winapi::um::minwinbase::OVERLAPPED;
+#[cfg(feature = "all")]
+use winapi::um::mswsock::{
+    LPFN_ACCEPTEX, LPFN_CONNECTEX, LPFN_GETACCEPTEXSOCKADDRS, SO_UPDATE_ACCEPT_CONTEXT,
+    SO_UPDATE_CONNECT_CONTEXT, WSAID_ACCEPTEX, WSAID_CONNECTEX, WSAID_GETACCEPTEXSOCKADDRS,
+};
 use winapi::um::processthreadsapi::GetCurrentProcessId;
 use winapi::um::winbase::{self, INFINITE};
 use winapi::um::winsock2::{
@@ -48,12 +82,13 @@ pub(crate) use winapi::shared::ws2def::{AF_INET, AF_INET6};
 // Used in `Type`.
 pub(crate) use winapi::shared::ws2def::{SOCK_DGRAM, SOCK_STREAM};
 #[cfg(feature = "all")]
-pub(crate) use winapi::shared::ws2def::{SOCK_RAW, SOCK_SEQPACKET};
+pub(crate) use winapi::shared::ws2def::{SOCK_RAW, SOCK_RDM, SOCK_SEQPACKET};
 // Used in `Protocol`.
 pub(crate) const IPPROTO_ICMP: c_int = winapi::shared::ws2def::IPPROTO_ICMP as c_int;
 pub(crate) const IPPROTO_ICMPV6: c_int = winapi::shared::ws2def::IPPROTO_ICMPV6 as c_int;
 pub(crate) const IPPROTO_TCP: c_int = winapi::shared::ws2def::IPPROTO_TCP as c_int;
 pub(crate) const IPPROTO_UDP: c_int = winapi::shared::ws2def::IPPROTO_UDP as c_int;
+pub(crate) const IPPROTO_RAW: c_int = winapi::shared::ws2def::IPPROTO_RAW as c_int;
 // Used in `SockAddr`.
 pub(crate) use winapi::shared::ws2def::{
     ADDRESS_FAMILY as sa_family_t, SOCKADDR as sockaddr, SOCKADDR_IN as sockaddr_in,
@@ -61,19 +96,27 @@ pub(crate) use winapi::shared::ws2def::{
 };
 pub(crate) use winapi::shared::ws2ipdef::SOCKADDR_IN6_LH as sockaddr_in6;
 pub(crate) use winapi::um::ws2tcpip::socklen_t;
+// Used in `getaddrinfo`.
+pub(crate) use winapi::shared::ws2def::{ADDRINFOA as addrinfo, AI_PASSIVE};
+pub(crate) use winapi::um::ws2tcpip::freeaddrinfo;
 // Used in `Socket`.
 pub(crate) use winapi::shared::ws2def::{
     IPPROTO_IP, SOL_SOCKET, SO_BROADCAST, SO_ERROR, SO_KEEPALIVE, SO_LINGER, SO_OOBINLINE,
     SO_RCVBUF, SO_RCVTIMEO, SO_REUSEADDR, SO_SNDBUF, SO_SNDTIMEO, TCP_NODELAY,
 };
 pub(crate) use winapi::shared::ws2ipdef::{
-    IPV6_ADD_MEMBERSHIP, IPV6_DROP_MEMBERSHIP, IPV6_MREQ as Ipv6Mreq, IPV6_MULTICAST_HOPS,
-    IPV6_MULTICAST_IF, IPV6_MULTICAST_LOOP, IPV6_UNICAST_HOPS, IPV6_V6ONLY, IP_ADD_MEMBERSHIP,
-    IP_DROP_MEMBERSHIP, IP_MREQ as IpMreq, IP_MULTICAST_IF, IP_MULTICAST_LOOP, IP_MULTICAST_TTL,
-    IP_TTL,
+    IPV6_ADD_MEMBERSHIP, IPV6_DONTFRAG, IPV6_DROP_MEMBERSHIP, IPV6_MREQ as Ipv6Mreq,
+    IPV6_MULTICAST_HOPS, IPV6_MULTICAST_IF, IPV6_MULTICAST_LOOP, IPV6_UNICAST_HOPS, IPV6_V6ONLY,
+    IP_ADD_MEMBERSHIP, IP_DROP_MEMBERSHIP, IP_MREQ as IpMreq, IP_MULTICAST_IF, IP_MULTICAST_LOOP,
+    IP_MULTICAST_TTL, IP_TTL,
 };
-pub(crate) use winapi::um::winsock2::{linger, MSG_OOB, MSG_PEEK};
+pub(crate) use winapi::um::winsock2::{linger, MSG_DONTROUTE, MSG_OOB, MSG_PEEK, MSG_WAITALL};
 pub(crate) const IPPROTO_IPV6: c_int = winapi::shared::ws2def::IPPROTO_IPV6 as c_int;
+#[cfg(feature = "all")]
+pub(crate) use winapi::shared::ws2ipdef::IPV6_UNICAST_IF;
+// Not defined in winapi, value from `ws2ipdef.h`.
+#[cfg(feature = "all")]
+pub(crate) const IP_UNICAST_IF: c_int = 31;
 
 /// Type used in set/getsockopt to retrieve the `TCP_NODELAY` option.
 ///
@@ -85,18 +128,33 @@ pub(crate) const IPPROTO_IPV6: c_int = winapi::shared::ws2def::IPPROTO_IPV6 as c
 pub(crate) type Bool = winapi::shared::ntdef::BOOLEAN;
 
 /// Maximum size of a buffer passed to system call like `recv` and `send`.
-const MAX_BUF_LEN: usize = <c_int>::max_value() as usize;
+const MAX_BUF_LEN: usize = <c_int>::MAX as usize;
 
 /// Helper macro to execute a system call that returns an `io::Result`.
+///
+/// The first argument of the system call must be the socket it operates on,
+/// for other system calls prefix the call with `@no_socket`.
 macro_rules! syscall {
-    ($fn: ident ( $($arg: expr),* $(,)* ), $err_test: path, $err_value: expr) => {{
+    (@no_socket $fn: ident ( $($arg: expr),* $(,)* ), $err_test: path, $err_value: expr) => {{
+        syscall!(@call $fn ( $($arg),* ), $err_test, $err_value, None)
+    }};
+    ($fn: ident ( $socket: expr $(, $arg: expr)* $(,)* ), $err_test: path, $err_value: expr) => {{
+        let socket = $socket;
+        syscall!(@call $fn ( socket $(, $arg)* ), $err_test, $err_value, Some(socket))
+    }};
+    (@call $fn: ident ( $($arg: expr),* ), $err_test: path, $err_value: expr, $socket: expr) => {{
         #[allow(unused_unsafe)]
         let res = unsafe { sock::$fn($($arg, )*) };
-        if $err_test(&res, &$err_value) {
+        let res = if $err_test(&res, &$err_value) {
             Err(io::Error::last_os_error())
         } else {
             Ok(res)
-        }
+        };
+        #[cfg(feature = "error-context")]
+        crate::error::record(stringify!($fn), $socket, res.as_ref().err());
+        #[cfg(feature = "tracing")]
+        tracing::trace!(syscall = stringify!($fn), result = ?res);
+        res
     }};
 }
 
@@ -112,6 +170,7 @@ impl_debug!(
 impl Type {
     /// Our custom flag to set `WSA_FLAG_NO_HANDLE_INHERIT` on socket creation.
     /// Trying to mimic `Type::cloexec` on windows.
+    #[allow(clippy::manual_bits)] // `c_int::BITS` requires Rust 1.53.
     const NO_INHERIT: c_int = 1 << ((size_of::<c_int>() * 8) - 1); // Last bit.
 
     /// Set `WSA_FLAG_NO_HANDLE_INHERIT` on the socket.
@@ -140,6 +199,7 @@ impl_debug!(
     self::IPPROTO_ICMPV6,
     self::IPPROTO_TCP,
     self::IPPROTO_UDP,
+    self::IPPROTO_RAW,
 );
 
 impl std::fmt::Debug for RecvFlags {
@@ -181,28 +241,167 @@ fn init() {
     static INIT: Once = Once::new();
 
     INIT.call_once(|| {
-        // Initialize winsock through the standard library by just creating a
-        // dummy socket. Whether this is successful or not we drop the result as
-        // libstd will be sure to have initialized winsock.
-        let _ = net::UdpSocket::bind("127.0.0.1:34254");
+        // Initialise Winsock 2.2, the same version the standard library uses.
+        // Whether this is successful or not we drop the result, if it fails
+        // the socket function calling `init` will return `WSANOTINITIALISED`.
+        // Like the standard library we never call `WSACleanup`.
+        let mut data: MaybeUninit<sock::WSADATA> = MaybeUninit::uninit();
+        let _ = unsafe { sock::WSAStartup(0x202, data.as_mut_ptr()) };
     });
 }
 
 pub(crate) type Socket = sock::SOCKET;
 
+pub(crate) fn getaddrinfo(
+    host: Option<&CStr>,
+    service: Option<&CStr>,
+    hints: &addrinfo,
+) -> io::Result<*mut addrinfo> {
+    init();
+
+    let mut list = ptr::null_mut();
+    let res = unsafe {
+        winapi::um::ws2tcpip::getaddrinfo(
+            host.map_or(ptr::null(), CStr::as_ptr),
+            service.map_or(ptr::null(), CStr::as_ptr),
+            hints,
+            &mut list,
+        )
+    };
+    if res == 0 {
+        Ok(list)
+    } else {
+        Err(io::Error::from_raw_os_error(res))
+    }
+}
+
+pub(crate) fn interfaces() -> io::Result<Vec<crate::Interface>> {
+    let buf = adapters_addresses()?;
+    let mut interfaces = Vec::new();
+    for adapter in adapters(&buf) {
+        let mut addresses = Vec::new();
+        let mut next_address = adapter.FirstUnicastAddress;
+        while !next_address.is_null() {
+            let address = unsafe { &*next_address };
+            next_address = address.Next;
+
+            let addr = address.Address.lpSockaddr;
+            let len = address.Address.iSockaddrLength;
+            if addr.is_null() || len as usize > size_of::<sockaddr_storage>() {
+                continue;
+            }
+            let mut storage: sockaddr_storage = unsafe { mem::zeroed() };
+            // Safety: `lpSockaddr` is valid for `iSockaddrLength` bytes.
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    addr.cast::<u8>(),
+                    (&mut storage as *mut sockaddr_storage).cast::<u8>(),
+                    len as usize,
+                );
+            }
+            addresses.push(unsafe { SockAddr::new(storage, len) });
+        }
+
+        let index = match unsafe { adapter.u.s().IfIndex } {
+            0 => adapter.Ipv6IfIndex,
+            index => index,
+        };
+        interfaces.push(crate::Interface {
+            name: from_wide_ptr(adapter.FriendlyName),
+            index,
+            flags: adapter.Flags,
+            up: adapter.OperStatus == IfOperStatusUp,
+            loopback: adapter.IfType == IF_TYPE_SOFTWARE_LOOPBACK,
+            multicast: adapter.Flags & IP_ADAPTER_NO_MULTICAST == 0,
+            addresses,
+        });
+    }
+    Ok(interfaces)
+}
+
+pub(crate) fn interface_mtu(name: &str) -> io::Result<u32> {
+    let buf = adapters_addresses()?;
+    let mtu = adapters(&buf)
+        .find(|adapter| from_wide_ptr(adapter.FriendlyName) == name)
+        .map(|adapter| adapter.Mtu);
+    mtu.ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "interface not found"))
+}
+
+/// Calls `GetAdaptersAddresses`, returning the buffer holding the adapters.
+///
+/// Use [`adapters`] to iterate over the adapters.
+#[allow(clippy::manual_div_ceil)] // `usize::div_ceil` requires Rust 1.73.
+fn adapters_addresses() -> io::Result<Vec<u64>> {
+    const FLAGS: ULONG = GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST | GAA_FLAG_SKIP_DNS_SERVER;
+
+    // Recommended initial size of the buffer is 15KB, `u64` for alignment.
+    let mut buf: Vec<u64> = vec![0; 15 * 1024 / size_of::<u64>()];
+    loop {
+        let mut size = (buf.len() * size_of::<u64>()) as ULONG;
+        let res = unsafe {
+            GetAdaptersAddresses(
+                AF_UNSPEC as ULONG,
+                FLAGS,
+                ptr::null_mut(),
+                buf.as_mut_ptr().cast(),
+                &mut size,
+            )
+        };
+        match res {
+            ERROR_SUCCESS => return Ok(buf),
+            ERROR_NO_DATA => return Ok(Vec::new()),
+            ERROR_BUFFER_OVERFLOW => {
+                let len = (size as usize + size_of::<u64>() - 1) / size_of::<u64>();
+                buf.resize(len, 0)
+            }
+            err => return Err(io::Error::from_raw_os_error(err as i32)),
+        }
+    }
+}
+
+/// Returns an iterator over the adapters in `buf`, as returned by
+/// [`adapters_addresses`].
+fn adapters(buf: &[u64]) -> impl Iterator<Item = &IP_ADAPTER_ADDRESSES_LH> {
+    let mut next = if buf.is_empty() {
+        ptr::null()
+    } else {
+        buf.as_ptr().cast::<IP_ADAPTER_ADDRESSES_LH>()
+    };
+    std::iter::from_fn(move || {
+        if next.is_null() {
+            return None;
+        }
+        // Safety: `GetAdaptersAddresses` initialised a valid linked list,
+        // which lives as long as `buf`.
+        let adapter = unsafe { &*next };
+        next = adapter.Next;
+        Some(adapter)
+    })
+}
+
+/// Converts a nul terminated wide string into a `String`.
+fn from_wide_ptr(ptr: *const u16) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+    // Safety: caller ensures `ptr` points to a nul terminated string.
+    let len = (0..).take_while(|&i| unsafe { *ptr.add(i) } != 0).count();
+    String::from_utf16_lossy(unsafe { slice::from_raw_parts(ptr, len) })
+}
+
 pub(crate) fn socket(family: c_int, mut ty: c_int, protocol: c_int) -> io::Result<Socket> {
     init();
 
     // Check if we set our custom flag.
     let flags = if ty & Type::NO_INHERIT != 0 {
-        ty = ty & !Type::NO_INHERIT;
+        ty &= !Type::NO_INHERIT;
         sock::WSA_FLAG_NO_HANDLE_INHERIT
     } else {
         0
     };
 
     syscall!(
-        WSASocketW(
+        @no_socket WSASocketW(
             family,
             ty,
             protocol,
@@ -220,9 +419,11 @@ pub(crate) fn bind(socket: Socket, addr: &SockAddr) -> io::Result<()> {
 }
 
 pub(crate) fn connect(socket: Socket, addr: &SockAddr) -> io::Result<()> {
+    syscall_span!("connect", socket, addr = ?addr);
     syscall!(connect(socket, addr.as_ptr(), addr.len()), PartialEq::ne, 0).map(|_| ())
 }
 
+#[allow(clippy::io_other_error)] // `io::Error::other` requires Rust 1.74.
 pub(crate) fn poll_connect(socket: &crate::Socket, timeout: Duration) -> io::Result<()> {
     let start = Instant::now();
 
@@ -239,10 +440,10 @@ pub(crate) fn poll_connect(socket: &crate::Socket, timeout: Duration) -> io::Res
         }
 
         let timeout = (timeout - elapsed).as_millis();
-        let timeout = clamp(timeout, 1, c_int::max_value() as u128) as c_int;
+        let timeout = clamp(timeout, 1, c_int::MAX as u128) as c_int;
 
         match syscall!(
-            WSAPoll(&mut fd_array, 1, timeout),
+            @no_socket WSAPoll(&mut fd_array, 1, timeout),
             PartialEq::eq,
             sock::SOCKET_ERROR
         ) {
@@ -270,6 +471,27 @@ pub(crate) fn poll_connect(socket: &crate::Socket, timeout: Duration) -> io::Res
     }
 }
 
+/// Returns `true` if `socket` is in the except set of `select`, which is how
+/// a failed connect is reported.
+pub(crate) fn connect_failed(socket: Socket) -> io::Result<bool> {
+    let mut except = sock::fd_set {
+        fd_count: 1,
+        fd_array: [0; sock::FD_SETSIZE],
+    };
+    except.fd_array[0] = socket;
+    // Don't block.
+    let timeout = sock::timeval {
+        tv_sec: 0,
+        tv_usec: 0,
+    };
+    syscall!(
+        @no_socket select(0, ptr::null_mut(), ptr::null_mut(), &mut except, &timeout),
+        PartialEq::eq,
+        sock::SOCKET_ERROR
+    )
+    .map(|n| n != 0)
+}
+
 // TODO: use clamp from std lib, stable since 1.50.
 fn clamp<T>(value: T, min: T, max: T) -> T
 where
@@ -330,23 +552,43 @@ pub(crate) fn getpeername(socket: Socket) -> io::Result<SockAddr> {
 }
 
 pub(crate) fn try_clone(socket: Socket) -> io::Result<Socket> {
+    duplicate(
+        socket,
+        sock::WSA_FLAG_OVERLAPPED | sock::WSA_FLAG_NO_HANDLE_INHERIT,
+    )
+}
+
+pub(crate) fn try_clone_raw(socket: Socket) -> io::Result<Socket> {
+    duplicate(socket, sock::WSA_FLAG_OVERLAPPED)
+}
+
+fn duplicate(socket: Socket, flags: DWORD) -> io::Result<Socket> {
+    let info = protocol_info(socket, unsafe { GetCurrentProcessId() })?;
+    from_protocol_info(info, flags)
+}
+
+/// Returns the protocol info needed to duplicate `socket` in the process with
+/// id `process_id`, using `WSADuplicateSocketW`.
+fn protocol_info(socket: Socket, process_id: DWORD) -> io::Result<sock::WSAPROTOCOL_INFOW> {
     let mut info: MaybeUninit<sock::WSAPROTOCOL_INFOW> = MaybeUninit::uninit();
     syscall!(
-        WSADuplicateSocketW(socket, GetCurrentProcessId(), info.as_mut_ptr()),
+        WSADuplicateSocketW(socket, process_id, info.as_mut_ptr()),
         PartialEq::eq,
         sock::SOCKET_ERROR
     )?;
     // Safety: `WSADuplicateSocketW` intialised `info` for us.
-    let mut info = unsafe { info.assume_init() };
+    Ok(unsafe { info.assume_init() })
+}
 
+fn from_protocol_info(mut info: sock::WSAPROTOCOL_INFOW, flags: DWORD) -> io::Result<Socket> {
     syscall!(
-        WSASocketW(
+        @no_socket WSASocketW(
             info.iAddressFamily,
             info.iSocketType,
             info.iProtocol,
             &mut info,
             0,
-            sock::WSA_FLAG_OVERLAPPED | sock::WSA_FLAG_NO_HANDLE_INHERIT,
+            flags,
         ),
         PartialEq::eq,
         sock::INVALID_SOCKET
@@ -358,6 +600,23 @@ pub(crate) fn set_nonblocking(socket: Socket, nonblocking: bool) -> io::Result<(
     ioctlsocket(socket, sock::FIONBIO, &mut nonblocking)
 }
 
+pub(crate) fn bytes_available(socket: Socket) -> io::Result<usize> {
+    let mut available: u_long = 0;
+    ioctlsocket(socket, sock::FIONREAD, &mut available).map(|()| available as usize)
+}
+
+pub(crate) fn peek_size(socket: Socket) -> io::Result<usize> {
+    // For datagram sockets `FIONREAD` returns the size of the next datagram.
+    bytes_available(socket)
+}
+
+pub(crate) fn at_mark(socket: Socket) -> io::Result<bool> {
+    let mut at_mark: u_long = 0;
+    // `SIOCATMARK` returns `TRUE` if *no* OOB data is waiting to be read,
+    // which is the inverse of `sockatmark(3)`.
+    ioctlsocket(socket, sock::SIOCATMARK, &mut at_mark).map(|()| at_mark == 0)
+}
+
 pub(crate) fn shutdown(socket: Socket, how: Shutdown) -> io::Result<()> {
     let how = match how {
         Shutdown::Write => SD_SEND,
@@ -368,6 +627,7 @@ pub(crate) fn shutdown(socket: Socket, how: Shutdown) -> io::Result<()> {
 }
 
 pub(crate) fn recv(socket: Socket, buf: &mut [MaybeUninit<u8>], flags: c_int) -> io::Result<usize> {
+    syscall_span!("recv", socket, len = buf.len(), flags);
     let res = syscall!(
         recv(
             socket,
@@ -380,7 +640,7 @@ pub(crate) fn recv(socket: Socket, buf: &mut [MaybeUninit<u8>], flags: c_int) ->
     );
     match res {
         Ok(n) => Ok(n as usize),
-        Err(ref err) if err.raw_os_error() == Some(sock::WSAESHUTDOWN as i32) => Ok(0),
+        Err(ref err) if err.raw_os_error() == Some(sock::WSAESHUTDOWN) => Ok(0),
         Err(err) => Err(err),
     }
 }
@@ -390,13 +650,14 @@ pub(crate) fn recv_vectored(
     bufs: &mut [crate::MaybeUninitSlice<'_>],
     flags: c_int,
 ) -> io::Result<(usize, RecvFlags)> {
+    syscall_span!("recv_vectored", socket, bufs = bufs.len(), flags);
     let mut nread = 0;
     let mut flags = flags as DWORD;
     let res = syscall!(
         WSARecv(
             socket,
             bufs.as_mut_ptr().cast(),
-            min(bufs.len(), DWORD::max_value() as usize) as DWORD,
+            min(bufs.len(), DWORD::MAX as usize) as DWORD,
             &mut nread,
             &mut flags,
             ptr::null_mut(),
@@ -407,10 +668,8 @@ pub(crate) fn recv_vectored(
     );
     match res {
         Ok(_) => Ok((nread as usize, RecvFlags(0))),
-        Err(ref err) if err.raw_os_error() == Some(sock::WSAESHUTDOWN as i32) => {
-            Ok((0, RecvFlags(0)))
-        }
-        Err(ref err) if err.raw_os_error() == Some(sock::WSAEMSGSIZE as i32) => {
+        Err(ref err) if err.raw_os_error() == Some(sock::WSAESHUTDOWN) => Ok((0, RecvFlags(0))),
+        Err(ref err) if err.raw_os_error() == Some(sock::WSAEMSGSIZE) => {
             Ok((nread as usize, RecvFlags(MSG_TRUNC)))
         }
         Err(err) => Err(err),
@@ -422,7 +681,10 @@ pub(crate) fn recv_from(
     buf: &mut [MaybeUninit<u8>],
     flags: c_int,
 ) -> io::Result<(usize, SockAddr)> {
-    // Safety: `recvfrom` initialises the `SockAddr` for us.
+    syscall_span!("recv_from", socket, len = buf.len(), flags);
+    // Safety: `recvfrom` initialises the `SockAddr` for us. On
+    // connection-oriented sockets Windows doesn't write the address at all,
+    // so we rely on `SockAddr::init` zeroing the storage.
     unsafe {
         SockAddr::init(|storage, addrlen| {
             let res = syscall!(
@@ -439,7 +701,11 @@ pub(crate) fn recv_from(
             );
             match res {
                 Ok(n) => Ok(n as usize),
-                Err(ref err) if err.raw_os_error() == Some(sock::WSAESHUTDOWN as i32) => Ok(0),
+                Err(ref err) if err.raw_os_error() == Some(sock::WSAESHUTDOWN) => {
+                    // No address was written.
+                    *addrlen = 0;
+                    Ok(0)
+                }
                 Err(err) => Err(err),
             }
         })
@@ -451,6 +717,7 @@ pub(crate) fn recv_from_vectored(
     bufs: &mut [crate::MaybeUninitSlice<'_>],
     flags: c_int,
 ) -> io::Result<(usize, RecvFlags, SockAddr)> {
+    syscall_span!("recv_from_vectored", socket, bufs = bufs.len(), flags);
     // Safety: `recvfrom` initialises the `SockAddr` for us.
     unsafe {
         SockAddr::init(|storage, addrlen| {
@@ -460,7 +727,7 @@ pub(crate) fn recv_from_vectored(
                 WSARecvFrom(
                     socket,
                     bufs.as_mut_ptr().cast(),
-                    min(bufs.len(), DWORD::max_value() as usize) as DWORD,
+                    min(bufs.len(), DWORD::MAX as usize) as DWORD,
                     &mut nread,
                     &mut flags,
                     storage.cast(),
@@ -473,10 +740,10 @@ pub(crate) fn recv_from_vectored(
             );
             match res {
                 Ok(_) => Ok((nread as usize, RecvFlags(0))),
-                Err(ref err) if err.raw_os_error() == Some(sock::WSAESHUTDOWN as i32) => {
+                Err(ref err) if err.raw_os_error() == Some(sock::WSAESHUTDOWN) => {
                     Ok((nread as usize, RecvFlags(0)))
                 }
-                Err(ref err) if err.raw_os_error() == Some(sock::WSAEMSGSIZE as i32) => {
+                Err(ref err) if err.raw_os_error() == Some(sock::WSAEMSGSIZE) => {
                     Ok((nread as usize, RecvFlags(MSG_TRUNC)))
                 }
                 Err(err) => Err(err),
@@ -487,6 +754,7 @@ pub(crate) fn recv_from_vectored(
 }
 
 pub(crate) fn send(socket: Socket, buf: &[u8], flags: c_int) -> io::Result<usize> {
+    syscall_span!("send", socket, len = buf.len(), flags);
     syscall!(
         send(
             socket,
@@ -505,6 +773,7 @@ pub(crate) fn send_vectored(
     bufs: &[IoSlice<'_>],
     flags: c_int,
 ) -> io::Result<usize> {
+    syscall_span!("send_vectored", socket, bufs = bufs.len(), flags);
     let mut nsent = 0;
     syscall!(
         WSASend(
@@ -523,7 +792,7 @@ pub(crate) fn send_vectored(
             //
             // [1] https://docs.microsoft.com/en-us/windows/win32/api/winsock2/nf-winsock2-wsasend
             bufs.as_ptr() as *mut _,
-            min(bufs.len(), DWORD::max_value() as usize) as DWORD,
+            min(bufs.len(), DWORD::MAX as usize) as DWORD,
             &mut nsent,
             flags as DWORD,
             std::ptr::null_mut(),
@@ -541,6 +810,7 @@ pub(crate) fn send_to(
     addr: &SockAddr,
     flags: c_int,
 ) -> io::Result<usize> {
+    syscall_span!("send_to", socket, len = buf.len(), addr = ?addr, flags);
     syscall!(
         sendto(
             socket,
@@ -562,6 +832,7 @@ pub(crate) fn send_to_vectored(
     addr: &SockAddr,
     flags: c_int,
 ) -> io::Result<usize> {
+    syscall_span!("send_to_vectored", socket, bufs = bufs.len(), addr = ?addr, flags);
     let mut nsent = 0;
     syscall!(
         WSASendTo(
@@ -593,7 +864,7 @@ fn from_ms(duration: DWORD) -> Option<Duration> {
     } else {
         let secs = duration / 1000;
         let nsec = (duration % 1000) * 1000000;
-        Some(Duration::new(secs as u64, nsec as u32))
+        Some(Duration::new(secs as u64, nsec))
     }
 }
 
@@ -621,19 +892,74 @@ fn into_ms(duration: Option<Duration>) -> DWORD {
         .unwrap_or(0)
 }
 
+// Not defined in winapi, values from `ws2ipdef.h`. Supported since Windows 10,
+// version 1709.
+pub(crate) const TCP_KEEPIDLE: c_int = 3;
+pub(crate) const TCP_KEEPCNT: c_int = 16;
+pub(crate) const TCP_KEEPINTVL: c_int = 17;
+
+#[cfg(feature = "all")]
+pub(crate) fn keepalive_time(socket: Socket) -> io::Result<Duration> {
+    unsafe {
+        getsockopt::<DWORD>(socket, IPPROTO_TCP, TCP_KEEPIDLE)
+            .map(|secs| Duration::from_secs(secs as u64))
+    }
+}
+
 pub(crate) fn set_tcp_keepalive(socket: Socket, keepalive: &TcpKeepalive) -> io::Result<()> {
-    let mut keepalive = tcp_keepalive {
+    match set_tcp_keepalive_native(socket, keepalive) {
+        // Older versions of Windows don't support the keepalive options, fall
+        // back to `SIO_KEEPALIVE_VALS`, which can't set the number of retries.
+        Err(err)
+            if keepalive.retries.is_none()
+                && matches!(
+                    err.raw_os_error(),
+                    Some(code) if code == WSAENOPROTOOPT as i32 || code == WSAEINVAL as i32
+                ) =>
+        {
+            set_tcp_keepalive_vals(socket, keepalive)
+        }
+        res => res,
+    }
+}
+
+fn set_tcp_keepalive_native(socket: Socket, keepalive: &TcpKeepalive) -> io::Result<()> {
+    if let Some(time) = keepalive.time {
+        unsafe { setsockopt(socket, IPPROTO_TCP, TCP_KEEPIDLE, into_secs(time))? }
+    }
+    if let Some(interval) = keepalive.interval {
+        unsafe { setsockopt(socket, IPPROTO_TCP, TCP_KEEPINTVL, into_secs(interval))? }
+    }
+    if let Some(retries) = keepalive.retries {
+        unsafe { setsockopt(socket, IPPROTO_TCP, TCP_KEEPCNT, retries as DWORD)? }
+    }
+    Ok(())
+}
+
+fn into_secs(duration: Duration) -> DWORD {
+    min(duration.as_secs(), DWORD::MAX as u64) as DWORD
+}
+
+fn set_tcp_keepalive_vals(socket: Socket, keepalive: &TcpKeepalive) -> io::Result<()> {
+    let keepalive = tcp_keepalive {
         onoff: 1,
         keepalivetime: into_ms(keepalive.time),
         keepaliveinterval: into_ms(keepalive.interval),
     };
+    unsafe { wsa_ioctl_in(socket, SIO_KEEPALIVE_VALS, &keepalive) }
+}
+
+/// Issue `WSAIoctl` with `input` as input buffer and no output buffer.
+///
+/// Caller must ensure `T` is the correct type for `control_code`.
+unsafe fn wsa_ioctl_in<T>(socket: Socket, control_code: DWORD, input: &T) -> io::Result<()> {
     let mut out = 0;
     syscall!(
         WSAIoctl(
             socket,
-            SIO_KEEPALIVE_VALS,
-            &mut keepalive as *mut _ as *mut _,
-            size_of::<tcp_keepalive>() as _,
+            control_code,
+            (input as *const T as *mut T).cast(),
+            size_of::<T>() as DWORD,
             ptr::null_mut(),
             0,
             &mut out,
@@ -650,6 +976,7 @@ pub(crate) fn set_tcp_keepalive(socket: Socket, keepalive: &TcpKeepalive) -> io:
 pub(crate) unsafe fn getsockopt<T>(socket: Socket, level: c_int, optname: c_int) -> io::Result<T> {
     let mut optval: MaybeUninit<T> = MaybeUninit::uninit();
     let mut optlen = mem::size_of::<T>() as c_int;
+    syscall_span!("getsockopt", socket, level, optname, optlen);
     syscall!(
         getsockopt(
             socket,
@@ -661,6 +988,11 @@ pub(crate) unsafe fn getsockopt<T>(socket: Socket, level: c_int, optname: c_int)
         PartialEq::eq,
         sock::SOCKET_ERROR
     )
+    .map_err(|err| {
+        #[cfg(feature = "error-context")]
+        crate::error::record_option("getsockopt", socket, level, optname, &err);
+        err
+    })
     .map(|_| {
         debug_assert_eq!(optlen as usize, mem::size_of::<T>());
         // Safety: `getsockopt` initialised `optval` for us.
@@ -675,6 +1007,13 @@ pub(crate) unsafe fn setsockopt<T>(
     optname: c_int,
     optval: T,
 ) -> io::Result<()> {
+    syscall_span!(
+        "setsockopt",
+        socket,
+        level,
+        optname,
+        optlen = mem::size_of::<T>()
+    );
     syscall!(
         setsockopt(
             socket,
@@ -686,6 +1025,11 @@ pub(crate) unsafe fn setsockopt<T>(
         PartialEq::eq,
         sock::SOCKET_ERROR
     )
+    .map_err(|err| {
+        #[cfg(feature = "error-context")]
+        crate::error::record_option("setsockopt", socket, level, optname, &err);
+        err
+    })
     .map(|_| ())
 }
 
@@ -712,6 +1056,29 @@ pub(crate) fn to_in_addr(addr: &Ipv4Addr) -> IN_ADDR {
     IN_ADDR { S_un: s_un }
 }
 
+pub(crate) fn to_mreqn(
+    multiaddr: &Ipv4Addr,
+    interface: &crate::InterfaceIndexOrAddress,
+) -> io::Result<IpMreq> {
+    // `imr_interface` can also hold an interface index (in network byte
+    // order), i.e. index 1 is the same as address `0.0.0.1`. Indices must be
+    // below 2^24, otherwise the index is interpreted as an address.
+    let interface = match interface {
+        crate::InterfaceIndexOrAddress::Index(index) if *index >= 1 << 24 => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "interface index too large",
+            ));
+        }
+        crate::InterfaceIndexOrAddress::Index(index) => Ipv4Addr::from(*index),
+        crate::InterfaceIndexOrAddress::Address(address) => *address,
+    };
+    Ok(IpMreq {
+        imr_multiaddr: to_in_addr(multiaddr),
+        imr_interface: to_in_addr(&interface),
+    })
+}
+
 pub(crate) fn from_in_addr(in_addr: IN_ADDR) -> Ipv4Addr {
     Ipv4Addr::from(unsafe { *in_addr.S_un.S_addr() }.to_ne_bytes())
 }
@@ -730,6 +1097,24 @@ pub(crate) fn from_in6_addr(addr: in6_addr) -> Ipv6Addr {
 
 /// Windows only API.
 impl crate::Socket {
+    /// Returns `true` if `HANDLE_FLAG_INHERIT` is **not** set on the socket,
+    /// using `GetHandleInformation`.
+    ///
+    /// For more information about this option, see [`set_no_inherit`].
+    ///
+    /// [`set_no_inherit`]: crate::Socket::set_no_inherit
+    #[cfg(feature = "all")]
+    pub fn no_inherit(&self) -> io::Result<bool> {
+        let mut flags = 0;
+        // NOTE: can't use `syscall!` because it expects the function in the
+        // `sock::` path.
+        if unsafe { GetHandleInformation(self.inner as HANDLE, &mut flags) } == 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(flags & winbase::HANDLE_FLAG_INHERIT == 0)
+        }
+    }
+
     /// Sets `HANDLE_FLAG_INHERIT` using `SetHandleInformation`.
     #[cfg(feature = "all")]
     pub fn set_no_inherit(&self, no_inherit: bool) -> io::Result<()> {
@@ -753,6 +1138,589 @@ impl crate::Socket {
             Ok(())
         }
     }
+
+    /// Duplicates the socket for use in the process with id `process_id`,
+    /// using `WSADuplicateSocketW`.
+    ///
+    /// This returns the `WSAPROTOCOL_INFOW` structure as bytes, which must be
+    /// passed to the target process by other means, e.g. a pipe, where
+    /// [`Socket::from_duplicated`] creates the socket. This allows handing a
+    /// socket to another process, e.g. for a graceful restart where a new
+    /// version of a server takes over the listener of the old version.
+    ///
+    /// [`Socket::from_duplicated`]: crate::Socket::from_duplicated
+    #[cfg(feature = "all")]
+    pub fn duplicate_for_process(&self, process_id: u32) -> io::Result<Vec<u8>> {
+        let info = protocol_info(self.inner, process_id)?;
+        // Safety: `WSAPROTOCOL_INFOW` is plain old data.
+        let bytes = unsafe {
+            slice::from_raw_parts(
+                (&info as *const sock::WSAPROTOCOL_INFOW).cast::<u8>(),
+                size_of::<sock::WSAPROTOCOL_INFOW>(),
+            )
+        };
+        Ok(bytes.to_vec())
+    }
+
+    /// Creates a socket from the bytes returned by
+    /// [`Socket::duplicate_for_process`] in another process, using
+    /// `WSASocketW`.
+    ///
+    /// The returned socket is not inherited by child processes. Returns an
+    /// error of kind [`io::ErrorKind::InvalidInput`] if `info` has the wrong
+    /// size.
+    ///
+    /// [`Socket::duplicate_for_process`]: crate::Socket::duplicate_for_process
+    #[cfg(feature = "all")]
+    pub fn from_duplicated(info: &[u8]) -> io::Result<crate::Socket> {
+        if info.len() != size_of::<sock::WSAPROTOCOL_INFOW>() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid duplicated socket info",
+            ));
+        }
+        // Safety: checked the size above and `WSAPROTOCOL_INFOW` is plain old
+        // data, `WSASocketW` validates the contents.
+        let info = unsafe { ptr::read_unaligned(info.as_ptr().cast()) };
+        from_protocol_info(
+            info,
+            sock::WSA_FLAG_OVERLAPPED | sock::WSA_FLAG_NO_HANDLE_INHERIT,
+        )
+        .map(|inner| crate::Socket { inner })
+    }
+
+    /// Sends this socket to the process with id `process_id` over `channel`,
+    /// e.g. a pipe, where [`Socket::receive_from_parent`] receives it.
+    ///
+    /// This is the portable way to hand a socket to another process, e.g. for
+    /// a graceful restart where a new version of a server takes over the
+    /// listener of the old version. On Windows this writes the bytes returned
+    /// by [`Socket::duplicate_for_process`] to `channel`, on Unix the channel
+    /// is a connected Unix domain socket and no process id is needed.
+    ///
+    /// [`Socket::receive_from_parent`]: crate::Socket::receive_from_parent
+    /// [`Socket::duplicate_for_process`]: crate::Socket::duplicate_for_process
+    #[cfg(feature = "all")]
+    pub fn send_to_process<W>(&self, mut channel: W, process_id: u32) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        let info = self.duplicate_for_process(process_id)?;
+        channel.write_all(&info)?;
+        channel.flush()
+    }
+
+    /// Receives a socket sent by [`Socket::send_to_process`] over `channel`,
+    /// e.g. a pipe.
+    ///
+    /// On Windows this reads the bytes for [`Socket::from_duplicated`] from
+    /// `channel`. On Unix the channel is a connected Unix domain socket.
+    ///
+    /// [`Socket::send_to_process`]: crate::Socket::send_to_process
+    /// [`Socket::from_duplicated`]: crate::Socket::from_duplicated
+    #[cfg(feature = "all")]
+    pub fn receive_from_parent<R>(mut channel: R) -> io::Result<crate::Socket>
+    where
+        R: io::Read,
+    {
+        let mut info = vec![0; size_of::<sock::WSAPROTOCOL_INFOW>()];
+        channel.read_exact(&mut info)?;
+        crate::Socket::from_duplicated(&info)
+    }
+
+    /// Set the `SIO_RCVALL` control code on this socket.
+    ///
+    /// This enables receiving all IPv4 or IPv6 packets passing through the
+    /// network interface the socket is bound to. The socket must be a raw
+    /// socket (created using [`Type::RAW`]) bound to a specific local
+    /// interface address, which requires administrator privileges.
+    ///
+    /// [`Type::RAW`]: crate::Type::RAW
+    #[cfg(feature = "all")]
+    pub fn set_recv_all(&self, mode: RcvAllMode) -> io::Result<()> {
+        let mode: RCVALL_VALUE = match mode {
+            RcvAllMode::Off => RCVALL_OFF,
+            RcvAllMode::On => RCVALL_ON,
+            RcvAllMode::SocketLevelOnly => RCVALL_SOCKETLEVELONLY,
+            RcvAllMode::IpLevel => RCVALL_IPLEVEL,
+        };
+        unsafe { wsa_ioctl_in(self.inner, SIO_RCVALL, &mode) }
+    }
+
+    /// Get the value of the `SO_CONDITIONAL_ACCEPT` option on this socket.
+    ///
+    /// For more information about this option, see [`set_conditional_accept`].
+    ///
+    /// [`set_conditional_accept`]: crate::Socket::set_conditional_accept
+    #[cfg(feature = "all")]
+    pub fn conditional_accept(&self) -> io::Result<bool> {
+        unsafe {
+            getsockopt::<c_int>(self.inner, SOL_SOCKET, sock::SO_CONDITIONAL_ACCEPT)
+                .map(|conditional_accept| conditional_accept != 0)
+        }
+    }
+
+    /// Set the value of the `SO_CONDITIONAL_ACCEPT` option on this socket.
+    ///
+    /// If enabled, incoming connections are not accepted by the stack until
+    /// the application accepts them using `WSAAccept` with a condition
+    /// function, allowing connections to be rejected before the TCP handshake
+    /// completes. This must be set before calling `listen`.
+    #[cfg(feature = "all")]
+    pub fn set_conditional_accept(&self, conditional_accept: bool) -> io::Result<()> {
+        unsafe {
+            setsockopt(
+                self.inner,
+                SOL_SOCKET,
+                sock::SO_CONDITIONAL_ACCEPT,
+                conditional_accept as c_int,
+            )
+        }
+    }
+
+    /// Enable or disable the TCP loopback fast path on this socket, using the
+    /// `SIO_LOOPBACK_FAST_PATH` control code.
+    ///
+    /// The fast path bypasses most of the TCP/IP stack for connections over
+    /// the loopback interface, improving latency and throughput. It must be
+    /// set on both the listening and connecting socket, before calling
+    /// `listen` or `connect`.
+    #[cfg(feature = "all")]
+    pub fn set_loopback_fast_path(&self, enable: bool) -> io::Result<()> {
+        let enable = enable as c_int;
+        unsafe { wsa_ioctl_in(self.inner, SIO_LOOPBACK_FAST_PATH, &enable) }
+    }
+
+    /// Set the initial retransmission timeout and the maximum number of SYN
+    /// retransmissions for connecting this socket, using the
+    /// `SIO_TCP_INITIAL_RTO` control code.
+    ///
+    /// Lowering these values makes connection attempts to unreachable hosts
+    /// fail faster. `None` uses the system default, the `rtt` is rounded down
+    /// to milliseconds and limited to `u16::MAX` milliseconds. This must be
+    /// set before calling `connect`.
+    #[cfg(feature = "all")]
+    pub fn set_tcp_initial_rto(
+        &self,
+        rtt: Option<Duration>,
+        max_syn_retransmissions: Option<u8>,
+    ) -> io::Result<()> {
+        let params = TCP_INITIAL_RTO_PARAMETERS {
+            Rtt: rtt.map_or(TCP_INITIAL_RTO_DEFAULT_RTT, |rtt| {
+                // Zero means the default, so use at least one millisecond.
+                clamp(rtt.as_millis(), 1, u16::MAX as u128) as u16
+            }),
+            MaxSynRetransmissions: max_syn_retransmissions
+                .unwrap_or(TCP_INITIAL_RTO_DEFAULT_MAX_SYN_RETRANSMISSIONS),
+        };
+        unsafe { wsa_ioctl_in(self.inner, SIO_TCP_INITIAL_RTO, &params) }
+    }
+
+    /// Associate the event object `event` with the network events `events`
+    /// (`FD_*` flags, e.g. `FD_READ | FD_CLOSE`) on this socket, using
+    /// `WSAEventSelect`.
+    ///
+    /// The event is signaled when any of the network events occur, after which
+    /// [`enum_network_events`] can be used to determine which events occurred.
+    /// Passing zero `events` cancels the association.
+    ///
+    /// Note that this automatically puts the socket in non-blocking mode.
+    ///
+    /// [`enum_network_events`]: crate::Socket::enum_network_events
+    #[cfg(feature = "all")]
+    pub fn event_select(&self, event: RawHandle, events: c_long) -> io::Result<()> {
+        syscall!(
+            WSAEventSelect(self.inner, event as HANDLE, events),
+            PartialEq::eq,
+            sock::SOCKET_ERROR
+        )
+        .map(|_| ())
+    }
+
+    /// Returns the network events that occurred since the last call, using
+    /// `WSAEnumNetworkEvents`.
+    ///
+    /// If `event` is provided the event object is reset as well.
+    ///
+    /// See [`event_select`].
+    ///
+    /// [`event_select`]: crate::Socket::event_select
+    #[cfg(feature = "all")]
+    pub fn enum_network_events(&self, event: Option<RawHandle>) -> io::Result<NetworkEvents> {
+        let event = event.map_or(ptr::null_mut(), |event| event as HANDLE);
+        let mut events: sock::WSANETWORKEVENTS = unsafe { mem::zeroed() };
+        syscall!(
+            WSAEnumNetworkEvents(self.inner, event, &mut events),
+            PartialEq::eq,
+            sock::SOCKET_ERROR
+        )
+        .map(|_| NetworkEvents {
+            events: events.lNetworkEvents,
+            errors: events.iErrorCode,
+        })
+    }
+
+    /// Number of bytes [`accept_ex`] needs at the end of the buffer to store
+    /// the local and remote address.
+    ///
+    /// [`accept_ex`]: crate::Socket::accept_ex
+    #[cfg(feature = "all")]
+    pub const ACCEPT_EX_ADDRS_LEN: usize = 2 * ACCEPT_EX_ADDR_LEN;
+
+    /// Accept a new connection on this listener into the socket `accepted`,
+    /// using `AcceptEx`.
+    ///
+    /// `buf` receives the first block of data sent by the peer, followed by
+    /// the local and remote address. The last [`ACCEPT_EX_ADDRS_LEN`] bytes
+    /// are reserved for the addresses, if `buf` is exactly that long the call
+    /// completes as soon as a connection arrives, without waiting for data.
+    ///
+    /// Returns `Some` with the number of data bytes received if the call
+    /// completed immediately, or `None` if the operation is pending and will
+    /// complete using `overlapped`. Once complete call
+    /// [`update_accept_context`] on `accepted` and use
+    /// [`accept_ex_addrs`] to get the addresses.
+    ///
+    /// # Safety
+    ///
+    /// `accepted` must be a new, unbound and unconnected socket. `buf` and
+    /// `overlapped` must remain valid (and not be moved) until the operation
+    /// completes.
+    ///
+    /// [`ACCEPT_EX_ADDRS_LEN`]: crate::Socket::ACCEPT_EX_ADDRS_LEN
+    /// [`update_accept_context`]: crate::Socket::update_accept_context
+    /// [`accept_ex_addrs`]: crate::Socket::accept_ex_addrs
+    #[cfg(feature = "all")]
+    pub unsafe fn accept_ex(
+        &self,
+        accepted: &crate::Socket,
+        buf: &mut [MaybeUninit<u8>],
+        overlapped: *mut OVERLAPPED,
+    ) -> io::Result<Option<usize>> {
+        let data_len = accept_ex_data_len(buf.len())?;
+        let accept_ex = match extension_fn::<LPFN_ACCEPTEX>(self.inner, WSAID_ACCEPTEX)? {
+            Some(accept_ex) => accept_ex,
+            None => return Err(io::Error::from_raw_os_error(WSAEOPNOTSUPP as i32)),
+        };
+        let mut received = 0;
+        let res = accept_ex(
+            self.inner,
+            accepted.inner,
+            buf.as_mut_ptr().cast(),
+            data_len,
+            ACCEPT_EX_ADDR_LEN as DWORD,
+            ACCEPT_EX_ADDR_LEN as DWORD,
+            &mut received,
+            overlapped,
+        );
+        if res != 0 {
+            Ok(Some(received as usize))
+        } else {
+            match sock::WSAGetLastError() {
+                code if code == ERROR_IO_PENDING as i32 => Ok(None),
+                code => Err(io::Error::from_raw_os_error(code)),
+            }
+        }
+    }
+
+    /// Set the `SO_UPDATE_ACCEPT_CONTEXT` option on this socket, accepted
+    /// using [`accept_ex`] on `listener`.
+    ///
+    /// This inherits the properties of the `listener` and is required before
+    /// functions such as `getpeername` or `shutdown` can be used.
+    ///
+    /// [`accept_ex`]: crate::Socket::accept_ex
+    #[cfg(feature = "all")]
+    pub fn update_accept_context(&self, listener: &crate::Socket) -> io::Result<()> {
+        unsafe {
+            setsockopt(
+                self.inner,
+                SOL_SOCKET,
+                SO_UPDATE_ACCEPT_CONTEXT,
+                listener.inner,
+            )
+        }
+    }
+
+    /// Returns the local and remote address from a buffer filled by
+    /// [`accept_ex`] on this listener, using `GetAcceptExSockaddrs`.
+    ///
+    /// # Safety
+    ///
+    /// `buf` must be the same buffer passed to [`accept_ex`], after the
+    /// operation completed successfully.
+    ///
+    /// [`accept_ex`]: crate::Socket::accept_ex
+    #[cfg(feature = "all")]
+    pub unsafe fn accept_ex_addrs(
+        &self,
+        buf: &[MaybeUninit<u8>],
+    ) -> io::Result<(SockAddr, SockAddr)> {
+        let data_len = accept_ex_data_len(buf.len())?;
+        let get_accept_ex_sockaddrs = match extension_fn::<LPFN_GETACCEPTEXSOCKADDRS>(
+            self.inner,
+            WSAID_GETACCEPTEXSOCKADDRS,
+        )? {
+            Some(get_accept_ex_sockaddrs) => get_accept_ex_sockaddrs,
+            None => return Err(io::Error::from_raw_os_error(WSAEOPNOTSUPP as i32)),
+        };
+        let mut local = ptr::null_mut();
+        let mut local_len = 0;
+        let mut remote = ptr::null_mut();
+        let mut remote_len = 0;
+        get_accept_ex_sockaddrs(
+            buf.as_ptr() as *mut _,
+            data_len,
+            ACCEPT_EX_ADDR_LEN as DWORD,
+            ACCEPT_EX_ADDR_LEN as DWORD,
+            &mut local,
+            &mut local_len,
+            &mut remote,
+            &mut remote_len,
+        );
+        Ok((
+            copy_sockaddr(local, local_len),
+            copy_sockaddr(remote, remote_len),
+        ))
+    }
+
+    /// Connect this socket to `address` using `ConnectEx`, optionally sending
+    /// `buf` once connected (e.g. as TCP Fast Open data).
+    ///
+    /// `ConnectEx` requires the socket to be bound, if it isn't it's bound to
+    /// the unspecified address of the same family as `address` first.
+    ///
+    /// Returns `Some` with the number of bytes sent if the call completed
+    /// immediately, or `None` if the operation is pending and will complete
+    /// using `overlapped`. Once complete call [`update_connect_context`].
+    ///
+    /// # Safety
+    ///
+    /// `buf` and `overlapped` must remain valid (and not be moved) until the
+    /// operation completes.
+    ///
+    /// [`update_connect_context`]: crate::Socket::update_connect_context
+    #[cfg(feature = "all")]
+    pub unsafe fn connect_ex(
+        &self,
+        address: &SockAddr,
+        buf: &[u8],
+        overlapped: *mut OVERLAPPED,
+    ) -> io::Result<Option<usize>> {
+        match getsockname(self.inner) {
+            Ok(_) => {}
+            // Not bound yet.
+            Err(ref err) if err.raw_os_error() == Some(WSAEINVAL as i32) => {
+                let unspecified: std::net::SocketAddr =
+                    if address.family() == AF_INET6 as sa_family_t {
+                        (Ipv6Addr::UNSPECIFIED, 0).into()
+                    } else {
+                        (Ipv4Addr::UNSPECIFIED, 0).into()
+                    };
+                bind(self.inner, &unspecified.into())?;
+            }
+            Err(err) => return Err(err),
+        }
+
+        let connect_ex = match extension_fn::<LPFN_CONNECTEX>(self.inner, WSAID_CONNECTEX)? {
+            Some(connect_ex) => connect_ex,
+            None => return Err(io::Error::from_raw_os_error(WSAEOPNOTSUPP as i32)),
+        };
+        let mut sent = 0;
+        let res = connect_ex(
+            self.inner,
+            address.as_ptr(),
+            address.len(),
+            buf.as_ptr() as *mut _,
+            min(buf.len(), DWORD::MAX as usize) as DWORD,
+            &mut sent,
+            overlapped,
+        );
+        if res != 0 {
+            Ok(Some(sent as usize))
+        } else {
+            match sock::WSAGetLastError() {
+                code if code == ERROR_IO_PENDING as i32 => Ok(None),
+                code => Err(io::Error::from_raw_os_error(code)),
+            }
+        }
+    }
+
+    /// Set the `SO_UPDATE_CONNECT_CONTEXT` option on this socket, connected
+    /// using [`connect_ex`].
+    ///
+    /// This is required before functions such as `getpeername` or `shutdown`
+    /// can be used.
+    ///
+    /// [`connect_ex`]: crate::Socket::connect_ex
+    #[cfg(feature = "all")]
+    pub fn update_connect_context(&self) -> io::Result<()> {
+        syscall!(
+            setsockopt(
+                self.inner,
+                SOL_SOCKET,
+                SO_UPDATE_CONNECT_CONTEXT,
+                ptr::null(),
+                0
+            ),
+            PartialEq::eq,
+            sock::SOCKET_ERROR
+        )
+        .map(|_| ())
+    }
+
+    /// Issue the `WSAIoctl` control code `control_code` on the socket.
+    ///
+    /// `input` is passed as input buffer and `output` as output buffer, the
+    /// number of bytes written to `output` is returned. This can be used for
+    /// `SIO_*` control codes this crate doesn't provide a dedicated method for.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the contents of `input` and the size of
+    /// `output` are valid for `control_code`, e.g. some control codes read or
+    /// write pointers stored in the buffers.
+    #[cfg(feature = "all")]
+    pub unsafe fn wsa_ioctl(
+        &self,
+        control_code: DWORD,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> io::Result<usize> {
+        let mut out = 0;
+        syscall!(
+            WSAIoctl(
+                self.inner,
+                control_code,
+                input.as_ptr() as *mut _,
+                min(input.len(), DWORD::MAX as usize) as DWORD,
+                output.as_mut_ptr().cast(),
+                min(output.len(), DWORD::MAX as usize) as DWORD,
+                &mut out,
+                ptr::null_mut(),
+                None,
+            ),
+            PartialEq::eq,
+            sock::SOCKET_ERROR
+        )
+        .map(|_| out as usize)
+    }
+}
+
+/// Space `AcceptEx` needs for a single address, which must be 16 bytes more
+/// than the maximum address length.
+#[cfg(feature = "all")]
+const ACCEPT_EX_ADDR_LEN: usize = size_of::<sockaddr_storage>() + 16;
+
+/// Returns the length of the data part of a buffer passed to `AcceptEx`.
+#[cfg(feature = "all")]
+fn accept_ex_data_len(buf_len: usize) -> io::Result<DWORD> {
+    match buf_len.checked_sub(2 * ACCEPT_EX_ADDR_LEN) {
+        Some(data_len) => Ok(min(data_len, DWORD::MAX as usize) as DWORD),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "buffer too small to hold the addresses",
+        )),
+    }
+}
+
+/// Loads the extension function identified by `guid` for `socket`, e.g.
+/// `ConnectEx` using `WSAID_CONNECTEX`.
+///
+/// `F` must be the matching `LPFN_*` function pointer type.
+#[cfg(feature = "all")]
+fn extension_fn<F: Default>(socket: Socket, guid: GUID) -> io::Result<F> {
+    let mut function = F::default();
+    let mut out = 0;
+    syscall!(
+        WSAIoctl(
+            socket,
+            ws2def::SIO_GET_EXTENSION_FUNCTION_POINTER,
+            &guid as *const _ as *mut _,
+            mem::size_of_val(&guid) as DWORD,
+            (&mut function as *mut F).cast(),
+            size_of::<F>() as DWORD,
+            &mut out,
+            ptr::null_mut(),
+            None,
+        ),
+        PartialEq::eq,
+        sock::SOCKET_ERROR
+    )
+    .map(|_| function)
+}
+
+/// Copies the address pointed to by `addr` of `len` bytes into a `SockAddr`.
+///
+/// Caller must ensure `addr` points to at least `len` bytes, if not null.
+#[cfg(feature = "all")]
+unsafe fn copy_sockaddr(addr: *const sockaddr, len: c_int) -> SockAddr {
+    let mut storage: sockaddr_storage = mem::zeroed();
+    if addr.is_null() {
+        return SockAddr::new(storage, 0);
+    }
+    let len = min(len.max(0) as usize, size_of::<sockaddr_storage>());
+    ptr::copy_nonoverlapping(
+        addr.cast::<u8>(),
+        (&mut storage as *mut sockaddr_storage).cast::<u8>(),
+        len,
+    );
+    SockAddr::new(storage, len as socklen_t)
+}
+
+/// Network events that occurred on a socket, returned by
+/// [`Socket::enum_network_events`].
+///
+/// [`Socket::enum_network_events`]: crate::Socket::enum_network_events
+#[cfg(feature = "all")]
+#[derive(Copy, Clone, Debug)]
+pub struct NetworkEvents {
+    events: c_long,
+    errors: [c_int; sock::FD_MAX_EVENTS],
+}
+
+#[cfg(feature = "all")]
+impl NetworkEvents {
+    /// Returns the network events (`FD_*` flags, e.g. `FD_READ`) that
+    /// occurred.
+    pub const fn events(&self) -> c_long {
+        self.events
+    }
+
+    /// Returns `true` if `event` (a single `FD_*` flag) occurred.
+    pub const fn contains(&self, event: c_long) -> bool {
+        self.events & event != 0
+    }
+
+    /// Returns the error associated with `event` (a single `FD_*` flag), if
+    /// any.
+    pub fn error(&self, event: c_long) -> Option<io::Error> {
+        let bit = event.trailing_zeros() as usize;
+        match self.errors.get(bit) {
+            Some(&errno) if self.contains(event) && errno != 0 => {
+                Some(io::Error::from_raw_os_error(errno))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Mode of the `SIO_RCVALL` control code, see [`Socket::set_recv_all`].
+///
+/// [`Socket::set_recv_all`]: crate::Socket::set_recv_all
+#[cfg(feature = "all")]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RcvAllMode {
+    /// Disable receiving all packets, `RCVALL_OFF`.
+    Off,
+    /// Receive all IP packets on the interface, `RCVALL_ON`.
+    On,
+    /// Receive all packets destined for this socket's protocol, without
+    /// enabling promiscuous mode on the interface, `RCVALL_SOCKETLEVELONLY`.
+    SocketLevelOnly,
+    /// Receive all IP packets on the interface, without enabling promiscuous
+    /// mode on the interface, `RCVALL_IPLEVEL`.
+    IpLevel,
 }
 
 impl AsRawSocket for crate::Socket {
@@ -777,7 +1745,30 @@ impl FromRawSocket for crate::Socket {
     }
 }
 
+impl AsSocket for crate::Socket {
+    fn as_socket(&self) -> BorrowedSocket<'_> {
+        // SAFETY: lifetime is bound by the socket, which owns the socket.
+        unsafe { BorrowedSocket::borrow_raw(self.as_raw_socket()) }
+    }
+}
+
+impl From<OwnedSocket> for crate::Socket {
+    fn from(socket: OwnedSocket) -> crate::Socket {
+        crate::Socket {
+            inner: socket.into_raw_socket() as Socket,
+        }
+    }
+}
+
+impl From<crate::Socket> for OwnedSocket {
+    fn from(socket: crate::Socket) -> OwnedSocket {
+        // SAFETY: `into_raw_socket` gives up ownership of the socket.
+        unsafe { OwnedSocket::from_raw_socket(socket.into_raw_socket()) }
+    }
+}
+
 #[test]
+#[allow(clippy::identity_op)] // Shows the byte positions.
 fn in_addr_convertion() {
     let ip = Ipv4Addr::new(127, 0, 0, 1);
     let raw = to_in_addr(&ip);
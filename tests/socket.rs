@@ -1,3 +1,6 @@
+// The `assert_eq!(.., true/false)` form is used deliberately for readability.
+#![allow(clippy::bool_assert_comparison)]
+
 #[cfg(all(
     feature = "all",
     any(
@@ -11,7 +14,7 @@ use std::fs::File;
 use std::io;
 #[cfg(not(target_os = "redox"))]
 use std::io::IoSlice;
-#[cfg(all(unix, feature = "all"))]
+#[cfg(unix)]
 use std::io::Read;
 use std::io::Write;
 use std::mem::MaybeUninit;
@@ -28,13 +31,14 @@ use std::net::{Ipv6Addr, SocketAddrV6};
     )
 ))]
 use std::num::NonZeroUsize;
+use std::os::raw::c_int;
 #[cfg(unix)]
-use std::os::unix::io::AsRawFd;
+use std::os::unix::io::{AsFd, AsRawFd, OwnedFd};
 #[cfg(windows)]
-use std::os::windows::io::AsRawSocket;
+use std::os::windows::io::{AsRawSocket, AsSocket, OwnedSocket};
 use std::str;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 #[cfg(all(unix, feature = "all"))]
 use std::{env, fs};
 
@@ -45,9 +49,37 @@ use winapi::um::handleapi::GetHandleInformation;
 #[cfg(windows)]
 use winapi::um::winbase::HANDLE_FLAG_INHERIT;
 
+#[cfg(all(
+    feature = "all",
+    any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_vendor = "apple",
+    )
+))]
+use socket2::Icmpv6Filter;
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "fuchsia",
+    target_os = "linux",
+    windows,
+))]
+use socket2::InterfaceIndexOrAddress;
 #[cfg(not(target_os = "redox"))]
 use socket2::MaybeUninitSlice;
-use socket2::{Domain, Protocol, SockAddr, Socket, TcpKeepalive, Type};
+#[cfg(not(target_os = "redox"))]
+use socket2::RecvFlags;
+use socket2::{getaddrinfo, AddrInfoHints};
+use socket2::{
+    Domain, ListenerOptions, Protocol, RecvOptions, SendFlags, SockAddr, Socket, TcpKeepalive,
+    Type, UdpOptions,
+};
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+use socket2::{NetlinkMessage, RdsTransport};
+#[cfg(all(feature = "all", target_os = "linux"))]
+use socket2::{TcpAoKey, TlsCryptoInfo, TlsVersion};
 
 #[test]
 fn domain_for_address() {
@@ -93,6 +125,8 @@ fn type_fmt_debug() {
         (Type::SEQPACKET, "SOCK_SEQPACKET"),
         #[cfg(all(feature = "all", not(target_os = "redox")))]
         (Type::RAW, "SOCK_RAW"),
+        #[cfg(all(feature = "all", not(any(target_os = "redox", target_os = "haiku"))))]
+        (Type::RDM, "SOCK_RDM"),
         (500.into(), "500"),
     ];
 
@@ -112,6 +146,13 @@ fn protocol_fmt_debug() {
         (Protocol::ICMPV6, "IPPROTO_ICMPV6"),
         (Protocol::TCP, "IPPROTO_TCP"),
         (Protocol::UDP, "IPPROTO_UDP"),
+        #[cfg(all(feature = "all", not(target_os = "redox")))]
+        (Protocol::RAW, "IPPROTO_RAW"),
+        #[cfg(all(
+            feature = "all",
+            any(target_os = "android", target_os = "freebsd", target_os = "linux")
+        ))]
+        (Protocol::SCTP, "IPPROTO_SCTP"),
         (500.into(), "500"),
     ];
 
@@ -124,6 +165,41 @@ fn protocol_fmt_debug() {
     }
 }
 
+#[test]
+#[cfg(all(
+    feature = "all",
+    any(target_os = "android", target_os = "freebsd", target_os = "linux")
+))]
+fn sctp() {
+    let socket = match Socket::new(Domain::IPV4, Type::STREAM, Some(Protocol::SCTP)) {
+        Ok(socket) => socket,
+        // The `sctp` kernel module is not loaded.
+        Err(ref err) if err.raw_os_error() == Some(libc::EPROTONOSUPPORT) => return,
+        Err(err) => panic!("unexpected error creating SCTP socket: {}", err),
+    };
+    assert_eq!(socket.protocol().unwrap(), Some(Protocol::SCTP));
+}
+
+#[test]
+#[cfg(all(
+    feature = "all",
+    any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "fuchsia",
+        target_os = "linux",
+    )
+))]
+fn raw_ip() {
+    let socket = match Socket::new(Domain::IPV4, Type::RAW, Some(Protocol::RAW)) {
+        Ok(socket) => socket,
+        // Creating a raw socket requires the `CAP_NET_RAW` capability.
+        Err(ref err) if err.kind() == io::ErrorKind::PermissionDenied => return,
+        Err(err) => panic!("unexpected error creating raw socket: {}", err),
+    };
+    assert_eq!(socket.r#type().unwrap(), Type::RAW);
+}
+
 #[test]
 #[cfg(all(unix, feature = "all"))]
 fn socket_address_unix() {
@@ -131,6 +207,16 @@ fn socket_address_unix() {
     let addr = SockAddr::unix(string).unwrap();
     assert!(addr.as_socket_ipv4().is_none());
     assert!(addr.as_socket_ipv6().is_none());
+    assert_eq!(addr.to_string(), string);
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    {
+        let addr = SockAddr::unix("\0abstract\n").unwrap();
+        assert_eq!(addr.to_string(), "@abstract\\n");
+    }
+
+    let (a, _) = Socket::pair(Domain::UNIX, Type::STREAM, None).unwrap();
+    assert_eq!(a.local_addr().unwrap().to_string(), "(unnamed)");
 }
 
 #[test]
@@ -142,6 +228,164 @@ fn socket_address_vsock() {
     assert_eq!(addr.vsock_address().unwrap(), (1, 9999));
 }
 
+#[test]
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+fn socket_address_netlink() {
+    let addr = SockAddr::netlink(123, 1);
+    assert_eq!(addr.family(), libc::AF_NETLINK as libc::sa_family_t);
+    assert!(addr.as_socket().is_none());
+    assert_eq!(addr.netlink_address(), Some((123, 1)));
+    assert!(SockAddr::from(any_ipv4().as_socket().unwrap())
+        .netlink_address()
+        .is_none());
+}
+
+#[test]
+fn builder() {
+    let listener = Socket::builder(Domain::IPV4, Type::STREAM)
+        .with_reuse_address(true)
+        .with_nonblocking(true)
+        .bind(&any_ipv4())
+        .unwrap();
+    assert!(listener.reuse_address().unwrap());
+    listener.listen(1).unwrap();
+    let err = listener.accept().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+    let stream = Socket::builder(Domain::IPV4, Type::STREAM)
+        .with_protocol(Protocol::TCP)
+        .with_nodelay(true)
+        .connect(&listener.local_addr().unwrap())
+        .unwrap();
+    assert!(stream.nodelay().unwrap());
+
+    // Errors include the option that failed.
+    let err = Socket::builder(Domain::IPV4, Type::DGRAM)
+        .with_only_v6(true)
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("IPV6_V6ONLY"), "{}", err);
+    // The original error is kept as source.
+    let source = std::error::Error::source(err.get_ref().unwrap())
+        .and_then(|source| source.downcast_ref::<io::Error>())
+        .unwrap();
+    assert!(source.raw_os_error().is_some());
+    assert_eq!(source.kind(), err.kind());
+}
+
+#[test]
+#[cfg(not(target_os = "redox"))]
+fn tcp_listen() {
+    let options = ListenerOptions::new().with_nonblocking(true);
+    let listener = Socket::tcp_listen(&any_ipv4(), 128, &options).unwrap();
+    assert_eq!(listener.reuse_address().unwrap(), cfg!(unix));
+    let err = listener.accept().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+    let addr = SocketAddrV6::new(Ipv6Addr::LOCALHOST, 0, 0, 0);
+    let options = ListenerOptions::new().with_only_v6(true);
+    let listener = Socket::tcp_listen(&addr.into(), 128, &options).unwrap();
+    assert!(listener.only_v6().unwrap());
+
+    let stream = Socket::new(Domain::IPV6, Type::STREAM, None).unwrap();
+    stream.connect(&listener.local_addr().unwrap()).unwrap();
+    listener.accept().unwrap();
+}
+
+#[test]
+fn udp_connect() {
+    let server = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    server.bind(&any_ipv4()).unwrap();
+    let remote = server.local_addr().unwrap();
+
+    let options = UdpOptions::new()
+        .with_recv_buffer_size(1 << 16)
+        .with_nonblocking(true);
+    let socket = Socket::udp_connect(Some(&any_ipv4()), &remote, &options).unwrap();
+    assert_eq!(socket.peer_addr().unwrap(), remote);
+    let mut buf = [MaybeUninit::new(0); 16];
+    let err = socket.recv(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+    socket.send(DATA).unwrap();
+    let mut buf = [MaybeUninit::new(0); DATA.len() + 1];
+    let (n, addr) = server.recv_from(&mut buf).unwrap();
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, DATA);
+    assert_eq!(addr, socket.local_addr().unwrap());
+}
+
+#[test]
+fn resolve() {
+    let hints = AddrInfoHints::new()
+        .with_domain(Domain::IPV4)
+        .with_type(Type::STREAM);
+    let infos = getaddrinfo(Some("127.0.0.1"), Some("8080"), &hints).unwrap();
+    assert!(!infos.is_empty());
+    for info in infos {
+        assert_eq!(info.domain(), Domain::IPV4);
+        assert_eq!(info.r#type(), Type::STREAM);
+        let want: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+        assert_eq!(info.address().as_socket(), Some(want));
+    }
+
+    let hints = AddrInfoHints::new()
+        .with_domain(Domain::IPV4)
+        .with_type(Type::DGRAM)
+        .with_passive(true);
+    let infos = getaddrinfo(None, Some("0"), &hints).unwrap();
+    let (address, domain, ty, protocol) = infos.into_iter().next().unwrap().into_parts();
+    let want = SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0));
+    assert_eq!(address.as_socket(), Some(want));
+    let socket = Socket::new(domain, ty, Some(protocol)).unwrap();
+    socket.bind(&address).unwrap();
+
+    let err = getaddrinfo(Some("local\0host"), None, &hints).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+#[cfg(not(target_os = "redox"))]
+fn interfaces() {
+    let interfaces = socket2::interfaces().unwrap();
+    let loopback = interfaces
+        .iter()
+        .find(|interface| interface.is_loopback())
+        .expect("no loopback interface");
+    assert!(!loopback.name().is_empty());
+    assert_ne!(loopback.index(), 0);
+    assert!(loopback
+        .addresses()
+        .iter()
+        .filter_map(SockAddr::as_socket)
+        .any(|addr| addr.ip().is_loopback()));
+}
+
+#[test]
+#[cfg(any(
+    windows,
+    all(
+        feature = "all",
+        any(
+            target_os = "android",
+            target_os = "freebsd",
+            target_os = "linux",
+            target_vendor = "apple",
+        )
+    )
+))]
+fn interface_mtu() {
+    let interfaces = socket2::interfaces().unwrap();
+    let loopback = interfaces
+        .iter()
+        .find(|interface| interface.is_loopback())
+        .expect("no loopback interface");
+    let mtu = socket2::interface_mtu(loopback.name()).unwrap();
+    assert!(mtu >= 576, "unexpected MTU: {}", mtu);
+
+    let err = socket2::interface_mtu("nosuchif0").unwrap_err();
+    assert!(err.kind() == io::ErrorKind::NotFound || err.raw_os_error().is_some());
+}
+
 #[test]
 fn set_nonblocking() {
     let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
@@ -217,12 +461,15 @@ pub fn assert_nonblocking<S>(_: &S, _: bool) {
 fn set_cloexec() {
     let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
     assert_close_on_exec(&socket, true);
+    assert!(socket.cloexec().unwrap());
 
     socket.set_cloexec(false).unwrap();
     assert_close_on_exec(&socket, false);
+    assert!(!socket.cloexec().unwrap());
 
     socket.set_cloexec(true).unwrap();
     assert_close_on_exec(&socket, true);
+    assert!(socket.cloexec().unwrap());
 }
 
 #[cfg(all(
@@ -244,6 +491,23 @@ fn type_cloexec() {
     assert_close_on_exec(&socket, true);
 }
 
+#[test]
+fn try_clone_raw() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+
+    let clone = socket.try_clone().unwrap();
+    #[cfg(unix)]
+    assert_close_on_exec(&clone, true);
+    #[cfg(windows)]
+    assert_flag_no_inherit(&clone, true);
+
+    let clone = socket.try_clone_raw().unwrap();
+    #[cfg(unix)]
+    assert_close_on_exec(&clone, false);
+    #[cfg(windows)]
+    assert_flag_no_inherit(&clone, false);
+}
+
 /// Assert that `CLOEXEC` is set on `socket`.
 #[cfg(unix)]
 #[track_caller]
@@ -260,12 +524,15 @@ where
 fn set_no_inherit() {
     let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
     assert_flag_no_inherit(&socket, true);
+    assert!(socket.no_inherit().unwrap());
 
     socket.set_no_inherit(false).unwrap();
     assert_flag_no_inherit(&socket, false);
+    assert!(!socket.no_inherit().unwrap());
 
     socket.set_no_inherit(true).unwrap();
     assert_flag_no_inherit(&socket, true);
+    assert!(socket.no_inherit().unwrap());
 }
 
 #[cfg(all(feature = "all", windows))]
@@ -337,6 +604,187 @@ where
 
 const DATA: &[u8] = b"hello world";
 
+#[test]
+fn connect_nonblocking() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    listener.bind(&addr.into()).unwrap();
+    listener.listen(1).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    if !socket.connect_nonblocking(&addr).unwrap() {
+        let mut connected = false;
+        for _ in 0..100 {
+            connected = socket.finish_connect().unwrap();
+            if connected {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(connected);
+    }
+    assert_eq!(socket.peer_addr().unwrap(), addr);
+    assert_nonblocking(&socket, true);
+    let _ = listener.accept().unwrap();
+}
+
+#[test]
+fn connect_nonblocking_refused() {
+    // Find a port that is not in use.
+    let closed = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    closed.bind(&addr.into()).unwrap();
+    let addr = closed.local_addr().unwrap();
+    drop(closed);
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let err = match socket.connect_nonblocking(&addr) {
+        Ok(true) => panic!("unexpected connection"),
+        Ok(false) => loop {
+            match socket.finish_connect() {
+                Ok(false) => thread::sleep(Duration::from_millis(10)),
+                Ok(true) => panic!("unexpected connection"),
+                Err(err) => break err,
+            }
+        },
+        Err(err) => err,
+    };
+    assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+}
+
+#[test]
+fn is_connected() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    listener.bind(&addr.into()).unwrap();
+    listener.listen(1).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert!(!socket.is_connected().unwrap());
+    socket.connect(&addr).unwrap();
+    assert!(socket.is_connected().unwrap());
+    let (peer, _) = listener.accept().unwrap();
+
+    // Out-of-band data is reported in the except set of `select`, same as a
+    // failed connection on Windows.
+    peer.send_out_of_band(b"!").unwrap();
+    thread::sleep(Duration::from_millis(10));
+    assert!(socket.is_connected().unwrap());
+}
+
+#[test]
+fn is_connected_nonblocking() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_ipv4()).unwrap();
+    listener.listen(1).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    // In progress, until the connection is established.
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    if !socket.connect_nonblocking(&addr).unwrap() {
+        let start = Instant::now();
+        while !socket.is_connected().unwrap() {
+            assert!(start.elapsed() < Duration::from_secs(10), "not connected");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+    assert!(socket.is_connected().unwrap());
+    drop(listener);
+
+    // Failed.
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let err = match socket.connect_nonblocking(&addr) {
+        Ok(true) => panic!("unexpected connection"),
+        Ok(false) => loop {
+            match socket.is_connected() {
+                Ok(false) => thread::sleep(Duration::from_millis(10)),
+                Ok(true) => panic!("unexpected connection"),
+                Err(err) => break err,
+            }
+        },
+        Err(err) => err,
+    };
+    assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+}
+
+#[test]
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "fuchsia",
+    target_os = "linux",
+    windows,
+))]
+fn join_multicast_v4_n() {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    let multiaddr = Ipv4Addr::new(224, 0, 0, 251);
+    let lo = socket2::interfaces()
+        .unwrap()
+        .into_iter()
+        .find(|interface| interface.is_loopback())
+        .unwrap();
+    let index = InterfaceIndexOrAddress::Index(lo.index());
+    socket.join_multicast_v4_n(&multiaddr, &index).unwrap();
+    socket.leave_multicast_v4_n(&multiaddr, &index).unwrap();
+    let address = InterfaceIndexOrAddress::Address(Ipv4Addr::LOCALHOST);
+    socket.join_multicast_v4_n(&multiaddr, &address).unwrap();
+    socket.leave_multicast_v4_n(&multiaddr, &address).unwrap();
+
+    socket.set_multicast_if_v4_n(&address).unwrap();
+    assert_eq!(socket.multicast_if_v4().unwrap(), Ipv4Addr::LOCALHOST);
+
+    // Windows passes the index as address, which limits it to 24 bits.
+    #[cfg(windows)]
+    {
+        let index = InterfaceIndexOrAddress::Index(1 << 24);
+        let err = socket.join_multicast_v4_n(&multiaddr, &index).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = socket.set_multicast_if_v4_n(&index).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
+
+#[test]
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+fn bind_address_no_port_local_addr() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket.set_bind_address_no_port(true).unwrap();
+    socket.bind(&any_ipv4()).unwrap();
+    // The port is only chosen when connecting.
+    let addr = socket.local_addr().unwrap().as_socket().unwrap();
+    assert_eq!(addr.port(), 0);
+}
+
+#[test]
+#[cfg(all(
+    feature = "all",
+    any(target_os = "android", target_os = "linux", windows)
+))]
+fn unicast_if() {
+    let lo = socket2::interfaces()
+        .unwrap()
+        .into_iter()
+        .find(|interface| interface.is_loopback())
+        .unwrap()
+        .index();
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    assert_eq!(socket.unicast_if_v4().unwrap(), 0);
+    socket.set_unicast_if_v4(lo).unwrap();
+    assert_eq!(socket.unicast_if_v4().unwrap(), lo);
+    socket.set_unicast_if_v4(0).unwrap();
+    assert_eq!(socket.unicast_if_v4().unwrap(), 0);
+
+    let socket = Socket::new(Domain::IPV6, Type::DGRAM, None).unwrap();
+    assert_eq!(socket.unicast_if_v6().unwrap(), 0);
+    socket.set_unicast_if_v6(lo).unwrap();
+    assert_eq!(socket.unicast_if_v6().unwrap(), lo);
+    socket.set_unicast_if_v6(0).unwrap();
+    assert_eq!(socket.unicast_if_v6().unwrap(), 0);
+}
+
 #[test]
 fn connect_timeout_unrouteable() {
     // This IP is unroutable, so connections should always time out.
@@ -386,235 +834,1574 @@ fn connect_timeout_valid() {
 }
 
 #[test]
-#[cfg(all(feature = "all", unix))]
-fn pair() {
-    let (mut a, mut b) = Socket::pair(Domain::UNIX, Type::STREAM, None).unwrap();
-    a.write(DATA).unwrap();
-    let mut buf = [0; DATA.len() + 1];
-    let n = b.read(&mut buf).unwrap();
-    assert_eq!(n, DATA.len());
-    assert_eq!(&buf[..n], DATA);
-}
-
-#[test]
-#[cfg(all(feature = "all", unix))]
-fn unix() {
-    let mut path = env::temp_dir();
-    path.push("socket2");
-    let _ = fs::remove_dir_all(&path);
-    fs::create_dir_all(&path).unwrap();
-    path.push("unix");
-
-    let addr = SockAddr::unix(path).unwrap();
-
-    let listener = Socket::new(Domain::UNIX, Type::STREAM, None).unwrap();
-    listener.bind(&addr).unwrap();
-    listener.listen(10).unwrap();
+fn connect_deadline() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_ipv4()).unwrap();
+    listener.listen(128).unwrap();
+    let addr = listener.local_addr().unwrap();
 
-    let mut a = Socket::new(Domain::UNIX, Type::STREAM, None).unwrap();
-    a.connect(&addr).unwrap();
-    let mut b = listener.accept().unwrap().0;
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let err = socket.connect_deadline(&addr, Instant::now()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
 
-    a.write(DATA).unwrap();
-    let mut buf = [0; DATA.len() + 1];
-    let n = b.read(&mut buf).unwrap();
-    assert_eq!(n, DATA.len());
-    assert_eq!(&buf[..n], DATA);
+    socket
+        .connect_deadline(&addr, Instant::now() + Duration::from_millis(250))
+        .unwrap();
 }
 
 #[test]
-#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
-#[ignore = "using VSOCK family requires optional kernel support (works when enabled)"]
-fn vsock() {
-    let addr = SockAddr::vsock(libc::VMADDR_CID_LOCAL, libc::VMADDR_PORT_ANY).unwrap();
+fn recv_send_deadline() {
+    let (socket_a, socket_b) = udp_pair_connected();
 
-    let listener = Socket::new(Domain::VSOCK, Type::STREAM, None).unwrap();
-    listener.bind(&addr).unwrap();
-    listener.listen(10).unwrap();
+    let mut buf = [MaybeUninit::new(0); DATA.len()];
+    let err = socket_b
+        .recv_deadline(&mut buf, Instant::now())
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+    // The previous timeout is restored.
+    let timeout = socket_b.read_timeout().unwrap();
+    let deadline = Instant::now() + Duration::from_millis(50);
+    let err = socket_b.recv_deadline(&mut buf, deadline).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    assert_eq!(socket_b.read_timeout().unwrap(), timeout);
+
+    let timeout = socket_a.write_timeout().unwrap();
+    let deadline = Instant::now() + Duration::from_secs(1);
+    let sent = socket_a.send_deadline(DATA, deadline).unwrap();
+    assert_eq!(sent, DATA.len());
+    assert_eq!(socket_a.write_timeout().unwrap(), timeout);
+    let received = socket_b.recv_deadline(&mut buf, deadline).unwrap();
+    assert_eq!(unsafe { assume_init(&buf[..received]) }, DATA);
+}
 
-    let (_, port) = listener.local_addr().unwrap().vsock_address().unwrap();
-    let addr = SockAddr::vsock(libc::VMADDR_CID_LOCAL, port).unwrap();
-    let mut a = Socket::new(Domain::VSOCK, Type::STREAM, None).unwrap();
-    a.connect(&addr).unwrap();
-    let mut b = listener.accept().unwrap().0;
+#[test]
+#[cfg(unix)]
+fn io_safety() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let raw = socket.as_raw_fd();
+    assert_eq!(socket.as_fd().as_raw_fd(), raw);
+
+    let fd: OwnedFd = socket.into();
+    assert_eq!(fd.as_raw_fd(), raw);
+    let socket = Socket::from(fd);
+    assert_eq!(socket.as_raw_fd(), raw);
+    assert_eq!(socket.nodelay().unwrap(), false);
+}
 
-    a.write(DATA).unwrap();
-    let mut buf = [0; DATA.len() + 1];
-    let n = b.read(&mut buf).unwrap();
-    assert_eq!(n, DATA.len());
-    assert_eq!(&buf[..n], DATA);
+#[test]
+#[cfg(windows)]
+fn io_safety() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let raw = socket.as_raw_socket();
+    assert_eq!(socket.as_socket().as_raw_socket(), raw);
+
+    let owned: OwnedSocket = socket.into();
+    assert_eq!(owned.as_raw_socket(), raw);
+    let socket = Socket::from(owned);
+    assert_eq!(socket.as_raw_socket(), raw);
+    assert_eq!(socket.nodelay().unwrap(), false);
 }
 
 #[test]
-fn out_of_band() {
+#[cfg(all(unix, feature = "mio"))]
+fn mio_source() {
+    use mio::{Events, Interest, Poll, Token};
+
     let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
     listener.bind(&any_ipv4()).unwrap();
     listener.listen(1).unwrap();
 
-    let sender = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
-    sender.bind(&any_ipv4()).unwrap();
+    let mut sender = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
     sender.connect(&listener.local_addr().unwrap()).unwrap();
+    let (mut receiver, _) = listener.accept().unwrap();
+    receiver.set_nonblocking(true).unwrap();
 
-    let (receiver, _) = listener.accept().unwrap();
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(8);
+    poll.registry()
+        .register(&mut receiver, Token(0), Interest::READABLE)
+        .unwrap();
 
-    sender.send(&DATA).unwrap();
+    sender.write_all(DATA).unwrap();
+    poll.poll(&mut events, Some(Duration::from_secs(1)))
+        .unwrap();
+    assert!(events
+        .iter()
+        .any(|event| event.token() == Token(0) && event.is_readable()));
 
-    const FIRST: &[u8] = b"!";
-    assert_eq!(sender.send_out_of_band(FIRST).unwrap(), FIRST.len());
-    // On macOS if no `MSG_OOB` is available it will return `EINVAL`, to prevent
-    // this from happening we'll sleep to ensure the data is present.
-    thread::sleep(Duration::from_millis(10));
+    poll.registry().deregister(&mut receiver).unwrap();
+}
 
-    let mut buf = [MaybeUninit::new(1); DATA.len() + 1];
-    let n = receiver.recv_out_of_band(&mut buf).unwrap();
-    assert_eq!(n, FIRST.len());
-    assert_eq!(unsafe { assume_init(&buf[..n]) }, FIRST);
+#[test]
+#[cfg(all(unix, feature = "tokio"))]
+fn async_socket() {
+    use socket2::AsyncSocket;
 
-    let n = receiver.recv(&mut buf).unwrap();
-    assert_eq!(n, DATA.len());
-    assert_eq!(unsafe { assume_init(&buf[..n]) }, DATA);
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_io()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let (socket_a, socket_b) = udp_pair_connected();
+        let socket_a = AsyncSocket::new(socket_a).unwrap();
+        let socket_b = AsyncSocket::new(socket_b).unwrap();
+        assert_nonblocking(socket_b.get_ref(), true);
+
+        // Nothing to receive yet.
+        let mut buf = [MaybeUninit::new(0); DATA.len() + 1];
+        let pending = std::future::poll_fn(|cx| {
+            std::task::Poll::Ready(socket_b.poll_recv(cx, &mut buf).is_pending())
+        })
+        .await;
+        assert!(pending);
+
+        assert_eq!(socket_a.send(DATA).await.unwrap(), DATA.len());
+        let n = socket_b.recv(&mut buf).await.unwrap();
+        assert_eq!(unsafe { assume_init(&buf[..n]) }, DATA);
+
+        let socket = socket_a.into_inner();
+        assert_nonblocking(&socket, true);
+    });
 }
 
 #[test]
-#[cfg(not(target_os = "redox"))]
-fn send_recv_vectored() {
-    let (socket_a, socket_b) = udp_pair_connected();
+#[cfg(feature = "tracing")]
+fn tracing_events() {
+    use std::fmt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Counts the number of events and collects the fields of all spans.
+    struct Collector {
+        events: Arc<AtomicUsize>,
+        fields: Arc<Mutex<Vec<String>>>,
+    }
 
-    let sent = socket_a
-        .send_vectored(&[
-            IoSlice::new(b"the"),
-            IoSlice::new(b"weeknight"),
-            IoSlice::new(b"would"),
-            IoSlice::new(b"yellow"),
-        ])
-        .unwrap();
-    assert_eq!(sent, 23);
+    impl Visit for &Collector {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            let field = format!("{}={:?}", field.name(), value);
+            self.fields.lock().unwrap().push(field);
+        }
+    }
+
+    impl Subscriber for Collector {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            span.record(&mut &*self);
+            Id::from_u64(1)
+        }
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, _: &Event<'_>) {
+            self.events.fetch_add(1, Ordering::Relaxed);
+        }
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let events = Arc::new(AtomicUsize::new(0));
+    let fields = Arc::new(Mutex::new(Vec::new()));
+    let subscriber = Collector {
+        events: events.clone(),
+        fields: fields.clone(),
+    };
+    tracing::subscriber::with_default(subscriber, || {
+        socket.set_nodelay(true).unwrap();
+    });
+    // A single event for `setsockopt`, within a span holding the arguments.
+    assert_eq!(events.load(Ordering::Relaxed), 1);
+    let fields = fields.lock().unwrap();
+    #[cfg(unix)]
+    assert!(fields.contains(&format!("fd={}", socket.as_raw_fd())));
+    #[cfg(windows)]
+    assert!(fields.contains(&format!("socket={}", socket.as_raw_socket())));
+    assert!(fields.contains(&"level=6".to_owned())); // `IPPROTO_TCP`.
+    assert!(fields.contains(&"optname=1".to_owned())); // `TCP_NODELAY`.
+}
+
+#[test]
+#[cfg(all(
+    feature = "all",
+    any(target_os = "android", target_os = "fuchsia", target_os = "linux")
+))]
+fn original_dst() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_ipv4()).unwrap();
+    listener.listen(1).unwrap();
+
+    let sender = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    sender.connect(&listener.local_addr().unwrap()).unwrap();
+    let (receiver, _) = listener.accept().unwrap();
+
+    // Without a NAT rule this either fails (`ENOENT`, no conntrack entry) or
+    // returns the actual destination.
+    match receiver.original_dst() {
+        Ok(addr) => assert_eq!(
+            addr.as_socket_ipv4(),
+            receiver.local_addr().unwrap().as_socket_ipv4()
+        ),
+        Err(err) => assert!(err.raw_os_error().is_some(), "{}", err),
+    }
+    // Not a IPv4 socket.
+    let socket = Socket::new(Domain::IPV6, Type::STREAM, None).unwrap();
+    assert!(socket.original_dst().is_err());
+}
+
+#[test]
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+fn original_dst_ipv6() {
+    let listener = Socket::new(Domain::IPV6, Type::STREAM, None).unwrap();
+    let addr = SocketAddrV6::new(Ipv6Addr::LOCALHOST, 0, 0, 0);
+    listener.bind(&addr.into()).unwrap();
+    listener.listen(1).unwrap();
+
+    let sender = Socket::new(Domain::IPV6, Type::STREAM, None).unwrap();
+    sender.connect(&listener.local_addr().unwrap()).unwrap();
+    let (receiver, _) = listener.accept().unwrap();
+
+    // Without a NAT rule this either fails (`ENOENT`, no conntrack entry) or
+    // returns the actual destination.
+    match receiver.original_dst_ipv6() {
+        Ok(addr) => assert_eq!(
+            addr.as_socket_ipv6(),
+            receiver.local_addr().unwrap().as_socket_ipv6()
+        ),
+        Err(err) => assert!(err.raw_os_error().is_some(), "{}", err),
+    }
+    // Not a IPv6 socket.
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert!(socket.original_dst_ipv6().is_err());
+}
+
+#[test]
+#[cfg(all(feature = "all", unix))]
+fn pair() {
+    let (mut a, mut b) = Socket::pair(Domain::UNIX, Type::STREAM, None).unwrap();
+    a.write_all(DATA).unwrap();
+    let mut buf = [0; DATA.len() + 1];
+    let n = b.read(&mut buf).unwrap();
+    assert_eq!(n, DATA.len());
+    assert_eq!(&buf[..n], DATA);
+}
+
+#[test]
+#[cfg(all(feature = "all", unix, not(target_os = "redox")))]
+fn send_recv_fd() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_ipv4()).unwrap();
+    listener.listen(1).unwrap();
+
+    let (a, b) = Socket::pair(Domain::UNIX, Type::STREAM, None).unwrap();
+    a.send_fd(listener.as_fd()).unwrap();
+    let received = b.recv_fd().unwrap();
+    assert_ne!(received.as_raw_fd(), listener.as_raw_fd());
+    assert_eq!(
+        received.local_addr().unwrap(),
+        listener.local_addr().unwrap()
+    );
+    assert!(received.cloexec().unwrap());
+
+    // No file descriptor in the message.
+    a.send(&[1]).unwrap();
+    let err = b.recv_fd().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    // Multiple file descriptors, only the first is returned.
+    let other = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    send_fds(&a, &[listener.as_raw_fd(), other.as_raw_fd()]);
+    let received = b.recv_fd().unwrap();
+    assert_eq!(
+        received.local_addr().unwrap(),
+        listener.local_addr().unwrap()
+    );
+
+    // More file descriptors than fit in the control buffer.
+    send_fds(&a, &[other.as_raw_fd(); 16]);
+    let err = b.recv_fd().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    drop(a);
+    let err = b.recv_fd().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+#[cfg(all(feature = "all", unix, not(target_os = "redox")))]
+fn send_to_process() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_ipv4()).unwrap();
+    listener.listen(1).unwrap();
+
+    let (a, b) = std::os::unix::net::UnixStream::pair().unwrap();
+    listener.send_to_process(&a).unwrap();
+    let received = Socket::receive_from_parent(&b).unwrap();
+    assert_eq!(
+        received.local_addr().unwrap(),
+        listener.local_addr().unwrap()
+    );
+}
+
+/// Sends all `fds` in a single `SCM_RIGHTS` message.
+#[cfg(all(feature = "all", unix, not(target_os = "redox")))]
+fn send_fds(socket: &Socket, fds: &[c_int]) {
+    let mut data = [0u8; 1];
+    let mut iov = libc::iovec {
+        iov_base: data.as_mut_ptr().cast(),
+        iov_len: data.len(),
+    };
+    let fds_len = std::mem::size_of_val(fds);
+    let mut control = vec![0u64; unsafe { libc::CMSG_SPACE(fds_len as _) } as usize / 8 + 1];
+    unsafe {
+        let mut msg: libc::msghdr = std::mem::zeroed();
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr().cast();
+        msg.msg_controllen = libc::CMSG_SPACE(fds_len as _) as _;
+        let cmsg = &mut *libc::CMSG_FIRSTHDR(&msg);
+        cmsg.cmsg_level = libc::SOL_SOCKET;
+        cmsg.cmsg_type = libc::SCM_RIGHTS;
+        cmsg.cmsg_len = libc::CMSG_LEN(fds_len as _) as _;
+        std::ptr::copy_nonoverlapping(fds.as_ptr().cast::<u8>(), libc::CMSG_DATA(cmsg), fds_len);
+        assert_eq!(libc::sendmsg(socket.as_raw_fd(), &msg, 0), 1);
+    }
+}
+
+#[test]
+#[cfg(all(feature = "all", windows))]
+fn duplicate_for_process() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_ipv4()).unwrap();
+    listener.listen(1).unwrap();
+
+    let info = listener.duplicate_for_process(std::process::id()).unwrap();
+    let duplicated = Socket::from_duplicated(&info).unwrap();
+    assert_eq!(
+        duplicated.local_addr().unwrap(),
+        listener.local_addr().unwrap()
+    );
+    assert!(duplicated.no_inherit().unwrap());
+
+    let err = Socket::from_duplicated(&info[1..]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+#[cfg(all(feature = "all", windows))]
+fn send_to_process() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_ipv4()).unwrap();
+    listener.listen(1).unwrap();
+
+    let mut channel = Vec::new();
+    listener
+        .send_to_process(&mut channel, std::process::id())
+        .unwrap();
+    let received = Socket::receive_from_parent(&*channel).unwrap();
+    assert_eq!(
+        received.local_addr().unwrap(),
+        listener.local_addr().unwrap()
+    );
+
+    let err = Socket::receive_from_parent(&channel[1..]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+#[cfg(all(feature = "all", unix, not(target_vendor = "apple")))]
+fn pair_seqpacket() {
+    let (a, b) = Socket::pair(Domain::UNIX, Type::SEQPACKET, None).unwrap();
+    a.send(b"hello").unwrap();
+    a.send(b"world").unwrap();
+
+    // Message boundaries are preserved.
+    let mut buf = [MaybeUninit::new(0); 16];
+    let n = b.recv(&mut buf).unwrap();
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, b"hello");
+    let n = b.recv(&mut buf).unwrap();
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, b"world");
+}
+
+#[test]
+#[cfg(all(feature = "all", unix, not(target_vendor = "apple")))]
+fn send_end_of_record() {
+    let (a, b) = Socket::pair(Domain::UNIX, Type::SEQPACKET, None).unwrap();
+    let n = a.send_with_flags(DATA, SendFlags::END_OF_RECORD).unwrap();
+    assert_eq!(n, DATA.len());
+
+    let mut buf = [MaybeUninit::new(0); 64];
+    let n = b.recv(&mut buf).unwrap();
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, DATA);
+}
+
+#[test]
+#[cfg(unix)]
+fn unix_std_conversions() {
+    use std::os::unix::net::{UnixDatagram, UnixStream};
+
+    let (a, b) = UnixStream::pair().unwrap();
+    let a = Socket::from(a);
+    let mut b = UnixStream::from(Socket::from(b));
+    (&a).write_all(DATA).unwrap();
+    let mut buf = [0; DATA.len()];
+    b.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, DATA);
+    let _: UnixStream = a.into();
+
+    let (a, b) = UnixDatagram::pair().unwrap();
+    let a = Socket::from(a);
+    let b = UnixDatagram::from(Socket::from(b));
+    a.send(DATA).unwrap();
+    let n = b.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..n], DATA);
+}
+
+#[test]
+#[cfg(all(feature = "all", unix))]
+fn unix() {
+    let mut path = env::temp_dir();
+    path.push("socket2");
+    let _ = fs::remove_dir_all(&path);
+    fs::create_dir_all(&path).unwrap();
+    path.push("unix");
+
+    let addr = SockAddr::unix(path).unwrap();
+
+    let listener = Socket::new(Domain::UNIX, Type::STREAM, None).unwrap();
+    listener.bind(&addr).unwrap();
+    listener.listen(10).unwrap();
+
+    let mut a = Socket::new(Domain::UNIX, Type::STREAM, None).unwrap();
+    a.connect(&addr).unwrap();
+    let mut b = listener.accept().unwrap().0;
+
+    a.write_all(DATA).unwrap();
+    let mut buf = [0; DATA.len() + 1];
+    let n = b.read(&mut buf).unwrap();
+    assert_eq!(n, DATA.len());
+    assert_eq!(&buf[..n], DATA);
+}
+
+#[test]
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+#[ignore = "using VSOCK family requires optional kernel support (works when enabled)"]
+fn vsock() {
+    let addr = SockAddr::vsock(libc::VMADDR_CID_LOCAL, libc::VMADDR_PORT_ANY).unwrap();
+
+    let listener = Socket::new(Domain::VSOCK, Type::STREAM, None).unwrap();
+    listener.bind(&addr).unwrap();
+    listener.listen(10).unwrap();
+
+    let (_, port) = listener.local_addr().unwrap().vsock_address().unwrap();
+    let addr = SockAddr::vsock(libc::VMADDR_CID_LOCAL, port).unwrap();
+    let mut a = Socket::new(Domain::VSOCK, Type::STREAM, None).unwrap();
+    a.connect(&addr).unwrap();
+    let mut b = listener.accept().unwrap().0;
+
+    a.write_all(DATA).unwrap();
+    let mut buf = [0; DATA.len() + 1];
+    let n = b.read(&mut buf).unwrap();
+    assert_eq!(n, DATA.len());
+    assert_eq!(&buf[..n], DATA);
+}
+
+#[test]
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+fn netlink() {
+    let socket = Socket::new(
+        Domain::NETLINK,
+        Type::DGRAM,
+        Some(Protocol::from(libc::NETLINK_ROUTE)),
+    )
+    .unwrap();
+    socket.bind(&SockAddr::netlink(0, 0)).unwrap();
+    let (pid, _) = socket.local_addr().unwrap().netlink_address().unwrap();
+    assert_ne!(pid, 0);
+    // `RTNLGRP_LINK`.
+    socket.add_netlink_membership(1).unwrap();
+    socket.drop_netlink_membership(1).unwrap();
+
+    // Dump all links, the payload is a zeroed `struct ifinfomsg`.
+    let flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+    let request = NetlinkMessage::new(libc::RTM_GETLINK, flags, 1, &[0; 16]);
+    socket.send_netlink(&request).unwrap();
+
+    let mut buf = Vec::new();
+    let mut links = 0;
+    socket
+        .recv_netlink_multipart(&mut buf, |msg| {
+            assert_eq!(msg.msg_type(), libc::RTM_NEWLINK);
+            assert_eq!(msg.seq(), 1);
+            links += 1;
+            Ok(())
+        })
+        .unwrap();
+    // At least the loopback interface.
+    assert!(links >= 1);
+}
+
+#[test]
+#[cfg(all(feature = "all", target_os = "linux"))]
+#[ignore = "creating a packet socket requires the `CAP_NET_RAW` capability (works when running as root)"]
+fn packet_membership() {
+    let socket = Socket::new(Domain::PACKET, Type::RAW, None).unwrap();
+    let lo = unsafe { libc::if_nametoindex(b"lo\0".as_ptr().cast()) };
+    assert_ne!(lo, 0);
+
+    socket.set_promiscuous(lo, true).unwrap();
+    socket.set_promiscuous(lo, false).unwrap();
+    socket.set_all_multicast(lo, true).unwrap();
+    socket.set_all_multicast(lo, false).unwrap();
+
+    let address = [0x01, 0x00, 0x5e, 0x00, 0x00, 0xfb];
+    socket.join_packet_multicast(lo, &address).unwrap();
+    socket.leave_packet_multicast(lo, &address).unwrap();
+    let err = socket.join_packet_multicast(lo, &[0; 9]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+fn rds() {
+    let socket = match Socket::new(Domain::RDS, Type::SEQPACKET, None) {
+        Ok(socket) => socket,
+        // The `rds` kernel module is not loaded.
+        Err(ref err) if err.raw_os_error() == Some(libc::EAFNOSUPPORT) => return,
+        Err(err) => panic!("unexpected error creating RDS socket: {}", err),
+    };
+    assert_eq!(socket.rds_transport().unwrap(), None);
+    socket.set_rds_transport(RdsTransport::Tcp).unwrap();
+    assert_eq!(socket.rds_transport().unwrap(), Some(RdsTransport::Tcp));
+    socket.set_rds_recverr(true).unwrap();
+    socket.set_rds_cong_monitor(true).unwrap();
+    assert_eq!(socket.rds_tos().unwrap(), 0);
+    socket.set_rds_tos(1).unwrap();
+    assert_eq!(socket.rds_tos().unwrap(), 1);
+}
+
+#[test]
+#[cfg(all(feature = "all", target_os = "linux"))]
+fn ktls() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_ipv4()).unwrap();
+    listener.listen(1).unwrap();
+    let sender = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    sender.connect(&listener.local_addr().unwrap()).unwrap();
+    let (receiver, _) = listener.accept().unwrap();
+
+    match sender.set_tcp_ulp("tls") {
+        Ok(()) => {}
+        // The `tls` kernel module is not available.
+        Err(err) if err.raw_os_error() == Some(libc::ENOENT) => return,
+        Err(err) => panic!("unexpected error: {}", err),
+    }
+    receiver.set_tcp_ulp("tls").unwrap();
+
+    let info = TlsCryptoInfo::Aes128Gcm {
+        version: TlsVersion::Tls12,
+        iv: [1; 8],
+        key: [2; 16],
+        salt: [3; 4],
+        rec_seq: [0; 8],
+    };
+    sender.set_tls_tx(&info).unwrap();
+    receiver.set_tls_rx(&info).unwrap();
+
+    sender.send(DATA).unwrap();
+    let mut buf = [MaybeUninit::new(0); DATA.len()];
+    assert_eq!(receiver.recv_exact(&mut buf).unwrap(), DATA.len());
+    assert_eq!(unsafe { assume_init(&buf) }, DATA);
+}
+
+#[test]
+#[cfg(all(feature = "all", target_os = "linux"))]
+fn tcp_ao_key() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    // The port is ignored.
+    let peer: SockAddr = "127.0.0.1:8080".parse().unwrap();
+
+    let err = socket
+        .add_tcp_ao_key(&TcpAoKey::new(&peer, "hmac(sha1)", &[0; 81], 1, 1))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+    let key = TcpAoKey::new(&peer, "hmac(sha1)", b"secret", 1, 2);
+    match socket.add_tcp_ao_key(&key) {
+        Ok(()) => {}
+        // Kernel without TCP-AO support (before 6.7 or without `CONFIG_TCP_AO`).
+        Err(ref err) if err.raw_os_error() == Some(libc::ENOPROTOOPT) => return,
+        Err(err) => panic!("unexpected error adding TCP-AO key: {}", err),
+    }
+    socket.delete_tcp_ao_key(&key).unwrap();
+}
+
+#[test]
+fn out_of_band() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_ipv4()).unwrap();
+    listener.listen(1).unwrap();
+
+    let sender = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    sender.bind(&any_ipv4()).unwrap();
+    sender.connect(&listener.local_addr().unwrap()).unwrap();
+
+    let (receiver, _) = listener.accept().unwrap();
+
+    sender.send(DATA).unwrap();
+
+    const FIRST: &[u8] = b"!";
+    assert_eq!(sender.send_out_of_band(FIRST).unwrap(), FIRST.len());
+    // On macOS if no `MSG_OOB` is available it will return `EINVAL`, to prevent
+    // this from happening we'll sleep to ensure the data is present.
+    thread::sleep(Duration::from_millis(10));
+
+    let mut buf = [MaybeUninit::new(1); DATA.len() + 1];
+    let n = receiver.recv_out_of_band(&mut buf).unwrap();
+    assert_eq!(n, FIRST.len());
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, FIRST);
+
+    let n = receiver.recv(&mut buf).unwrap();
+    assert_eq!(n, DATA.len());
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, DATA);
+}
+
+#[test]
+fn recv_exact() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_ipv4()).unwrap();
+    listener.listen(1).unwrap();
+
+    let sender = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    sender.connect(&listener.local_addr().unwrap()).unwrap();
+    let (receiver, _) = listener.accept().unwrap();
+
+    let handle = thread::spawn(move || {
+        for chunk in DATA.chunks(4) {
+            sender.send(chunk).unwrap();
+            thread::sleep(Duration::from_millis(2));
+        }
+        sender
+    });
+
+    let mut buf = [MaybeUninit::new(0); DATA.len()];
+    assert_eq!(receiver.recv_exact(&mut buf).unwrap(), DATA.len());
+    assert_eq!(unsafe { assume_init(&buf) }, DATA);
+
+    // Closing the connection after part of the buffer is filled returns the
+    // number of bytes received.
+    let sender = handle.join().unwrap();
+    sender.send(&DATA[..4]).unwrap();
+    drop(sender);
+    assert_eq!(receiver.recv_exact(&mut buf).unwrap(), 4);
+    assert_eq!(unsafe { assume_init(&buf[..4]) }, &DATA[..4]);
+
+    // Closing the connection before receiving anything is an error.
+    let err = receiver.recv_exact(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn recv_exact_nonblocking() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_ipv4()).unwrap();
+    listener.listen(1).unwrap();
+
+    let sender = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    sender.connect(&listener.local_addr().unwrap()).unwrap();
+    let (receiver, _) = listener.accept().unwrap();
+    receiver.set_nonblocking(true).unwrap();
+
+    sender.send(DATA).unwrap();
+    // Give the data some time to arrive.
+    thread::sleep(Duration::from_millis(10));
+    let mut buf = [MaybeUninit::new(0); DATA.len()];
+    assert_eq!(receiver.recv_exact(&mut buf).unwrap(), DATA.len());
+    assert_eq!(unsafe { assume_init(&buf) }, DATA);
+
+    let err = receiver.recv_exact(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+    // Only part of the frame is available, the rest can be received later.
+    sender.send(&DATA[..4]).unwrap();
+    thread::sleep(Duration::from_millis(10));
+    assert_eq!(receiver.recv_exact(&mut buf).unwrap(), 4);
+    sender.send(&DATA[4..]).unwrap();
+    thread::sleep(Duration::from_millis(10));
+    let n = receiver.recv_exact(&mut buf[4..]).unwrap();
+    assert_eq!(n, DATA.len() - 4);
+    assert_eq!(unsafe { assume_init(&buf) }, DATA);
+}
+
+#[test]
+#[cfg(not(target_os = "redox"))]
+fn send_all() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_ipv4()).unwrap();
+    listener.listen(1).unwrap();
+
+    let sender = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    sender.connect(&listener.local_addr().unwrap()).unwrap();
+    let (receiver, _) = listener.accept().unwrap();
+    // Use a small send buffer to force short writes.
+    sender.set_send_buffer_size(4096).unwrap();
+
+    let data: Vec<u8> = (0..1024 * 1024).map(|i| i as u8).collect();
+    let expected = data.clone();
+    let handle = thread::spawn(move || {
+        let (head, tail) = data.split_at(1000);
+        sender.send_all(head).unwrap();
+        sender
+            .send_all_vectored(&[IoSlice::new(&[]), IoSlice::new(tail), IoSlice::new(&[])])
+            .unwrap();
+    });
+
+    let mut buf = vec![MaybeUninit::new(0); expected.len()];
+    assert_eq!(receiver.recv_exact(&mut buf).unwrap(), expected.len());
+    assert!(unsafe { assume_init(&buf) } == &*expected);
+    handle.join().unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+fn at_mark() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_ipv4()).unwrap();
+    listener.listen(1).unwrap();
+
+    let sender = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    sender.connect(&listener.local_addr().unwrap()).unwrap();
+    let (receiver, _) = listener.accept().unwrap();
+    receiver.set_out_of_band_inline(true).unwrap();
+
+    sender.send(DATA).unwrap();
+    sender.send_out_of_band(b"!").unwrap();
+    // Give the data some time to arrive.
+    thread::sleep(Duration::from_millis(10));
+    assert_eq!(receiver.at_mark().unwrap(), false);
+
+    // Reading stops at the mark.
+    let mut buf = [MaybeUninit::new(0); DATA.len() + 1];
+    let n = receiver.recv(&mut buf).unwrap();
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, DATA);
+    assert_eq!(receiver.at_mark().unwrap(), true);
+
+    let n = receiver.recv(&mut buf).unwrap();
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, b"!");
+    assert_eq!(receiver.at_mark().unwrap(), false);
+}
+
+#[test]
+fn at_mark_out_of_band_only() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_ipv4()).unwrap();
+    listener.listen(1).unwrap();
+
+    let sender = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    sender.connect(&listener.local_addr().unwrap()).unwrap();
+    let (receiver, _) = listener.accept().unwrap();
+    receiver.set_out_of_band_inline(true).unwrap();
+    assert_eq!(receiver.at_mark().unwrap(), false);
+
+    // Without any data preceding the OOB data all OSs agree.
+    sender.send_out_of_band(b"!").unwrap();
+    // Give the data some time to arrive.
+    thread::sleep(Duration::from_millis(10));
+    assert_eq!(receiver.at_mark().unwrap(), true);
+
+    let mut buf = [MaybeUninit::new(0); 2];
+    let n = receiver.recv(&mut buf).unwrap();
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, b"!");
+    assert_eq!(receiver.at_mark().unwrap(), false);
+}
+
+#[test]
+#[cfg(not(target_os = "redox"))]
+fn send_recv_vectored() {
+    let (socket_a, socket_b) = udp_pair_connected();
+
+    let sent = socket_a
+        .send_vectored(&[
+            IoSlice::new(b"the"),
+            IoSlice::new(b"weeknight"),
+            IoSlice::new(b"would"),
+            IoSlice::new(b"yellow"),
+        ])
+        .unwrap();
+    assert_eq!(sent, 23);
+
+    let mut the = [MaybeUninit::new(1); 3];
+    let mut wee = [MaybeUninit::new(2); 3];
+    let mut knight = [MaybeUninit::new(3); 6];
+    let mut would = [MaybeUninit::new(4); 5];
+    let mut yell = [MaybeUninit::new(5); 4];
+    let mut ow = [MaybeUninit::new(6); 2];
+
+    let (received, flags) = socket_b
+        .recv_vectored(&mut [
+            MaybeUninitSlice::new(&mut the),
+            MaybeUninitSlice::new(&mut wee),
+            MaybeUninitSlice::new(&mut knight),
+            MaybeUninitSlice::new(&mut would),
+            MaybeUninitSlice::new(&mut yell),
+            MaybeUninitSlice::new(&mut ow),
+        ])
+        .unwrap();
+    assert_eq!(received, 23);
+    #[cfg(all(unix, not(target_os = "redox")))]
+    assert_eq!(flags.is_end_of_record(), false);
+    #[cfg(all(unix, not(target_os = "redox")))]
+    assert_eq!(flags.is_out_of_band(), false);
+    assert_eq!(flags.is_truncated(), false);
+
+    assert_eq!(unsafe { assume_init(&the) }, b"the");
+    assert_eq!(unsafe { assume_init(&wee) }, b"wee");
+    assert_eq!(unsafe { assume_init(&knight) }, b"knight");
+    assert_eq!(unsafe { assume_init(&would) }, b"would");
+    assert_eq!(unsafe { assume_init(&yell) }, b"yell");
+    assert_eq!(unsafe { assume_init(&ow) }, b"ow");
+}
+
+#[test]
+#[cfg(not(target_os = "redox"))]
+fn send_from_recv_to_vectored() {
+    let (socket_a, socket_b) = udp_pair_unconnected();
+    let addr_a = socket_a.local_addr().unwrap();
+    let addr_b = socket_b.local_addr().unwrap();
+
+    let sent = socket_a
+        .send_to_vectored(
+            &[
+                IoSlice::new(b"surgeon"),
+                IoSlice::new(b"has"),
+                IoSlice::new(b"menswear"),
+            ],
+            &addr_b,
+        )
+        .unwrap();
+    assert_eq!(sent, 18);
+
+    let mut surgeon = [MaybeUninit::new(10); 7];
+    let mut has = [MaybeUninit::new(11); 3];
+    let mut men = [MaybeUninit::new(12); 3];
+    let mut swear = [MaybeUninit::new(13); 5];
+    let (received, flags, addr) = socket_b
+        .recv_from_vectored(&mut [
+            MaybeUninitSlice::new(&mut surgeon),
+            MaybeUninitSlice::new(&mut has),
+            MaybeUninitSlice::new(&mut men),
+            MaybeUninitSlice::new(&mut swear),
+        ])
+        .unwrap();
+
+    assert_eq!(received, 18);
+    #[cfg(all(unix, not(target_os = "redox")))]
+    assert_eq!(flags.is_end_of_record(), false);
+    #[cfg(all(unix, not(target_os = "redox")))]
+    assert_eq!(flags.is_out_of_band(), false);
+    assert_eq!(flags.is_truncated(), false);
+    assert_eq!(
+        addr.as_socket_ipv6().unwrap(),
+        addr_a.as_socket_ipv6().unwrap()
+    );
+
+    assert_eq!(unsafe { assume_init(&surgeon) }, b"surgeon");
+    assert_eq!(unsafe { assume_init(&has) }, b"has");
+    assert_eq!(unsafe { assume_init(&men) }, b"men");
+    assert_eq!(unsafe { assume_init(&swear) }, b"swear");
+}
+
+#[test]
+#[cfg(not(target_os = "redox"))]
+fn recv_vectored_truncated() {
+    let (socket_a, socket_b) = udp_pair_connected();
+
+    let sent = socket_a
+        .send(b"do not feed the gremlins after midnight")
+        .unwrap();
+    assert_eq!(sent, 39);
+
+    let mut buffer = [MaybeUninit::new(20); 24];
+
+    let (received, flags) = socket_b
+        .recv_vectored(&mut [MaybeUninitSlice::new(&mut buffer)])
+        .unwrap();
+    assert_eq!(received, 24);
+    assert_eq!(flags.is_truncated(), true);
+    assert_eq!(unsafe { assume_init(&buffer) }, b"do not feed the gremlins");
+}
+
+#[test]
+#[cfg(all(
+    feature = "all",
+    any(target_os = "android", target_os = "fuchsia", target_os = "linux")
+))]
+fn recv_trunc() {
+    let (socket_a, socket_b) = udp_pair_connected();
+
+    let sent = socket_a
+        .send(b"do not feed the gremlins after midnight")
+        .unwrap();
+    assert_eq!(sent, 39);
+
+    let mut buffer = [MaybeUninit::new(20); 24];
+    let (received, flags) = socket_b.recv_trunc(&mut buffer).unwrap();
+    assert_eq!(received, 39);
+    assert_eq!(flags.is_truncated(), true);
+    assert_eq!(unsafe { assume_init(&buffer) }, b"do not feed the gremlins");
+
+    socket_a.send(b"gremlins").unwrap();
+    let (received, flags) = socket_b.recv_trunc(&mut buffer).unwrap();
+    assert_eq!(received, 8);
+    assert_eq!(flags.is_truncated(), false);
+    assert_eq!(unsafe { assume_init(&buffer[..received]) }, b"gremlins");
+}
+
+#[test]
+#[cfg(all(feature = "all", target_os = "linux"))]
+fn prepared_op() {
+    use std::os::unix::io::FromRawFd;
+
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_ipv4()).unwrap();
+    listener.listen(1).unwrap();
+    let listener_addr = listener.local_addr().unwrap();
+
+    // Perform the operations using the prepared arguments, as a completion
+    // based API would.
+    let sender = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let op = sender.prepare_connect(&listener_addr);
+    assert_eq!(op.fd(), sender.as_raw_fd());
+    assert!(op.buf_ptr().is_null());
+    assert_eq!(op.addr_len(), listener_addr.len());
+    let res = unsafe { libc::connect(op.fd(), op.addr_ptr(), op.addr_len()) };
+    assert_eq!(res, 0);
+
+    let mut peer_addr = SockAddr::from(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)));
+    let op = listener.prepare_accept(&mut peer_addr);
+    let fd = unsafe { libc::accept(op.fd(), op.addr_ptr(), op.addr_len_ptr()) };
+    assert!(fd >= 0);
+    let receiver = unsafe { Socket::from_raw_fd(fd) };
+    assert_eq!(
+        peer_addr.as_socket(),
+        sender.local_addr().unwrap().as_socket()
+    );
+
+    let op = sender.prepare_send(DATA);
+    assert!(op.addr_ptr().is_null());
+    assert_eq!(op.addr_len(), 0);
+    let n = unsafe { libc::send(op.fd(), op.buf_ptr().cast(), op.buf_len(), 0) };
+    assert_eq!(n, DATA.len() as isize);
+
+    let mut buf = [MaybeUninit::new(0); DATA.len()];
+    let op = receiver.prepare_recv(&mut buf);
+    let n = unsafe { libc::recv(op.fd(), op.buf_ptr().cast(), op.buf_len(), 0) };
+    assert_eq!(n, DATA.len() as isize);
+    assert_eq!(unsafe { assume_init(&buf) }, DATA);
+}
+
+#[test]
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+fn recv_error() {
+    // Find a port that is not in use.
+    let closed = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    closed.bind(&any_ipv4()).unwrap();
+    let closed_addr = closed.local_addr().unwrap();
+    drop(closed);
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    assert_eq!(socket.recverr().unwrap(), false);
+    socket.set_recverr(true).unwrap();
+    assert_eq!(socket.recverr().unwrap(), true);
+
+    let mut buf = [MaybeUninit::new(0); DATA.len()];
+    let err = socket.recv_error(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+    socket.send_to(DATA, &closed_addr).unwrap();
+    // Give the ICMP error some time to arrive.
+    thread::sleep(Duration::from_millis(10));
+
+    let (n, err) = socket.recv_error(&mut buf).unwrap();
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, DATA);
+    let err = err.unwrap();
+    assert_eq!(err.error().raw_os_error(), Some(libc::ECONNREFUSED));
+    assert_eq!(err.origin(), libc::SO_EE_ORIGIN_ICMP);
+    assert_eq!(err.r#type(), 3); // Destination unreachable.
+    assert_eq!(err.code(), 3); // Port unreachable.
+    assert_eq!(
+        err.offender()
+            .and_then(SockAddr::as_socket_ipv4)
+            .map(|a| *a.ip()),
+        Some(Ipv4Addr::LOCALHOST)
+    );
+}
+
+#[test]
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+fn recv_from_with_ttl() {
+    let socket_a = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket_a.bind(&any_ipv4()).unwrap();
+    let socket_b = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket_b.bind(&any_ipv4()).unwrap();
+    let addr_b = socket_b.local_addr().unwrap();
+    socket_a.set_ttl(42).unwrap();
+
+    let mut buf = [MaybeUninit::new(0); DATA.len()];
+    socket_a.send_to(DATA, &addr_b).unwrap();
+    let (n, addr, ttl) = socket_b.recv_from_with_ttl(&mut buf).unwrap();
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, DATA);
+    assert_eq!(addr, socket_a.local_addr().unwrap());
+    assert_eq!(ttl, None);
+
+    assert_eq!(socket_b.recvttl().unwrap(), false);
+    socket_b.set_recvttl(true).unwrap();
+    assert_eq!(socket_b.recvttl().unwrap(), true);
+
+    socket_a.send_to(DATA, &addr_b).unwrap();
+    let (n, _, ttl) = socket_b.recv_from_with_ttl(&mut buf).unwrap();
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, DATA);
+    assert_eq!(ttl, Some(42));
+}
+
+#[test]
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+fn recv_from_with_hoplimit() {
+    let (socket_a, socket_b) = udp_pair_unconnected();
+    let addr_b = socket_b.local_addr().unwrap();
+    socket_a.set_unicast_hops_v6(42).unwrap();
+
+    assert_eq!(socket_b.recvhoplimit_v6().unwrap(), false);
+    socket_b.set_recvhoplimit_v6(true).unwrap();
+    assert_eq!(socket_b.recvhoplimit_v6().unwrap(), true);
+
+    let mut buf = [MaybeUninit::new(0); DATA.len()];
+    socket_a.send_to(DATA, &addr_b).unwrap();
+    let (n, addr, hoplimit) = socket_b.recv_from_with_ttl(&mut buf).unwrap();
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, DATA);
+    assert_eq!(addr, socket_a.local_addr().unwrap());
+    assert_eq!(hoplimit, Some(42));
+}
+
+#[test]
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+fn flow_label() {
+    let (socket_a, socket_b) = udp_pair_unconnected();
+    let addr_b = socket_b.local_addr().unwrap();
+
+    assert_eq!(socket_a.flowinfo_send_v6().unwrap(), false);
+    socket_a.set_flowinfo_send_v6(true).unwrap();
+    assert_eq!(socket_a.flowinfo_send_v6().unwrap(), true);
+    let label = socket_a
+        .request_flow_label_v6(&Ipv6Addr::LOCALHOST, 0)
+        .unwrap();
+    assert_ne!(label, 0);
+
+    assert_eq!(socket_b.recvflowinfo_v6().unwrap(), false);
+    socket_b.set_recvflowinfo_v6(true).unwrap();
+    assert_eq!(socket_b.recvflowinfo_v6().unwrap(), true);
+
+    let addr_b = addr_b.as_socket_ipv6().unwrap();
+    // `sin6_flowinfo` is stored in network byte order.
+    let addr_b = SocketAddrV6::new(*addr_b.ip(), addr_b.port(), label.to_be(), 0);
+    let mut buf = [MaybeUninit::new(0); DATA.len()];
+    socket_a.send_to(DATA, &addr_b.into()).unwrap();
+    let (n, _, flowinfo) = socket_b.recv_from_with_flowinfo_v6(&mut buf).unwrap();
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, DATA);
+    assert_eq!(flowinfo.map(|flowinfo| flowinfo & 0xfffff), Some(label));
+
+    socket_a
+        .release_flow_label_v6(&Ipv6Addr::LOCALHOST, label)
+        .unwrap();
+}
+
+#[test]
+#[cfg(not(target_os = "redox"))]
+fn recv_from_vectored_truncated() {
+    let (socket_a, socket_b) = udp_pair_unconnected();
+    let addr_a = socket_a.local_addr().unwrap();
+    let addr_b = socket_b.local_addr().unwrap();
+
+    let sent = socket_a
+        .send_to(b"do not feed the gremlins after midnight", &addr_b)
+        .unwrap();
+    assert_eq!(sent, 39);
+
+    let mut buffer = [MaybeUninit::new(30); 24];
+
+    let (received, flags, addr) = socket_b
+        .recv_from_vectored(&mut [MaybeUninitSlice::new(&mut buffer)])
+        .unwrap();
+    assert_eq!(received, 24);
+    assert_eq!(flags.is_truncated(), true);
+    assert_eq!(
+        addr.as_socket_ipv6().unwrap(),
+        addr_a.as_socket_ipv6().unwrap()
+    );
+    assert_eq!(unsafe { assume_init(&buffer) }, b"do not feed the gremlins");
+}
+
+#[test]
+#[cfg(not(target_os = "redox"))]
+fn peek_from_vectored() {
+    let (socket_a, socket_b) = udp_pair_unconnected();
+    let addr_a = socket_a.local_addr().unwrap();
+    let addr_b = socket_b.local_addr().unwrap();
+
+    let sent = socket_a.send_to(b"headerbody", &addr_b).unwrap();
+    assert_eq!(sent, 10);
+
+    let mut header = [MaybeUninit::new(0); 6];
+    let mut body = [MaybeUninit::new(0); 16];
+    let (peeked, flags, addr) = socket_b
+        .peek_from_vectored(&mut [
+            MaybeUninitSlice::new(&mut header),
+            MaybeUninitSlice::new(&mut body),
+        ])
+        .unwrap();
+    assert_eq!(peeked, 10);
+    assert_eq!(flags.is_truncated(), false);
+    assert_eq!(addr.as_socket(), addr_a.as_socket());
+    assert_eq!(unsafe { assume_init(&header) }, b"header");
+    assert_eq!(unsafe { assume_init(&body[..4]) }, b"body");
+
+    // The data should still be in the queue.
+    let mut buf = [MaybeUninit::new(0); 16];
+    let (received, addr) = socket_b.recv_from(&mut buf).unwrap();
+    assert_eq!(received, 10);
+    assert_eq!(addr.as_socket(), addr_a.as_socket());
+    assert_eq!(unsafe { assume_init(&buf[..received]) }, b"headerbody");
+}
+
+#[test]
+fn bytes_available() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_ipv4()).unwrap();
+    listener.listen(1).unwrap();
+
+    let sender = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    sender.connect(&listener.local_addr().unwrap()).unwrap();
+    let (receiver, _) = listener.accept().unwrap();
+    assert_eq!(receiver.bytes_available().unwrap(), 0);
+
+    sender.send(DATA).unwrap();
+    // Give the data some time to arrive.
+    thread::sleep(Duration::from_millis(10));
+    assert_eq!(receiver.bytes_available().unwrap(), DATA.len());
+
+    let mut buf = [MaybeUninit::new(0); DATA.len()];
+    receiver.recv(&mut buf).unwrap();
+    assert_eq!(receiver.bytes_available().unwrap(), 0);
+}
+
+#[test]
+fn peek_size() {
+    let (socket_a, socket_b) = udp_pair_connected();
+
+    // Only queue a single datagram, as on the BSDs `peek_size` returns the
+    // size of all queued datagrams.
+    socket_a.send(DATA).unwrap();
+    // Give the data some time to arrive.
+    thread::sleep(Duration::from_millis(10));
+    assert_eq!(socket_b.peek_size().unwrap(), DATA.len());
+
+    let mut buf = [MaybeUninit::new(0); DATA.len()];
+    let n = socket_b.recv(&mut buf).unwrap();
+    assert_eq!(n, DATA.len());
+}
+
+#[test]
+#[cfg(any(
+    windows,
+    target_os = "android",
+    target_os = "fuchsia",
+    target_os = "linux",
+    target_vendor = "apple",
+))]
+fn peek_size_next_datagram() {
+    let (socket_a, socket_b) = udp_pair_connected();
+
+    socket_a.send(DATA).unwrap();
+    socket_a.send(&DATA[..4]).unwrap();
+    thread::sleep(Duration::from_millis(10));
+    // Only the size of the next datagram is returned.
+    assert_eq!(socket_b.peek_size().unwrap(), DATA.len());
+
+    let mut buf = [MaybeUninit::new(0); DATA.len()];
+    let n = socket_b.recv(&mut buf).unwrap();
+    assert_eq!(n, DATA.len());
+    assert_eq!(socket_b.peek_size().unwrap(), 4);
+}
+
+#[test]
+#[cfg(all(unix, feature = "all"))]
+fn ioctl() {
+    let (socket_a, socket_b) = udp_pair_connected();
+    socket_a.send(DATA).unwrap();
+    thread::sleep(Duration::from_millis(10));
+
+    let mut available: c_int = 0;
+    unsafe { socket_b.ioctl(libc::FIONREAD as _, &mut available) }.unwrap();
+    assert_eq!(available as usize, DATA.len());
+}
+
+#[test]
+#[cfg(all(windows, feature = "all"))]
+fn wsa_ioctl() {
+    use std::mem::size_of;
+
+    use winapi::shared::mstcpip::{tcp_keepalive, SIO_KEEPALIVE_VALS};
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let keepalive = tcp_keepalive {
+        onoff: 1,
+        keepalivetime: 10_000,
+        keepaliveinterval: 1_000,
+    };
+    let input = unsafe {
+        std::slice::from_raw_parts(
+            (&keepalive as *const tcp_keepalive).cast::<u8>(),
+            size_of::<tcp_keepalive>(),
+        )
+    };
+    let n = unsafe { socket.wsa_ioctl(SIO_KEEPALIVE_VALS, input, &mut []) }.unwrap();
+    assert_eq!(n, 0);
+    assert!(socket.keepalive().unwrap());
+}
+
+#[test]
+#[cfg(all(windows, feature = "all"))]
+#[ignore = "`SIO_RCVALL` requires administrator privileges"]
+fn set_recv_all() {
+    use socket2::RcvAllMode;
+
+    let socket = Socket::new(Domain::IPV4, Type::RAW, Some(Protocol::from(0))).unwrap();
+    // Needs to be bound to a specific interface, not the unspecified address.
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    socket.bind(&addr.into()).unwrap();
+    socket.set_recv_all(RcvAllMode::IpLevel).unwrap();
+    socket.set_recv_all(RcvAllMode::Off).unwrap();
+}
+
+#[test]
+#[cfg(all(windows, feature = "all"))]
+fn event_select() {
+    use winapi::um::winsock2::{WSACloseEvent, WSACreateEvent, FD_CLOSE, FD_READ, FD_WRITE};
+
+    let (socket_a, socket_b) = udp_pair_connected();
+    let event = unsafe { WSACreateEvent() };
+    assert!(!event.is_null());
+    socket_b
+        .event_select(event.cast(), FD_READ | FD_CLOSE)
+        .unwrap();
+
+    socket_a.send(DATA).unwrap();
+    thread::sleep(Duration::from_millis(10));
+    let events = socket_b.enum_network_events(Some(event.cast())).unwrap();
+    assert!(events.contains(FD_READ));
+    assert!(!events.contains(FD_WRITE));
+    assert!(events.error(FD_READ).is_none());
+
+    socket_b.event_select(event.cast(), 0).unwrap();
+    assert_ne!(unsafe { WSACloseEvent(event) }, 0);
+}
+
+#[test]
+#[cfg(all(windows, feature = "all"))]
+fn set_loopback_fast_path() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.set_loopback_fast_path(true).unwrap();
+    listener.bind(&any_ipv4()).unwrap();
+    listener.listen(1).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let addr = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), addr.as_socket().unwrap().port());
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket.set_loopback_fast_path(true).unwrap();
+    socket.connect(&addr.into()).unwrap();
+    let (accepted, _) = listener.accept().unwrap();
+    drop(accepted);
+}
+
+#[test]
+#[cfg(all(windows, feature = "all"))]
+fn set_tcp_initial_rto() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket
+        .set_tcp_initial_rto(Some(Duration::from_millis(200)), Some(1))
+        .unwrap();
+    socket.set_tcp_initial_rto(None, None).unwrap();
+}
+
+#[test]
+#[cfg(all(windows, feature = "all"))]
+fn conditional_accept() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert_eq!(socket.conditional_accept().unwrap(), false);
+    socket.set_conditional_accept(true).unwrap();
+    assert_eq!(socket.conditional_accept().unwrap(), true);
+}
+
+#[test]
+#[cfg(all(windows, feature = "all"))]
+fn accept_ex() {
+    use winapi::um::minwinbase::OVERLAPPED;
+    use winapi::um::winsock2::WSAGetOverlappedResult;
+
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_ipv4()).unwrap();
+    listener.listen(1).unwrap();
+    let port = listener.local_addr().unwrap().as_socket().unwrap().port();
+    let addr = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), port);
+
+    let accepted = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let mut buf = vec![MaybeUninit::new(0); DATA.len() + Socket::ACCEPT_EX_ADDRS_LEN];
+    let mut overlapped: OVERLAPPED = unsafe { std::mem::zeroed() };
+    let res = unsafe { listener.accept_ex(&accepted, &mut buf, &mut overlapped) }.unwrap();
+
+    let client = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    client.connect(&addr.into()).unwrap();
+    client.send(DATA).unwrap();
+
+    let n = match res {
+        Some(n) => n,
+        None => {
+            let mut n = 0;
+            let mut flags = 0;
+            let res = unsafe {
+                WSAGetOverlappedResult(
+                    listener.as_raw_socket() as _,
+                    &mut overlapped,
+                    &mut n,
+                    1,
+                    &mut flags,
+                )
+            };
+            assert_ne!(res, 0, "{}", io::Error::last_os_error());
+            n as usize
+        }
+    };
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, DATA);
+
+    accepted.update_accept_context(&listener).unwrap();
+    let (local, remote) = unsafe { listener.accept_ex_addrs(&buf) }.unwrap();
+    assert_eq!(local, accepted.local_addr().unwrap());
+    assert_eq!(remote, client.local_addr().unwrap());
+    assert_eq!(accepted.peer_addr().unwrap(), remote);
+}
+
+#[test]
+#[cfg(all(windows, feature = "all"))]
+fn connect_ex() {
+    use winapi::um::minwinbase::OVERLAPPED;
+    use winapi::um::winsock2::WSAGetOverlappedResult;
+
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_ipv4()).unwrap();
+    listener.listen(1).unwrap();
+    let port = listener.local_addr().unwrap().as_socket().unwrap().port();
+    let addr = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), port);
+
+    // Not bound, `connect_ex` takes care of that.
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let mut overlapped: OVERLAPPED = unsafe { std::mem::zeroed() };
+    let res = unsafe { socket.connect_ex(&addr.into(), DATA, &mut overlapped) }.unwrap();
+    let n = match res {
+        Some(n) => n,
+        None => {
+            let mut n = 0;
+            let mut flags = 0;
+            let res = unsafe {
+                WSAGetOverlappedResult(
+                    socket.as_raw_socket() as _,
+                    &mut overlapped,
+                    &mut n,
+                    1,
+                    &mut flags,
+                )
+            };
+            assert_ne!(res, 0, "{}", io::Error::last_os_error());
+            n as usize
+        }
+    };
+    assert_eq!(n, DATA.len());
+    socket.update_connect_context().unwrap();
+    assert_eq!(socket.peer_addr().unwrap().as_socket(), Some(addr));
+
+    let (accepted, _) = listener.accept().unwrap();
+    let mut buf = [MaybeUninit::new(0); DATA.len()];
+    let n = accepted.recv(&mut buf).unwrap();
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, DATA);
+}
+
+#[test]
+#[cfg(all(
+    feature = "all",
+    any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_os = "netbsd",
+        target_vendor = "apple",
+    )
+))]
+fn bytes_pending_send() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_ipv4()).unwrap();
+    listener.listen(1).unwrap();
+
+    let sender = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    sender.connect(&listener.local_addr().unwrap()).unwrap();
+    let (_receiver, _) = listener.accept().unwrap();
+    assert_eq!(sender.bytes_pending_send().unwrap(), 0);
+
+    sender.send(DATA).unwrap();
+    // The data should be acknowledged quickly over loopback.
+    for _ in 0..100 {
+        if sender.bytes_pending_send().unwrap() == 0 {
+            return;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+    panic!("send queue not drained");
+}
 
-    let mut the = [MaybeUninit::new(1); 3];
-    let mut wee = [MaybeUninit::new(2); 3];
-    let mut knight = [MaybeUninit::new(3); 6];
-    let mut would = [MaybeUninit::new(4); 5];
-    let mut yell = [MaybeUninit::new(5); 4];
-    let mut ow = [MaybeUninit::new(6); 2];
+#[test]
+#[cfg(not(target_os = "redox"))]
+fn recv_uninit() {
+    let (socket_a, socket_b) = udp_pair_connected();
 
-    let (received, flags) = socket_b
-        .recv_vectored(&mut [
-            MaybeUninitSlice::new(&mut the),
-            MaybeUninitSlice::new(&mut wee),
-            MaybeUninitSlice::new(&mut knight),
-            MaybeUninitSlice::new(&mut would),
-            MaybeUninitSlice::new(&mut yell),
-            MaybeUninitSlice::new(&mut ow),
-        ])
-        .unwrap();
-    assert_eq!(received, 23);
-    #[cfg(all(unix, not(target_os = "redox")))]
-    assert_eq!(flags.is_end_of_record(), false);
-    #[cfg(all(unix, not(target_os = "redox")))]
-    assert_eq!(flags.is_out_of_band(), false);
-    assert_eq!(flags.is_truncated(), false);
+    socket_a.send(DATA).unwrap();
+    let mut buf = [MaybeUninit::<u8>::uninit(); 64];
+    let n = socket_b.recv(&mut buf).unwrap();
+    assert_eq!(n, DATA.len());
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, DATA);
 
-    assert_eq!(unsafe { assume_init(&the) }, b"the");
-    assert_eq!(unsafe { assume_init(&wee) }, b"wee");
-    assert_eq!(unsafe { assume_init(&knight) }, b"knight");
-    assert_eq!(unsafe { assume_init(&would) }, b"would");
-    assert_eq!(unsafe { assume_init(&yell) }, b"yell");
-    assert_eq!(unsafe { assume_init(&ow) }, b"ow");
+    socket_a.send(DATA).unwrap();
+    let mut buf = [MaybeUninit::<u8>::uninit(); 64];
+    let (n, addr) = socket_b.recv_from(&mut buf).unwrap();
+    assert_eq!(n, DATA.len());
+    assert_eq!(
+        addr.as_socket_ipv6().unwrap(),
+        socket_a.local_addr().unwrap().as_socket_ipv6().unwrap()
+    );
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, DATA);
 }
 
 #[test]
 #[cfg(not(target_os = "redox"))]
-fn send_from_recv_to_vectored() {
-    let (socket_a, socket_b) = udp_pair_unconnected();
-    let addr_a = socket_a.local_addr().unwrap();
-    let addr_b = socket_b.local_addr().unwrap();
+fn send_recv_flags() {
+    let (socket_a, socket_b) = udp_pair_connected();
 
-    let sent = socket_a
-        .send_to_vectored(
-            &[
-                IoSlice::new(b"surgeon"),
-                IoSlice::new(b"has"),
-                IoSlice::new(b"menswear"),
-            ],
-            &addr_b,
-        )
+    let n = socket_a
+        .send_with_flags(DATA, SendFlags::DONT_ROUTE)
         .unwrap();
-    assert_eq!(sent, 18);
+    assert_eq!(n, DATA.len());
 
-    let mut surgeon = [MaybeUninit::new(10); 7];
-    let mut has = [MaybeUninit::new(11); 3];
-    let mut men = [MaybeUninit::new(12); 3];
-    let mut swear = [MaybeUninit::new(13); 5];
-    let (received, flags, addr) = socket_b
-        .recv_from_vectored(&mut [
-            MaybeUninitSlice::new(&mut surgeon),
-            MaybeUninitSlice::new(&mut has),
-            MaybeUninitSlice::new(&mut men),
-            MaybeUninitSlice::new(&mut swear),
-        ])
+    // Peeking shouldn't remove the data from the queue.
+    let mut buf = [MaybeUninit::<u8>::uninit(); 64];
+    let n = socket_b
+        .recv_with_flags(&mut buf, RecvOptions::PEEK)
+        .unwrap();
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, DATA);
+    let n = socket_b
+        .recv_with_flags(&mut buf, RecvOptions::default())
         .unwrap();
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, DATA);
 
-    assert_eq!(received, 18);
-    #[cfg(all(unix, not(target_os = "redox")))]
-    assert_eq!(flags.is_end_of_record(), false);
-    #[cfg(all(unix, not(target_os = "redox")))]
-    assert_eq!(flags.is_out_of_band(), false);
-    assert_eq!(flags.is_truncated(), false);
+    let mut flags = RecvOptions::PEEK;
+    flags |= RecvOptions::WAIT_ALL;
+    assert_eq!(flags, RecvOptions::PEEK | RecvOptions::WAIT_ALL);
     assert_eq!(
-        addr.as_socket_ipv6().unwrap(),
-        addr_a.as_socket_ipv6().unwrap()
+        c_int::from(flags),
+        c_int::from(RecvOptions::PEEK) | c_int::from(RecvOptions::WAIT_ALL)
     );
-
-    assert_eq!(unsafe { assume_init(&surgeon) }, b"surgeon");
-    assert_eq!(unsafe { assume_init(&has) }, b"has");
-    assert_eq!(unsafe { assume_init(&men) }, b"men");
-    assert_eq!(unsafe { assume_init(&swear) }, b"swear");
 }
 
 #[test]
 #[cfg(not(target_os = "redox"))]
-fn recv_vectored_truncated() {
+fn recv_msg_flags() {
     let (socket_a, socket_b) = udp_pair_connected();
 
-    let sent = socket_a
-        .send(b"do not feed the gremlins after midnight")
-        .unwrap();
-    assert_eq!(sent, 39);
+    socket_a.send(DATA).unwrap();
+    let mut buf = [MaybeUninit::<u8>::uninit(); 64];
+    let (n, flags) = socket_b.recv_msg_flags(&mut buf, 0).unwrap();
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, DATA);
+    assert!(!flags.is_truncated());
+
+    // Buffer too small for the datagram.
+    socket_a.send(DATA).unwrap();
+    let mut buf = [MaybeUninit::<u8>::uninit(); 4];
+    let (n, flags) = socket_b.recv_msg_flags(&mut buf, 0).unwrap();
+    assert_eq!(n, 4);
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, &DATA[..4]);
+    assert!(flags.is_truncated());
+}
 
-    let mut buffer = [MaybeUninit::new(20); 24];
+#[test]
+#[cfg(not(target_os = "redox"))]
+fn recv_flags_bits() {
+    let bits = 0x42;
+    let flags = RecvFlags::from_bits(bits);
+    assert_eq!(flags.bits(), bits);
+    assert_eq!(bits, c_int::from(flags));
+    assert_eq!(RecvFlags::from(bits), flags);
+    assert_eq!(RecvFlags::default().bits(), 0);
+}
 
-    let (received, flags) = socket_b
-        .recv_vectored(&mut [MaybeUninitSlice::new(&mut buffer)])
-        .unwrap();
-    assert_eq!(received, 24);
-    assert_eq!(flags.is_truncated(), true);
-    assert_eq!(unsafe { assume_init(&buffer) }, b"do not feed the gremlins");
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn send_more() {
+    let (socket_a, socket_b) = udp_pair_connected();
+
+    // Both parts are sent in a single datagram.
+    let flags = SendFlags::MORE;
+    assert_eq!(socket_a.send_with_flags(b"Hello ", flags).unwrap(), 6);
+    assert_eq!(socket_a.send(b"world").unwrap(), 5);
+
+    let mut buf = [MaybeUninit::<u8>::uninit(); 64];
+    let n = socket_b.recv(&mut buf).unwrap();
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, b"Hello world");
 }
 
 #[test]
-#[cfg(not(target_os = "redox"))]
-fn recv_from_vectored_truncated() {
-    let (socket_a, socket_b) = udp_pair_unconnected();
-    let addr_a = socket_a.local_addr().unwrap();
-    let addr_b = socket_b.local_addr().unwrap();
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn send_confirm() {
+    let (socket_a, socket_b) = udp_pair_connected();
 
-    let sent = socket_a
-        .send_to(b"do not feed the gremlins after midnight", &addr_b)
-        .unwrap();
-    assert_eq!(sent, 39);
+    let n = socket_a.send_with_flags(DATA, SendFlags::CONFIRM).unwrap();
+    assert_eq!(n, DATA.len());
 
-    let mut buffer = [MaybeUninit::new(30); 24];
+    let mut buf = [MaybeUninit::<u8>::uninit(); 64];
+    let n = socket_b.recv(&mut buf).unwrap();
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, DATA);
+}
 
-    let (received, flags, addr) = socket_b
-        .recv_from_vectored(&mut [MaybeUninitSlice::new(&mut buffer)])
-        .unwrap();
-    assert_eq!(received, 24);
-    assert_eq!(flags.is_truncated(), true);
-    assert_eq!(
-        addr.as_socket_ipv6().unwrap(),
-        addr_a.as_socket_ipv6().unwrap()
+#[test]
+fn raw_sockopt() {
+    #[cfg(unix)]
+    let (level, optname) = (libc::SOL_SOCKET, libc::SO_KEEPALIVE);
+    #[cfg(windows)]
+    let (level, optname) = (
+        winapi::um::winsock2::SOL_SOCKET,
+        winapi::um::winsock2::SO_KEEPALIVE,
     );
-    assert_eq!(unsafe { assume_init(&buffer) }, b"do not feed the gremlins");
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    unsafe { socket.setsockopt::<c_int>(level, optname, 1).unwrap() };
+    assert!(socket.keepalive().unwrap());
+    let keepalive = unsafe { socket.getsockopt::<c_int>(level, optname).unwrap() };
+    assert_ne!(keepalive, 0);
+
+    socket.set_keepalive(false).unwrap();
+    let keepalive = unsafe { socket.getsockopt::<c_int>(level, optname).unwrap() };
+    assert_eq!(keepalive, 0);
 }
 
 /// Create a pair of non-connected UDP sockets suitable for unit tests.
@@ -684,6 +2471,7 @@ fn tcp_keepalive() {
             target_os = "linux",
             target_os = "netbsd",
             target_vendor = "apple",
+            windows,
         )
     ))]
     let params = params.with_retries(10);
@@ -691,7 +2479,7 @@ fn tcp_keepalive() {
     // Set the parameters.
     socket.set_tcp_keepalive(&params).unwrap();
 
-    #[cfg(all(feature = "all", not(windows)))]
+    #[cfg(feature = "all")]
     assert_eq!(socket.keepalive_time().unwrap(), Duration::from_secs(200));
 
     #[cfg(all(
@@ -705,6 +2493,7 @@ fn tcp_keepalive() {
             target_os = "linux",
             target_os = "netbsd",
             target_vendor = "apple",
+            windows,
         )
     ))]
     assert_eq!(
@@ -723,6 +2512,7 @@ fn tcp_keepalive() {
             target_os = "linux",
             target_os = "netbsd",
             target_vendor = "apple",
+            windows,
         )
     ))]
     assert_eq!(socket.keepalive_retries().unwrap(), 10);
@@ -945,10 +2735,16 @@ fn r#type() {
     assert_eq!(socket.r#type().unwrap(), Type::SEQPACKET);
 }
 
+#[test]
+fn nodelay_default() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert_eq!(socket.nodelay().unwrap(), false);
+}
+
 #[cfg(all(feature = "all", target_os = "linux"))]
 #[test]
 fn cpu_affinity() {
-    let mut socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
 
     // NOTE: This requires at least 2 CPU cores.
     let cpu = socket.cpu_affinity().unwrap();
@@ -968,6 +2764,291 @@ unsafe fn assume_init(buf: &[MaybeUninit<u8>]) -> &[u8] {
     &*(buf as *const [MaybeUninit<u8>] as *const [u8])
 }
 
+#[test]
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+#[ignore = "setting `TCP_REPAIR` requires the `CAP_NET_ADMIN` capability (works when running as root)"]
+fn tcp_repair() {
+    use socket2::TcpRepairQueue;
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert!(!socket.tcp_repair().unwrap());
+    socket.set_tcp_repair(true).unwrap();
+    assert!(socket.tcp_repair().unwrap());
+
+    assert_eq!(socket.tcp_repair_queue().unwrap(), TcpRepairQueue::None);
+    socket.set_tcp_repair_queue(TcpRepairQueue::Send).unwrap();
+    assert_eq!(socket.tcp_repair_queue().unwrap(), TcpRepairQueue::Send);
+    socket.set_tcp_queue_seq(12345).unwrap();
+    assert_eq!(socket.tcp_queue_seq().unwrap(), 12345);
+
+    socket.set_tcp_repair(false).unwrap();
+    assert!(!socket.tcp_repair().unwrap());
+}
+
+#[test]
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+fn tcp_zerocopy_receive() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_ipv4()).unwrap();
+    listener.listen(1).unwrap();
+
+    let sender = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    sender.connect(&listener.local_addr().unwrap()).unwrap();
+    let (receiver, _) = listener.accept().unwrap();
+
+    sender.send(DATA).unwrap();
+    thread::sleep(Duration::from_millis(10));
+
+    let length = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let address = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            length,
+            libc::PROT_READ,
+            libc::MAP_SHARED,
+            receiver.as_raw_fd(),
+            0,
+        )
+    };
+    assert_ne!(address, libc::MAP_FAILED, "{}", io::Error::last_os_error());
+
+    // Less than a page of data can't be mapped, it must be read using `recv`.
+    let res = unsafe { receiver.tcp_zerocopy_receive(address.cast(), length as u32) }.unwrap();
+    assert_eq!(res.length(), 0);
+    assert_eq!(res.recv_skip_hint() as usize, DATA.len());
+
+    let mut buf = [MaybeUninit::new(0); DATA.len()];
+    let n = receiver.recv(&mut buf).unwrap();
+    assert_eq!(unsafe { assume_init(&buf[..n]) }, DATA);
+
+    unsafe { libc::munmap(address, length) };
+}
+
+#[test]
+fn buffer_size_too_large() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let size = c_int::MAX as usize + 1;
+    let err = socket.set_recv_buffer_size(size).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let err = socket.set_send_buffer_size(size).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+#[cfg(feature = "error-context")]
+fn context_error() {
+    use socket2::ContextError;
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+
+    // IPv6 options can't be used on IPv4 sockets.
+    let err = socket.only_v6().unwrap_err();
+    let code = err.raw_os_error();
+    let err = ContextError::from_last(err);
+    assert_eq!(err.operation(), Some("getsockopt"));
+    assert!(err.option().is_some());
+    assert_eq!(err.io_error().raw_os_error(), code);
+    #[cfg(unix)]
+    let on = format!(" on fd {}: ", socket.as_raw_fd());
+    #[cfg(windows)]
+    let on = format!(" on socket {}: ", socket.as_raw_socket());
+    let msg = err.to_string();
+    let expected = format!("getsockopt(IPPROTO_IPV6, IPV6_V6ONLY){}", on);
+    assert!(msg.starts_with(&expected), "{}", msg);
+
+    let err = socket.listen(128).unwrap_err();
+    let code = err.raw_os_error().unwrap();
+    let err = ContextError::from_last(err);
+    assert_eq!(err.operation(), Some("listen"));
+    assert_eq!(err.option(), None);
+    let msg = err.to_string();
+    assert!(msg.starts_with(&format!("listen{}", on)), "{}", msg);
+    // The context is consumed.
+    let err = ContextError::from_last(io::Error::from_raw_os_error(code));
+    assert_eq!(err.operation(), None);
+
+    // A successful system call clears the context.
+    socket.listen(128).unwrap_err();
+    socket.local_addr().unwrap();
+    let err = ContextError::from_last(io::Error::from_raw_os_error(code));
+    assert_eq!(err.operation(), None);
+
+    // Errors not created by socket2 don't get any context.
+    #[allow(clippy::io_other_error)] // `io::Error::other` requires Rust 1.74.
+    let err = ContextError::from_last(io::Error::new(io::ErrorKind::Other, "other"));
+    assert_eq!(err.operation(), None);
+    assert_eq!(err.to_string(), "other");
+    let err: io::Error = err.into();
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+}
+
+#[test]
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+#[ignore = "setting `SO_RCVBUFFORCE` requires the `CAP_NET_ADMIN` capability (works when running as root)"]
+fn buffer_size_force() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    // Larger than the default `rmem_max` and `wmem_max`.
+    const SIZE: usize = 64 * 1024 * 1024;
+    socket.set_recv_buffer_size_force(SIZE).unwrap();
+    assert_eq!(socket.recv_buffer_size().unwrap(), 2 * SIZE);
+    socket.set_send_buffer_size_force(SIZE).unwrap();
+    assert_eq!(socket.send_buffer_size().unwrap(), 2 * SIZE);
+}
+
+#[test]
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+fn passcred() {
+    let socket = Socket::new(Domain::UNIX, Type::DGRAM, None).unwrap();
+    assert!(!socket.passcred().unwrap());
+    socket.set_passcred(true).unwrap();
+    assert!(socket.passcred().unwrap());
+}
+
+#[test]
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+fn peer_security_context() {
+    let (socket, _) = Socket::pair(Domain::UNIX, Type::STREAM, None).unwrap();
+    match socket.peer_security_context() {
+        Ok(label) => assert!(!label.ends_with(&[0])),
+        // Returned if no security module provides a label.
+        Err(err) => assert_eq!(err.raw_os_error(), Some(libc::ENOPROTOOPT)),
+    }
+}
+
+#[test]
+#[cfg(all(feature = "all", target_os = "linux"))]
+fn cookie() {
+    let socket1 = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let socket2 = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let cookie = socket1.cookie().unwrap();
+    assert_ne!(cookie, 0);
+    assert_eq!(socket1.cookie().unwrap(), cookie);
+    assert_ne!(socket2.cookie().unwrap(), cookie);
+}
+
+#[test]
+#[cfg(all(feature = "all", target_os = "linux"))]
+fn incoming_napi_id() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    // No packets were received yet.
+    assert_eq!(socket.incoming_napi_id().unwrap(), 0);
+}
+
+#[test]
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+fn ip_options() {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    assert_eq!(socket.ip_options().unwrap(), &[]);
+    // Router alert option (RFC 2113).
+    let router_alert = [0x94, 0x04, 0x00, 0x00];
+    socket.set_ip_options(&router_alert).unwrap();
+    assert_eq!(socket.ip_options().unwrap(), &router_alert);
+    socket.set_ip_options(&[]).unwrap();
+    assert_eq!(socket.ip_options().unwrap(), &[]);
+}
+
+#[test]
+#[cfg(all(
+    feature = "all",
+    any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_vendor = "apple",
+    )
+))]
+fn icmpv6_filter_type() {
+    let mut filter = Icmpv6Filter::pass_all();
+    assert!(filter.will_pass(0) && filter.will_pass(128) && filter.will_pass(255));
+    filter.block(128);
+    assert!(filter.will_block(128));
+    assert!(filter.will_pass(129));
+    filter.pass(128);
+    assert_eq!(filter, Icmpv6Filter::pass_all());
+
+    let mut filter = Icmpv6Filter::block_all();
+    assert!(filter.will_block(0) && filter.will_block(129) && filter.will_block(255));
+    filter.pass(129);
+    assert!(filter.will_pass(129));
+    assert!(filter.will_block(128));
+}
+
+#[test]
+#[cfg(all(
+    feature = "all",
+    any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_vendor = "apple",
+    )
+))]
+#[ignore = "creating a raw socket requires the `CAP_NET_RAW` capability (works when running as root)"]
+fn icmpv6_filter() {
+    let socket = Socket::new(Domain::IPV6, Type::RAW, Some(Protocol::ICMPV6)).unwrap();
+    assert_eq!(socket.icmpv6_filter().unwrap(), Icmpv6Filter::pass_all());
+    let mut filter = Icmpv6Filter::block_all();
+    filter.pass(129); // Echo reply.
+    socket.set_icmpv6_filter(&filter).unwrap();
+    assert_eq!(socket.icmpv6_filter().unwrap(), filter);
+}
+
+#[test]
+fn linger_range() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket.set_linger(Some(Duration::ZERO)).unwrap();
+    assert_eq!(socket.linger().unwrap(), Some(Duration::ZERO));
+
+    let err = socket
+        .set_linger(Some(Duration::from_secs(u64::MAX)))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(socket.linger().unwrap(), Some(Duration::ZERO));
+}
+
+#[test]
+#[cfg(all(feature = "all", target_os = "freebsd"))]
+fn set_fib() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    // The default routing table always exists.
+    socket.set_fib(0).unwrap();
+}
+
+#[test]
+#[cfg(all(feature = "all", target_os = "freebsd"))]
+fn set_user_cookie() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket.set_user_cookie(123).unwrap();
+}
+
+#[test]
+#[cfg(all(feature = "all", target_os = "freebsd"))]
+fn set_accept_filter() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket.bind(&any_ipv4()).unwrap();
+    socket.listen(1).unwrap();
+
+    // Requires the `accf_data` kernel module.
+    match socket.set_accept_filter("dataready", "") {
+        Ok(()) => {}
+        Err(err) if err.raw_os_error() == Some(libc::ENOENT) => {}
+        Err(err) => panic!("unexpected error: {}", err),
+    }
+
+    let err = socket
+        .set_accept_filter("a_much_too_long_filter_name", "")
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+#[cfg(all(feature = "all", target_os = "android"))]
+fn bind_to_network() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    // `NETWORK_UNSPECIFIED` unbinds the socket, which always works.
+    socket.bind_to_network(0).unwrap();
+}
+
 /// Macro to create a simple test to set and get a socket option.
 macro_rules! test {
     // Test using the `arg`ument as expected return value.
@@ -1051,6 +3132,7 @@ test!(
     mark,
     set_mark(123)
 );
+
 test!(linger, set_linger(Some(Duration::from_secs(10))));
 test!(
     read_timeout,
@@ -1059,12 +3141,23 @@ test!(
 test!(keepalive, set_keepalive(true));
 #[cfg(all(feature = "all", any(target_os = "fuchsia", target_os = "linux")))]
 test!(freebind, set_freebind(true));
+#[cfg(all(
+    feature = "all",
+    any(target_os = "android", target_os = "fuchsia", target_os = "linux")
+))]
+test!(syn_count, set_syn_count(2));
+#[cfg(all(feature = "all", target_os = "linux"))]
+test!(max_pacing_rate, set_max_pacing_rate(1_000_000));
 
 test!(IPv4 ttl, set_ttl(40));
 #[cfg(not(windows))] // TODO: returns `WSAENOPROTOOPT` (10042) on Windows.
 test!(IPv4 broadcast, set_broadcast(true));
+#[cfg(all(feature = "all", any(target_os = "android", target_os = "linux")))]
+test!(IPv4 bind_address_no_port, set_bind_address_no_port(true));
 
 test!(IPv6 unicast_hops_v6, set_unicast_hops_v6(20));
+#[cfg(not(any(target_os = "fuchsia", target_os = "haiku", target_os = "redox")))]
+test!(IPv6 dontfrag_v6, set_dontfrag_v6(true));
 #[cfg(not(any(windows, target_os = "freebsd")))]
 test!(IPv6 only_v6, set_only_v6(true));
 // IPv6 socket are already IPv6 only on FreeBSD and Windows.